@@ -0,0 +1,39 @@
+//! Time to turn a window of samples into a frame, at a few window sizes.
+//! `cargo bench` runs it; the oscilloscope should stay close to flat as the window grows.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use sound_cows::scope::display::oscilloscope::Oscilloscope;
+use sound_cows::scope::display::spectroscope::Spectroscope;
+use sound_cows::scope::display::GraphConfig;
+use sound_cows::scope::widget::ScopeWidget;
+use sound_cows::scope::Matrix;
+
+fn tone(samples: u32) -> Matrix<f64> {
+    vec![(0..samples).map(|i| (i as f64 * 0.05).sin()).collect(); 2]
+}
+
+fn oscilloscope(c: &mut Criterion) {
+    let mut group = c.benchmark_group("oscilloscope");
+    let area = Rect::new(0, 0, 80, 24);
+    for samples in [2_000, 20_000, 200_000] {
+        let cfg = GraphConfig { samples, columns: 160, scale: 1.0, palette: vec![Color::White], ..Default::default() };
+        let data = tone(samples);
+        let mut scope = Oscilloscope::default();
+        let mut buf = Buffer::empty(area);
+        // Processing and drawing, as the RADIO tab does each frame
+        group.bench_with_input(BenchmarkId::from_parameter(samples), &data, |b, data| {
+            b.iter(|| ScopeWidget::new(&cfg, &mut scope, data).render(area, &mut buf))
+        });
+    }
+    group.finish();
+}
+
+fn spectroscope(c: &mut Criterion) {
+    let data = tone(2048);
+    let mut spectroscope = Spectroscope::default();
+    c.bench_function("spectroscope", |b| b.iter(|| spectroscope.process(&data, 44_100, 64).len()));
+}
+
+criterion_group!(benches, oscilloscope, spectroscope);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+//! The scope drawn into a plain ratatui app with `ScopeWidget`, fed a sine and a slower
+//! cosine instead of audio. `cargo run --example embed`, q quits.
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::style::Color;
+use ratatui::widgets::Block;
+use sound_cows::scope::display::oscilloscope::Oscilloscope;
+use sound_cows::scope::display::GraphConfig;
+use sound_cows::scope::widget::ScopeWidget;
+use sound_cows::scope::Matrix;
+
+const SAMPLE_RATE: u32 = 44_100;
+
+fn main() -> io::Result<()> {
+    let cfg = GraphConfig {
+        samples: 400,
+        sampling_rate: SAMPLE_RATE,
+        scale: 1.0,
+        references: true,
+        show_ui: true,
+        palette: vec![Color::Green, Color::Yellow],
+        labels_color: Color::Cyan,
+        axis_color: Color::DarkGray,
+        ..Default::default()
+    };
+    let mut scope = Oscilloscope::default();
+    let mut terminal = ratatui::init();
+    let mut start = 0usize;
+
+    let result = loop {
+        // Two channels, each a tone moving along a little every frame
+        let data: Matrix<f64> = [(440.0, 0.8), (220.0, 0.5)]
+            .iter()
+            .map(|(hz, gain)| {
+                (start..start + cfg.samples as usize)
+                    .map(|i| (i as f64 * hz / SAMPLE_RATE as f64 * std::f64::consts::TAU).sin() * gain)
+                    .collect()
+            })
+            .collect();
+        start += 37;
+
+        let drawn = terminal.draw(|f| {
+            let widget = ScopeWidget::new(&cfg, &mut scope, &data).block(Block::bordered().title(" embed (q quits) "));
+            f.render_widget(widget, f.area());
+        });
+        if let Err(e) = drawn {
+            break Err(e);
+        }
+        match event::poll(Duration::from_millis(30)) {
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.code == KeyCode::Char('q') => break Ok(()),
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            },
+            Ok(false) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::restore();
+    result
+}
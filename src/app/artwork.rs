@@ -0,0 +1,95 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+use image::imageops::FilterType;
+use image::RgbImage;
+use crate::app::state::AppEvent;
+use crate::audio::stream::is_offline;
+use crate::audio::tags::read_picture;
+
+// Covers are kept this many pixels square; drawing samples them down to whatever the panel has room for
+pub const ART_PIXELS: u32 = 48;
+// Thumbnails bigger than this aren't worth the wait
+const MAX_IMAGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Where the playing track's cover might be, tried in order
+pub struct ArtworkSource {
+    pub file: Option<PathBuf>,      // A picture in the file's ID3 tag
+    pub thumbnail: Option<String>,  // yt-dlp's thumbnail URL
+}
+
+/// Find, fetch and scale the cover on a background thread; AppEvent::ArtworkReady brings it back,
+/// tagged with `key` so a cover for a track that's no longer playing can be told apart
+pub fn load_async(key: String, source: ArtworkSource, tx: Sender<AppEvent>) {
+    thread::spawn(move || {
+        let bytes = source
+            .file
+            .as_deref()
+            .and_then(read_picture)
+            .or_else(|| source.thumbnail.as_deref().filter(|_| !is_offline()).and_then(|url| fetch(url).ok()));
+        let artwork = bytes.and_then(|bytes| decode(&bytes).ok());
+        let _ = tx.send(AppEvent::ArtworkReady(key, artwork));
+    });
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    let response = ureq::get(url).call().map_err(|e| format!("Artwork download failed: {}", e))?;
+    read_limited(response.into_reader(), MAX_IMAGE_BYTES)
+}
+
+// All of `reader`, unless that's more than `limit` bytes: half an image is no use
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Artwork download failed: {}", e))?;
+    if bytes.len() as u64 > limit {
+        return Err(format!("Artwork too large (over {} MB)", limit / 1024 / 1024));
+    }
+    Ok(bytes)
+}
+
+/// Decode any image and crop it to the middle square (video thumbnails are 16:9), scaled to ART_PIXELS
+pub fn decode(bytes: &[u8]) -> Result<RgbImage, String> {
+    let image = image::load_from_memory(bytes).map_err(|e| format!("Bad artwork: {}", e))?;
+    let side = image.width().min(image.height());
+    let square = image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side);
+    Ok(square.resize_exact(ART_PIXELS, ART_PIXELS, FilterType::Triangle).to_rgb8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb};
+    use std::io::Cursor;
+
+    fn png(width: u32, height: u32, color: impl Fn(u32, u32) -> [u8; 3]) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| Rgb(color(x, y)));
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn crops_wide_thumbnails_to_the_middle() {
+        // Red bars left and right of a blue square
+        let bytes = png(160, 90, |x, _| if (35..125).contains(&x) { [0, 0, 255] } else { [255, 0, 0] });
+        let art = decode(&bytes).unwrap();
+        assert_eq!(art.dimensions(), (ART_PIXELS, ART_PIXELS));
+        assert_eq!(art.get_pixel(0, 0).0, [0, 0, 255]);
+        assert_eq!(art.get_pixel(ART_PIXELS - 1, ART_PIXELS - 1).0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn rejects_what_isnt_an_image() {
+        assert!(decode(b"not an image").is_err());
+    }
+
+    #[test]
+    fn oversized_downloads_are_refused_not_cut_short() {
+        assert_eq!(read_limited(&[7u8; 10][..], 10).unwrap().len(), 10);
+        assert_eq!(read_limited(&[7u8; 11][..], 10).unwrap_err(), "Artwork too large (over 0 MB)");
+    }
+}
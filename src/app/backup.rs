@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::Path;
+use serde_derive::{Deserialize, Serialize};
+use super::favorites::{Favorite, Favorites};
+use super::history::{History, Play};
+use super::podcasts::{Feed, Podcasts};
+
+// Bump when the layout changes in a way older readers can't handle
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Everything worth keeping that isn't just cache: subscriptions, favorites and play history
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Backup {
+    pub version: u32,
+    #[serde(default)]
+    pub feeds: Vec<Feed>,
+    #[serde(default)]
+    pub favorites: Vec<Favorite>,
+    #[serde(default)]
+    pub history: Vec<Play>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct RestoreSummary {
+    pub feeds: usize,
+    pub favorites: usize,
+    pub plays: usize,
+    pub skipped: usize, // Already there
+}
+
+impl RestoreSummary {
+    pub fn describe(&self) -> String {
+        format!(
+            "Restored {} feeds, {} favorites, {} plays, {} skipped duplicates",
+            self.feeds, self.favorites, self.plays, self.skipped
+        )
+    }
+}
+
+impl Backup {
+    pub fn collect(podcasts: &Podcasts, favorites: &Favorites, history: &History) -> Self {
+        Backup {
+            version: BACKUP_VERSION,
+            feeds: podcasts.feeds.clone(),
+            favorites: favorites.items.clone(),
+            history: history.plays.clone(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Backup error: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let backup: Backup = serde_json::from_str(&contents).map_err(|e| format!("Not a backup file: {}", e))?;
+        if backup.version > BACKUP_VERSION {
+            return Err(format!("Backup version {} is newer than this build understands", backup.version));
+        }
+        Ok(backup)
+    }
+
+    /// Merge into the current library. Feeds match by URL (keeping episodes played in either),
+    /// favorites by URL, plays by URL and time.
+    pub fn merge_into(self, podcasts: &mut Podcasts, favorites: &mut Favorites, history: &mut History) -> RestoreSummary {
+        let mut summary = RestoreSummary::default();
+
+        for feed in self.feeds {
+            match podcasts.feeds.iter_mut().find(|f| f.url == feed.url) {
+                Some(existing) => {
+                    for episode in feed.episodes.iter().filter(|e| e.played) {
+                        if let Some(current) = existing.episodes.iter_mut().find(|e| e.url == episode.url) {
+                            current.played = true;
+                        }
+                    }
+                    summary.skipped += 1;
+                },
+                None => {
+                    podcasts.feeds.push(feed);
+                    summary.feeds += 1;
+                },
+            }
+        }
+
+        for favorite in self.favorites {
+            if favorites.is_favorite(&favorite.url) {
+                summary.skipped += 1;
+            } else {
+                favorites.items.push(favorite);
+                summary.favorites += 1;
+            }
+        }
+        favorites.items.sort_by_key(|f| f.added_at);
+
+        for play in self.history {
+            if history.plays.iter().any(|p| p.url == play.url && p.played_at == play.played_at) {
+                summary.skipped += 1;
+            } else {
+                history.plays.push(play);
+                summary.plays += 1;
+            }
+        }
+        history.plays.sort_by_key(|p| p.played_at);
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::podcasts::Episode;
+    use crate::testing::TempDir;
+
+    fn episode(url: &str, played: bool) -> Episode {
+        Episode { title: url.to_string(), url: url.to_string(), duration: None, published: None, played }
+    }
+
+    fn library() -> (Podcasts, Favorites, History) {
+        let podcasts = Podcasts {
+            feeds: vec![Feed {
+                url: "https://example.com/feed.xml".to_string(),
+                title: "Example".to_string(),
+                episodes: vec![episode("https://example.com/1.mp3", true), episode("https://example.com/2.mp3", false)],
+            }],
+            ..Default::default()
+        };
+        let favorites = Favorites {
+            items: vec![Favorite { url: "https://youtu.be/a".to_string(), title: "A".to_string(), added_at: 10 }],
+        };
+        let history = History {
+            plays: vec![
+                Play { url: "https://youtu.be/a".to_string(), title: "A".to_string(), played_at: 20, artist: None, listened_secs: 0 },
+                Play { url: "https://youtu.be/a".to_string(), title: "A".to_string(), played_at: 30, artist: None, listened_secs: 0 },
+            ],
+            ..Default::default()
+        };
+        (podcasts, favorites, history)
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let (podcasts, favorites, history) = library();
+        let dir = TempDir::new();
+        let path = dir.join("backup.json");
+        Backup::collect(&podcasts, &favorites, &history).write(&path).unwrap();
+        let restored = Backup::read(&path);
+
+        let (mut podcasts, mut favorites, mut history) = (Podcasts::default(), Favorites::default(), History::default());
+        let summary = restored.unwrap().merge_into(&mut podcasts, &mut favorites, &mut history);
+        assert_eq!(summary, RestoreSummary { feeds: 1, favorites: 1, plays: 2, skipped: 0 });
+        assert_eq!(podcasts.feeds[0].episodes.len(), 2);
+        assert!(podcasts.feeds[0].episodes[0].played);
+        assert_eq!(history.plays.len(), 2);
+    }
+
+    #[test]
+    fn restoring_twice_only_adds_duplicates_to_skipped() {
+        let (podcasts, favorites, history) = library();
+        let backup = || Backup::collect(&podcasts, &favorites, &history);
+
+        let (mut p, mut f, mut h) = (Podcasts::default(), Favorites::default(), History::default());
+        backup().merge_into(&mut p, &mut f, &mut h);
+        let summary = backup().merge_into(&mut p, &mut f, &mut h);
+        assert_eq!(summary, RestoreSummary { feeds: 0, favorites: 0, plays: 0, skipped: 4 });
+        assert_eq!((p.feeds.len(), f.items.len(), h.plays.len()), (1, 1, 2));
+    }
+
+    #[test]
+    fn keeps_episodes_played_on_either_side() {
+        let (mut podcasts, mut favorites, mut history) = library();
+        let mut backup = Backup::collect(&podcasts, &favorites, &history);
+        backup.feeds[0].episodes[1].played = true;
+        podcasts.feeds[0].episodes[0].played = true;
+
+        backup.merge_into(&mut podcasts, &mut favorites, &mut history);
+        assert!(podcasts.feeds[0].episodes.iter().all(|e| e.played));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let dir = TempDir::new();
+        let path = dir.join("backup.json");
+        fs::write(&path, r#"{"version": 99}"#).unwrap();
+        assert!(Backup::read(&path).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::env;
+use std::time::Duration;
+
+// How often the scope and other animations redraw unless --fps says otherwise
+const DEFAULT_FPS: u32 = 30;
+const MAX_FPS: u32 = 120;
+
+pub const USAGE: &str = "\
+Usage: sound_cows [OPTIONS]
+
+Options:
+  --cookies <PATH>                 Netscape cookies file for yt-dlp (age-restricted content)
+  --cookies-from-browser <BROWSER> Let yt-dlp read cookies from a browser (e.g. firefox)
+  --offline                        Start in offline mode (cached tracks only)
+  --no-color                       Draw without colors (also when $NO_COLOR is set)
+  --fps <N>                        Animation frame rate while playing, 1-120 (default 30)
+  --data-dir <PATH>                Where settings, feeds, favorites and history are kept
+                                   (default: $SOUND_COWS_DATA_DIR, then ~/.local/share/sound_cows)
+  --check                          Fix duplicates in the library and clean up the cache, then exit
+  -h, --help                       Show this help";
+
+// Command line flags; anything left as None falls back to the settings file
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub cookies: Option<String>,
+    pub cookies_from_browser: Option<String>,
+    pub offline: bool,
+    pub no_color: bool,
+    pub fps: Option<u32>,
+    pub data_dir: Option<String>,
+    pub check: bool,
+    pub help: bool,
+}
+
+impl CliArgs {
+    pub fn parse() -> Result<CliArgs, String> {
+        let mut cli = Self::parse_from(env::args().skip(1))?;
+        // https://no-color.org: any non-empty value
+        cli.no_color |= env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Ok(cli)
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+        let mut cli = CliArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{} needs a value", flag))
+            };
+
+            match flag.as_str() {
+                "--cookies" => cli.cookies = Some(value()?),
+                "--cookies-from-browser" => cli.cookies_from_browser = Some(value()?),
+                "--offline" => cli.offline = true,
+                "--no-color" => cli.no_color = true,
+                "--fps" => {
+                    let value = value()?;
+                    let fps = value.parse::<u32>().map_err(|_| format!("--fps needs a number, not {}", value))?;
+                    cli.fps = Some(fps.clamp(1, MAX_FPS));
+                }
+                "--data-dir" => cli.data_dir = Some(value()?),
+                "--check" => cli.check = true,
+                "-h" | "--help" => cli.help = true,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(cli)
+    }
+
+    /// Time between animation frames
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.fps.unwrap_or(DEFAULT_FPS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn each_flag_sets_its_field() {
+        let cli = parse(&["--cookies", "c.txt", "--cookies-from-browser=firefox", "--offline", "--no-color", "--data-dir", "/tmp/cows", "--check"]).unwrap();
+        assert_eq!(cli.cookies.as_deref(), Some("c.txt"));
+        assert_eq!(cli.cookies_from_browser.as_deref(), Some("firefox"));
+        assert!(cli.offline && cli.no_color && cli.check);
+        assert_eq!(cli.data_dir.as_deref(), Some("/tmp/cows"));
+        assert!(!cli.help && cli.fps.is_none());
+        assert!(parse(&["-h"]).unwrap().help);
+        assert!(parse(&["--help"]).unwrap().help);
+
+        let none = parse(&[]).unwrap();
+        assert!(!none.offline && !none.no_color && none.cookies.is_none());
+    }
+
+    #[test]
+    fn fps_is_a_number_kept_in_range() {
+        assert_eq!(parse(&["--fps", "60"]).unwrap().fps, Some(60));
+        assert_eq!(parse(&["--fps=500"]).unwrap().fps, Some(MAX_FPS));
+        assert_eq!(parse(&["--fps", "0"]).unwrap().fps, Some(1));
+        assert_eq!(parse(&["--fps", "fast"]).unwrap_err(), "--fps needs a number, not fast");
+        assert_eq!(parse(&["--fps", "60"]).unwrap().frame_interval(), Duration::from_secs(1) / 60);
+        assert_eq!(parse(&[]).unwrap().frame_interval(), Duration::from_secs(1) / DEFAULT_FPS);
+    }
+
+    #[test]
+    fn unknown_flags_and_missing_values_are_errors() {
+        assert_eq!(parse(&["--colour"]).unwrap_err(), "Unknown argument: --colour");
+        assert_eq!(parse(&["song.mp3"]).unwrap_err(), "Unknown argument: song.mp3");
+        assert_eq!(parse(&["--cookies"]).unwrap_err(), "--cookies needs a value");
+        assert_eq!(parse(&["--offline", "--fps"]).unwrap_err(), "--fps needs a value");
+        assert_eq!(parse(&["--data-dir"]).unwrap_err(), "--data-dir needs a value");
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+// How long a finished download's gauge stays up, full, before the view moves to RADIO
+const FLASH: Duration = Duration::from_millis(400);
+// Speeds measured over less than this jump around too much to show
+const MIN_SPEED_WINDOW: Duration = Duration::from_millis(500);
+
+/// The download being waited on, for the DATA tab's gauge
+#[derive(Debug, Clone)]
+pub struct Download {
+    pub title: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    started: Instant,
+    finished: Option<Instant>,
+}
+
+impl Download {
+    pub fn new(title: String) -> Self {
+        Download { title, downloaded: 0, total: None, started: Instant::now(), finished: None }
+    }
+
+    pub fn progressed(&mut self, downloaded: u64, total: Option<u64>) {
+        self.downloaded = downloaded;
+        self.total = total;
+    }
+
+    /// 0.0 to 1.0, None while the size isn't known. A finished download is always full.
+    pub fn ratio(&self) -> Option<f64> {
+        if self.finished.is_some() {
+            return Some(1.0);
+        }
+        self.total.filter(|t| *t > 0).map(|total| (self.downloaded as f64 / total as f64).min(1.0))
+    }
+
+    /// Average bytes per second since it started
+    pub fn speed(&self) -> Option<u64> {
+        let elapsed = self.finished.unwrap_or_else(Instant::now).duration_since(self.started);
+        (elapsed >= MIN_SPEED_WINDOW).then(|| (self.downloaded as f64 / elapsed.as_secs_f64()) as u64)
+    }
+
+    pub fn finish(&mut self) {
+        self.finished.get_or_insert_with(Instant::now);
+    }
+
+    /// Whether it finished and has been shown full for long enough
+    pub fn flash_over(&self) -> bool {
+        self.finished.is_some_and(|at| at.elapsed() >= FLASH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_needs_a_size_until_it_finishes() {
+        let mut download = Download::new("Song".to_string());
+        download.progressed(512, None);
+        assert_eq!(download.ratio(), None);
+        download.progressed(512, Some(2048));
+        assert_eq!(download.ratio(), Some(0.25));
+        download.finish();
+        assert_eq!(download.ratio(), Some(1.0));
+        assert!(!download.flash_over());
+    }
+
+    #[test]
+    fn speed_waits_for_a_steady_reading() {
+        let mut download = Download::new("Song".to_string());
+        download.progressed(1000, None);
+        assert_eq!(download.speed(), None);
+        download.started -= Duration::from_secs(2);
+        download.progressed(4000, None);
+        assert!(download.speed().is_some_and(|speed| (1900..=2000).contains(&speed)));
+    }
+}
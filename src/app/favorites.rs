@@ -0,0 +1,50 @@
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_derive::{Deserialize, Serialize};
+use super::paths::data_path;
+
+// Next to the settings and feeds files
+pub const FAVORITES_FILE: &str = "sound_cows_favorites.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Favorite {
+    pub url: String,
+    pub title: String,
+    pub added_at: u64, // Unix seconds
+}
+
+// Liked tracks, oldest first
+#[derive(Default)]
+pub struct Favorites {
+    pub items: Vec<Favorite>,
+}
+
+impl Favorites {
+    pub fn load() -> Self {
+        let items = fs::read_to_string(data_path(FAVORITES_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Favorites { items }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.items).map_err(|e| format!("Favorites error: {}", e))?;
+        fs::write(data_path(FAVORITES_FILE), json).map_err(|e| format!("Failed to save favorites: {}", e))
+    }
+
+    pub fn is_favorite(&self, url: &str) -> bool {
+        self.items.iter().any(|f| f.url == url)
+    }
+
+    /// Add or remove `url`. Returns true if it's a favorite now.
+    pub fn toggle(&mut self, url: &str, title: &str) -> bool {
+        if let Some(i) = self.items.iter().position(|f| f.url == url) {
+            self.items.remove(i);
+            return false;
+        }
+        let added_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.items.push(Favorite { url: url.to_string(), title: title.to_string(), added_at });
+        true
+    }
+}
@@ -0,0 +1,308 @@
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+use directories::UserDirs;
+use ratatui::widgets::ListState;
+use super::fuzzy::fuzzy_match;
+use super::input::TextInput;
+use super::nav::ListMove;
+use super::state::AppEvent;
+
+// What rodio (with the symphonia aac/mp4 features) can open
+const AUDIO_EXTENSIONS: [&str; 8] = ["mp3", "m4a", "mp4", "aac", "flac", "ogg", "wav", "opus"];
+// Adding a whole directory stops here, so a misplaced `a` on / doesn't walk the whole disk
+const MAX_COLLECTED: usize = 5_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+impl DirEntry {
+    pub fn is_audio(&self) -> bool {
+        !self.is_dir && is_audio(&self.path)
+    }
+}
+
+pub fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.iter().any(|a| a.eq_ignore_ascii_case(ext)))
+}
+
+/// The configured music directory, else the platform's (~/Music), else the working directory
+pub fn music_dir(configured: Option<&str>) -> PathBuf {
+    configured
+        .map(PathBuf::from)
+        .or_else(|| UserDirs::new().map(|dirs| dirs.audio_dir().map_or_else(|| dirs.home_dir().join("Music"), Path::to_path_buf)))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+// Directories first, then files, each by name ignoring case
+fn sort_entries(entries: &mut [DirEntry]) {
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+}
+
+/// Everything in `dir` but hidden files
+pub fn list_dir(dir: &Path) -> Result<Vec<DirEntry>, String> {
+    let read = fs::read_dir(dir).map_err(|e| format!("Can't open {}: {}", dir.display(), e))?;
+    let mut entries: Vec<DirEntry> = read
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            // Follows symlinks, so a linked music folder opens like any other
+            let is_dir = entry.path().is_dir();
+            Some(DirEntry { name, path: entry.path(), is_dir })
+        })
+        .collect();
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// Audio files under `path` (itself, if it's one), in browsing order
+pub fn collect_audio(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return if is_audio(path) { vec![path.to_path_buf()] } else { Vec::new() };
+    }
+    let mut files = Vec::new();
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = list_dir(&dir) else { continue };
+        // Pushed in reverse so subdirectories come out in order
+        for entry in entries.iter().rev().filter(|e| e.is_dir) {
+            stack.push(entry.path.clone());
+        }
+        files.extend(entries.into_iter().filter(DirEntry::is_audio).map(|e| e.path));
+        if files.len() >= MAX_COLLECTED {
+            files.truncate(MAX_COLLECTED);
+            break;
+        }
+    }
+    files
+}
+
+/// The `/` filter over the listed directory
+#[derive(Default)]
+pub struct FileFilter {
+    pub input: TextInput,
+    matches: Vec<(usize, Vec<usize>)>, // Entry index and the chars of its name that matched, best first
+    before: Option<usize>,             // Entry selected before filtering, selected again when it's cleared
+}
+
+/// The MAP tab: one directory at a time, listed from a background thread
+#[derive(Default)]
+pub struct FileBrowserState {
+    pub cwd: PathBuf,
+    pub entries: Vec<DirEntry>,
+    pub list_state: ListState, // Indexes the rows shown, which are the filter's matches while there is one
+    pub loading: bool,
+    pub filter: Option<FileFilter>,
+    offset: usize, // First row drawn; only the rows on screen are built each frame
+    select_after_load: Option<PathBuf>, // Going up selects the directory we came out of
+}
+
+impl FileBrowserState {
+    pub fn new(cwd: PathBuf) -> Self {
+        FileBrowserState { cwd, ..Default::default() }
+    }
+
+    /// List `dir` in the background; AppEvent::DirectoryListed brings the entries back
+    pub fn open(&mut self, dir: PathBuf, tx: Sender<AppEvent>) {
+        self.loading = true;
+        self.filter = None;
+        self.cwd = dir.clone();
+        thread::spawn(move || {
+            let result = list_dir(&dir);
+            let _ = tx.send(AppEvent::DirectoryListed(dir, result));
+        });
+    }
+
+    pub fn open_parent(&mut self, tx: Sender<AppEvent>) {
+        let Some(parent) = self.cwd.parent().map(Path::to_path_buf) else { return };
+        self.select_after_load = Some(self.cwd.clone());
+        self.open(parent, tx);
+    }
+
+    /// Entries for `dir`, unless the user has already moved on somewhere else
+    pub fn listed(&mut self, dir: PathBuf, entries: Vec<DirEntry>) {
+        if dir != self.cwd {
+            return;
+        }
+        self.loading = false;
+        let came_from = self.select_after_load.take();
+        let selected = came_from.and_then(|path| entries.iter().position(|e| e.path == path)).unwrap_or(0);
+        self.entries = entries;
+        self.filter = None;
+        self.offset = 0;
+        self.list_state.select((!self.entries.is_empty()).then_some(selected));
+    }
+
+    /// How many rows are shown: every entry, or the filter's matches
+    pub fn row_count(&self) -> usize {
+        self.filter.as_ref().map_or(self.entries.len(), |f| f.matches.len())
+    }
+
+    // Which entry the `row`th row shows
+    fn entry_index(&self, row: usize) -> Option<usize> {
+        match &self.filter {
+            Some(filter) => filter.matches.get(row).map(|(i, _)| *i),
+            None => (row < self.entries.len()).then_some(row),
+        }
+    }
+
+    pub fn selected(&self) -> Option<&DirEntry> {
+        self.entries.get(self.entry_index(self.list_state.selected()?)?)
+    }
+
+    /// The entries of `rows`, each with the chars of its name the filter matched
+    pub fn shown(&self, rows: Range<usize>) -> Vec<(&DirEntry, &[usize])> {
+        match &self.filter {
+            Some(filter) => filter.matches[rows].iter().map(|(i, chars)| (&self.entries[*i], chars.as_slice())).collect(),
+            None => self.entries[rows].iter().map(|entry| (entry, &[][..])).collect(),
+        }
+    }
+
+    /// Select the row showing `path`, if one does. Returns whether it did.
+    pub fn select_path(&mut self, path: &str) -> bool {
+        let row = (0..self.row_count()).find(|&row| self.entry_index(row).is_some_and(|i| self.entries[i].path.to_string_lossy() == path));
+        if let Some(row) = row {
+            self.list_state.select(Some(row));
+        }
+        row.is_some()
+    }
+
+    /// Open the filter, or go back to editing the one that's open
+    pub fn start_filter(&mut self) {
+        if self.filter.is_none() {
+            self.filter = Some(FileFilter { before: self.list_state.selected(), ..Default::default() });
+            self.filter_changed();
+        }
+    }
+
+    /// Match the entries against the filter's text again, selecting the best match
+    pub fn filter_changed(&mut self) {
+        let Some(filter) = &mut self.filter else { return };
+        let query = filter.input.as_str();
+        let mut scored: Vec<(i32, usize, Vec<usize>)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| fuzzy_match(query, &entry.name).map(|(score, chars)| (score, i, chars)))
+            .collect();
+        // Best first; equally good ones keep the directory's order
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        filter.matches = scored.into_iter().map(|(_, i, chars)| (i, chars)).collect();
+        self.offset = 0;
+        self.list_state.select((!filter.matches.is_empty()).then_some(0));
+    }
+
+    /// Show every entry again, with the selection from before filtering
+    pub fn clear_filter(&mut self) {
+        if let Some(filter) = self.filter.take() {
+            self.list_state.select(filter.before);
+        }
+    }
+
+    /// First row on screen, as of the last frame
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Select the `index`th entry, if there is one. Returns whether it did.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.row_count() {
+            return false;
+        }
+        self.list_state.select(Some(index));
+        true
+    }
+
+    pub fn move_selection(&mut self, movement: ListMove, page: usize) {
+        if let Some(i) = movement.apply(self.list_state.selected(), self.row_count(), page) {
+            self.list_state.select(Some(i));
+        }
+    }
+
+    /// The rows to draw in a list `rows` high, scrolled just enough to keep the selection in view
+    pub fn window(&mut self, rows: usize) -> Range<usize> {
+        let selected = self.list_state.selected().unwrap_or(0);
+        let rows = rows.max(1);
+        if selected < self.offset {
+            self.offset = selected;
+        } else if selected >= self.offset + rows {
+            self.offset = selected + 1 - rows;
+        }
+        let end = (self.offset + rows).min(self.row_count());
+        self.offset.min(end)..end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, is_dir: bool) -> DirEntry {
+        DirEntry { name: name.to_string(), path: PathBuf::from(name), is_dir }
+    }
+
+    #[test]
+    fn directories_come_first() {
+        let mut entries = vec![entry("b.mp3", false), entry("Zed", true), entry("A.flac", false), entry("alpha", true)];
+        sort_entries(&mut entries);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["alpha", "Zed", "A.flac", "b.mp3"]);
+    }
+
+    #[test]
+    fn knows_audio_by_extension() {
+        assert!(is_audio(Path::new("song.MP3")));
+        assert!(is_audio(Path::new("/music/a b/track.opus")));
+        assert!(!is_audio(Path::new("cover.jpg")));
+        assert!(!is_audio(Path::new("README")));
+    }
+
+    #[test]
+    fn filtered_rows_map_back_to_their_entries() {
+        let names = ["Abbey Road", "Blue.mp3", "moonlight.flac", "Harvest Moon.mp3", "notes.txt"];
+        let mut browser = FileBrowserState { entries: names.iter().map(|n| entry(n, false)).collect(), ..Default::default() };
+        browser.list_state.select(Some(1));
+        browser.start_filter();
+        "moon".chars().for_each(|c| browser.filter.as_mut().unwrap().input.insert(c));
+        browser.filter_changed();
+
+        assert_eq!(browser.row_count(), 2);
+        assert_eq!(browser.selected().unwrap().name, "moonlight.flac");
+        assert!(browser.select(1));
+        assert_eq!(browser.selected().unwrap().name, "Harvest Moon.mp3");
+        assert!(!browser.select(2));
+        assert_eq!(browser.shown(0..2)[1].1, [8, 9, 10, 11]);
+        assert!(browser.select_path("moonlight.flac"));
+        assert_eq!(browser.list_state.selected(), Some(0));
+
+        // Clearing it puts back the full list and the selection from before
+        browser.clear_filter();
+        assert_eq!(browser.row_count(), 5);
+        assert_eq!(browser.selected().unwrap().name, "Blue.mp3");
+    }
+
+    #[test]
+    fn window_follows_the_selection() {
+        let mut browser = FileBrowserState { entries: (0..100).map(|i| entry(&i.to_string(), false)).collect(), ..Default::default() };
+        browser.list_state.select(Some(0));
+        assert_eq!(browser.window(10), 0..10);
+        browser.list_state.select(Some(15));
+        assert_eq!(browser.window(10), 6..16);
+        // Moving back up inside the window doesn't scroll
+        browser.list_state.select(Some(8));
+        assert_eq!(browser.window(10), 6..16);
+        browser.list_state.select(Some(99));
+        assert_eq!(browser.window(10), 90..100);
+    }
+}
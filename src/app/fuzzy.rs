@@ -0,0 +1,65 @@
+// Matches one after another read like the word that was typed
+const RUN_BONUS: i32 = 5;
+// As do matches at the start of a word ("dsotm" for "Dark Side of the Moon")
+const WORD_START_BONUS: i32 = 3;
+
+fn same_letter(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Whether `query`'s chars all appear in `text` in order, ignoring case. If so, returns a score
+/// (higher is a better match) and the char positions in `text` that matched, for highlighting.
+/// Each char is taken at its first chance, which is simple and close enough for file names.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let mut wanted = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(&want) = wanted.peek() else { break };
+        if same_letter(c, want) {
+            score += 1;
+            if positions.last().is_some_and(|&last| last + 1 == i) {
+                score += RUN_BONUS;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += WORD_START_BONUS;
+            }
+            positions.push(i);
+            wanted.next();
+        } else if !positions.is_empty() {
+            // Gaps between matches count against it; skipping to the first one doesn't
+            score -= 1;
+        }
+        previous = Some(c);
+    }
+
+    wanted.peek().is_none().then_some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_in_order_ignoring_case() {
+        assert_eq!(fuzzy_match("adm", "Abba - Dancing Queen.mp3").map(|m| m.1), Some(vec![0, 7, 21]));
+        assert_eq!(fuzzy_match("ÉTÉ", "été.flac").map(|m| m.1), Some(vec![0, 1, 2]));
+        assert!(fuzzy_match("mda", "Abba - Dancing Queen.mp3").is_none());
+        assert!(fuzzy_match("x", "").is_none());
+    }
+
+    #[test]
+    fn an_empty_query_matches_everything() {
+        assert_eq!(fuzzy_match("", "anything"), Some((0, vec![])));
+        assert_eq!(fuzzy_match(" ", "anything"), Some((0, vec![])));
+    }
+
+    #[test]
+    fn runs_and_word_starts_score_higher() {
+        let score = |text| fuzzy_match("moon", text).unwrap().0;
+        assert!(score("Moon River.mp3") > score("my own ongoing noise.mp3"));
+        assert!(score("Harvest Moon.mp3") > score("harmonious tones.mp3"));
+    }
+}
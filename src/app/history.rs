@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use ratatui::widgets::ListState;
+use serde_derive::{Deserialize, Serialize};
+use super::nav::ListMove;
+use super::paths::data_path;
+
+// Next to the settings and feeds files
+pub const HISTORY_FILE: &str = "sound_cows_history.json";
+// Oldest plays are dropped past this many
+const MAX_PLAYS: usize = 10_000;
+// How many tracks the STAT tab's most played list shows
+pub const HISTORY_LIST_LEN: usize = 10;
+// The recently played list goes further back
+pub const RECENT_LIST_LEN: usize = 50;
+pub const ARTIST_LIST_LEN: usize = 10;
+pub const DAY_SECS: u64 = 86_400;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Play {
+    pub url: String,
+    pub title: String,
+    pub played_at: u64, // Unix seconds
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub listened_secs: u64, // Time actually spent playing it, pauses and skipped-over parts left out
+}
+
+// A track with its play count, for the "most played" list
+#[derive(Debug, Clone)]
+pub struct TopTrack {
+    pub url: String,
+    pub title: String,
+    pub play_count: usize,
+    pub last_played_at: u64,
+}
+
+// Which STAT list Up/Down/Enter act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HistoryFocus {
+    #[default]
+    Recent,
+    Top,
+    Artists,
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Snapshots are numbered as they're taken, so a write that runs late never lands over a newer one
+static TAKEN: AtomicU64 = AtomicU64::new(0);
+static WRITTEN: Mutex<u64> = Mutex::new(0);
+// The one thread save_async hands snapshots to
+static WRITER: OnceLock<Sender<(u64, Vec<Play>)>> = OnceLock::new();
+
+fn next_snapshot() -> u64 {
+    TAKEN.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+fn write_plays(snapshot: u64, plays: &[Play]) -> Result<(), String> {
+    let mut written = WRITTEN.lock().unwrap_or_else(PoisonError::into_inner);
+    if *written > snapshot {
+        return Ok(());
+    }
+    let json = serde_json::to_string(plays).map_err(|e| format!("History error: {}", e))?;
+    replace_file(&data_path(HISTORY_FILE), &json).map_err(|e| format!("Failed to save history: {}", e))?;
+    *written = snapshot;
+    Ok(())
+}
+
+// Written next to `path` and renamed over it, so a reader sees the old file or the new one, never half of either
+fn replace_file(path: &Path, contents: &str) -> io::Result<()> {
+    let partial = path.with_extension("json.part");
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, path).inspect_err(|_| {
+        let _ = fs::remove_file(&partial);
+    })
+}
+
+fn writer() -> &'static Sender<(u64, Vec<Play>)> {
+    WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<(u64, Vec<Play>)>();
+        thread::spawn(move || {
+            while let Ok(mut latest) = rx.recv() {
+                // Only the newest of whatever queued up while the last write ran is worth writing
+                while let Ok(newer) = rx.try_recv() {
+                    latest = newer;
+                }
+                // Nowhere to report this from here; the next play writes the whole list again anyway
+                let _ = write_plays(latest.0, &latest.1);
+            }
+        });
+        tx
+    })
+}
+
+// Every track that got past the first ~30 seconds, oldest first
+#[derive(Default)]
+pub struct History {
+    pub plays: Vec<Play>,
+    pub recent_state: ListState,
+    pub top_state: ListState,
+    pub artist_state: ListState,
+    pub focus: HistoryFocus,
+}
+
+impl History {
+    pub fn load() -> Self {
+        let plays = fs::read_to_string(data_path(HISTORY_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        let mut history = History { plays, ..Default::default() };
+        history.recent_state.select(Some(0));
+        history.top_state.select(Some(0));
+        history.artist_state.select(Some(0));
+        history
+    }
+
+    /// Append a play and write the file from a background thread
+    pub fn record(&mut self, url: String, title: String, artist: Option<String>, listened_secs: u64) {
+        self.plays.push(Play { url, title, played_at: now_secs(), artist, listened_secs });
+        if self.plays.len() > MAX_PLAYS {
+            self.plays.drain(..self.plays.len() - MAX_PLAYS);
+        }
+        self.save_async();
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        write_plays(next_snapshot(), &self.plays)
+    }
+
+    /// Write the whole history from the background writer thread
+    pub fn save_async(&self) {
+        let _ = writer().send((next_snapshot(), self.plays.clone()));
+    }
+
+    /// Latest play of each of the last `limit` distinct tracks, newest first
+    pub fn recent_unique(&self, limit: usize) -> Vec<&Play> {
+        let mut recent: Vec<&Play> = Vec::new();
+        for play in self.plays.iter().rev() {
+            if recent.len() == limit {
+                break;
+            }
+            if !recent.iter().any(|p| p.url == play.url) {
+                recent.push(play);
+            }
+        }
+        recent
+    }
+
+    /// Most played tracks, ties going to the more recently played one
+    pub fn top_played(&self, limit: usize) -> Vec<TopTrack> {
+        let mut tracks: HashMap<&str, TopTrack> = HashMap::new();
+        for play in &self.plays {
+            let track = tracks.entry(&play.url).or_insert_with(|| TopTrack {
+                url: play.url.clone(),
+                title: play.title.clone(),
+                play_count: 0,
+                last_played_at: 0,
+            });
+            track.play_count += 1;
+            track.last_played_at = track.last_played_at.max(play.played_at);
+        }
+        let mut tracks: Vec<TopTrack> = tracks.into_values().collect();
+        tracks.sort_by(|a, b| b.play_count.cmp(&a.play_count).then(b.last_played_at.cmp(&a.last_played_at)));
+        tracks.truncate(limit);
+        tracks
+    }
+
+    /// Artists by number of plays, ties going to the more recently played one. Plays without one are left out.
+    pub fn top_artists(&self, limit: usize) -> Vec<(String, usize)> {
+        let mut artists: HashMap<&str, (usize, u64)> = HashMap::new();
+        for play in &self.plays {
+            let Some(artist) = play.artist.as_deref().filter(|a| !a.is_empty()) else { continue };
+            let entry = artists.entry(artist).or_default();
+            entry.0 += 1;
+            entry.1 = entry.1.max(play.played_at);
+        }
+        let mut artists: Vec<(&str, (usize, u64))> = artists.into_iter().collect();
+        artists.sort_by(|a, b| b.1.0.cmp(&a.1.0).then(b.1.1.cmp(&a.1.1)));
+        artists.into_iter().take(limit).map(|(artist, (count, _))| (artist.to_string(), count)).collect()
+    }
+
+    /// Total listening time of the plays started at or after `since`
+    pub fn listened_since(&self, since: u64) -> u64 {
+        self.plays.iter().filter(|p| p.played_at >= since).map(|p| p.listened_secs).sum()
+    }
+
+    /// Count `secs` more listening towards the latest play, if it's still `url` playing
+    pub fn add_listened(&mut self, url: &str, secs: u64) {
+        if let Some(play) = self.plays.last_mut().filter(|p| p.url == url) {
+            play.listened_secs += secs;
+        }
+    }
+
+    /// (title, url) of the selected entry in the focused track list; None while the artists are focused
+    pub fn selected(&self) -> Option<(String, String)> {
+        match self.focus {
+            HistoryFocus::Top => {
+                let track = self.top_played(HISTORY_LIST_LEN).into_iter().nth(self.top_state.selected()?)?;
+                Some((track.title, track.url))
+            }
+            HistoryFocus::Recent => {
+                let play = self.recent_unique(RECENT_LIST_LEN).into_iter().nth(self.recent_state.selected()?)?;
+                Some((play.title.clone(), play.url.clone()))
+            }
+            HistoryFocus::Artists => None,
+        }
+    }
+
+    /// (title, url) of every track by the selected artist, most played first
+    pub fn selected_artist_tracks(&self) -> Vec<(String, String)> {
+        let Some((artist, _)) = self.artist_state.selected().and_then(|i| self.top_artists(ARTIST_LIST_LEN).into_iter().nth(i)) else {
+            return Vec::new();
+        };
+        self.top_played(usize::MAX)
+            .into_iter()
+            .filter(|track| self.plays.iter().any(|p| p.url == track.url && p.artist.as_deref() == Some(artist.as_str())))
+            .map(|track| (track.title, track.url))
+            .collect()
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            HistoryFocus::Recent => HistoryFocus::Top,
+            HistoryFocus::Top => HistoryFocus::Artists,
+            HistoryFocus::Artists => HistoryFocus::Recent,
+        };
+    }
+
+    /// How many entries one of the lists has
+    pub fn list_len(&self, focus: HistoryFocus) -> usize {
+        match focus {
+            HistoryFocus::Top => self.top_played(HISTORY_LIST_LEN).len(),
+            HistoryFocus::Recent => self.recent_unique(RECENT_LIST_LEN).len(),
+            HistoryFocus::Artists => self.top_artists(ARTIST_LIST_LEN).len(),
+        }
+    }
+
+    /// The selected row of one of the lists
+    pub fn selected_in(&self, focus: HistoryFocus) -> Option<usize> {
+        match focus {
+            HistoryFocus::Top => self.top_state.selected(),
+            HistoryFocus::Recent => self.recent_state.selected(),
+            HistoryFocus::Artists => self.artist_state.selected(),
+        }
+    }
+
+    // Length and selection of one of the lists
+    fn list(&mut self, focus: HistoryFocus) -> (usize, &mut ListState) {
+        let len = self.list_len(focus);
+        let state = match focus {
+            HistoryFocus::Top => &mut self.top_state,
+            HistoryFocus::Recent => &mut self.recent_state,
+            HistoryFocus::Artists => &mut self.artist_state,
+        };
+        (len, state)
+    }
+
+    /// Focus `focus` and select its `index`th entry, if it has one. Returns whether it did.
+    pub fn select(&mut self, focus: HistoryFocus, index: usize) -> bool {
+        self.focus = focus;
+        let (len, state) = self.list(focus);
+        if index >= len {
+            return false;
+        }
+        state.select(Some(index));
+        true
+    }
+
+    /// Move the selection in the focused list, `page` rows at a time for page moves
+    pub fn move_selection(&mut self, movement: ListMove, page: usize) {
+        let (len, state) = self.list(self.focus);
+        if let Some(i) = movement.apply(state.selected(), len, page) {
+            state.select(Some(i));
+        }
+    }
+}
+
+/// "just now", "5m ago", "2h ago", "yesterday", "3d ago"
+pub fn format_ago(now: u64, then: u64) -> String {
+    let secs = now.saturating_sub(then);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86_400 => format!("{}h ago", secs / 3600),
+        86_400..172_800 => "yesterday".to_string(),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn formats_minutes_and_hours() {
+        assert_eq!(format_ago(NOW, NOW), "just now");
+        assert_eq!(format_ago(NOW, NOW - 59), "just now");
+        assert_eq!(format_ago(NOW, NOW - 60), "1m ago");
+        assert_eq!(format_ago(NOW, NOW - 3599), "59m ago");
+        assert_eq!(format_ago(NOW, NOW - 3600), "1h ago");
+        assert_eq!(format_ago(NOW, NOW - 2 * 3600 - 59), "2h ago");
+    }
+
+    #[test]
+    fn formats_days() {
+        assert_eq!(format_ago(NOW, NOW - 86_399), "23h ago");
+        assert_eq!(format_ago(NOW, NOW - 86_400), "yesterday");
+        assert_eq!(format_ago(NOW, NOW - 172_799), "yesterday");
+        assert_eq!(format_ago(NOW, NOW - 172_800), "2d ago");
+        // Clock went backwards
+        assert_eq!(format_ago(NOW, NOW + 100), "just now");
+    }
+
+    #[test]
+    fn replacing_a_longer_file_leaves_nothing_of_it() {
+        let dir = TempDir::new();
+        let path = dir.join(HISTORY_FILE);
+        replace_file(&path, "[1, 2, 3, 4, 5]").unwrap();
+        replace_file(&path, "[6]").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[6]");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "no partial file left behind");
+    }
+
+    #[test]
+    fn recent_lists_each_track_once() {
+        let play = |url: &str, played_at| Play { url: url.to_string(), title: url.to_string(), played_at, artist: None, listened_secs: 0 };
+        let history = History { plays: vec![play("a", 1), play("b", 2), play("a", 3), play("c", 4)], ..Default::default() };
+        let urls: Vec<&str> = history.recent_unique(50).iter().map(|p| p.url.as_str()).collect();
+        assert_eq!(urls, ["c", "a", "b"]);
+        assert_eq!(history.recent_unique(2).len(), 2);
+    }
+
+    #[test]
+    fn artists_and_listening_time_add_up() {
+        let play = |url: &str, artist: Option<&str>, played_at, listened_secs| Play {
+            url: url.to_string(),
+            title: url.to_string(),
+            played_at,
+            artist: artist.map(str::to_string),
+            listened_secs,
+        };
+        let mut history = History {
+            plays: vec![
+                play("a", Some("Alice"), 10, 100),
+                play("b", Some("Bob"), 20, 200),
+                play("c", Some("Bob"), 30, 300),
+                play("d", None, 40, 400),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(history.top_artists(10), [("Bob".to_string(), 2), ("Alice".to_string(), 1)]);
+        assert_eq!(history.listened_since(20), 900);
+
+        history.add_listened("d", 50);
+        history.add_listened("a", 999); // Not the latest play
+        assert_eq!(history.listened_since(0), 1050);
+
+        history.artist_state.select(Some(0));
+        let bob: Vec<String> = history.selected_artist_tracks().into_iter().map(|(_, url)| url).collect();
+        assert_eq!(bob, ["c", "b"]);
+    }
+}
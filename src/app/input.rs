@@ -0,0 +1,152 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// One line of editable text. The cursor counts chars, never bytes, so accents, CJK and emoji
+/// can be typed and deleted anywhere in the line.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextInput {
+    text: String,
+    cursor: usize, // Chars before the cursor
+}
+
+impl TextInput {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, with the cursor at its end
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.chars().count();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    // Where the `chars`th char starts in the String
+    fn byte_index(&self, chars: usize) -> usize {
+        self.text.char_indices().nth(chars).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Remove the char left of the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let at = self.byte_index(self.cursor);
+        self.text.remove(at);
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    /// Terminal cells before the cursor, wide chars counting two
+    pub fn cursor_column(&self) -> usize {
+        self.text[..self.byte_index(self.cursor)].width()
+    }
+
+    /// The text split around the cursor (before, char under it, after), with enough dropped
+    /// from the left that the cursor fits in `width` cells. At the end of the text it's one cell wide.
+    pub fn visible(&self, width: usize) -> (&str, Option<char>, &str) {
+        let at = self.byte_index(self.cursor);
+        let mut rest = self.text[at..].chars();
+        let under = rest.next();
+        let cursor_cells = under.and_then(|c| c.width()).unwrap_or(1).max(1);
+
+        let mut start = 0;
+        let mut column = self.cursor_column();
+        let mut chars = self.text[..at].chars();
+        while column + cursor_cells > width {
+            let Some(c) = chars.next() else { break };
+            start += c.len_utf8();
+            column -= c.width().unwrap_or(0);
+        }
+        (&self.text[start..at], under, rest.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::default();
+        text.chars().for_each(|c| input.insert(c));
+        input
+    }
+
+    #[test]
+    fn inserts_multibyte_chars_mid_line() {
+        let mut input = typed("cafe");
+        input.move_left();
+        input.insert('é');
+        assert_eq!(input.as_str(), "cafée");
+        input.move_left();
+        input.move_left();
+        input.insert('🐄');
+        assert_eq!(input.as_str(), "ca🐄fée");
+        assert_eq!(input.cursor, 3);
+    }
+
+    #[test]
+    fn backspace_removes_whole_chars() {
+        let mut input = typed("日本語");
+        input.move_left();
+        input.backspace();
+        assert_eq!(input.as_str(), "日語");
+        input.backspace();
+        assert_eq!(input.as_str(), "語");
+        // Nothing left of the cursor
+        input.backspace();
+        assert_eq!(input.as_str(), "語");
+        assert_eq!(input.cursor, 0);
+    }
+
+    #[test]
+    fn cursor_stays_inside_the_text() {
+        let mut input = typed("ab");
+        input.move_right();
+        assert_eq!(input.cursor, 2);
+        input.clear();
+        input.move_left();
+        assert_eq!(input.cursor, 0);
+        input.set("ñandú");
+        assert_eq!(input.cursor, 5);
+    }
+
+    #[test]
+    fn cursor_column_counts_cells() {
+        let mut input = typed("a日本");
+        assert_eq!(input.cursor_column(), 5);
+        input.move_left();
+        assert_eq!(input.cursor_column(), 3);
+        input.set("é🐄");
+        assert_eq!(input.cursor_column(), 3);
+    }
+
+    #[test]
+    fn visible_scrolls_to_keep_the_cursor_in_view() {
+        let input = typed("hello");
+        assert_eq!(input.visible(10), ("hello", None, ""));
+        assert_eq!(input.visible(4), ("llo", None, ""));
+
+        let mut input = typed("日本語");
+        input.move_left();
+        // "日本" is 4 cells and the cursor on 語 two more
+        assert_eq!(input.visible(6), ("日本", Some('語'), ""));
+        assert_eq!(input.visible(5), ("本", Some('語'), ""));
+    }
+}
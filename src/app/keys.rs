@@ -0,0 +1,368 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use super::state::InputMode;
+use super::tab::Tab;
+
+// Where a binding applies. Tab contexts only cover Normal mode; the input modes get their own.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyContext {
+    Global,
+    Stat,
+    Inv,
+    Data,
+    SearchInput,
+    Results,
+    Map,
+    Files,
+    FileFilter,
+    Radio,
+    TextInput,
+    Confirm,
+}
+
+impl KeyContext {
+    pub fn title(&self) -> &'static str {
+        match self {
+            KeyContext::Global => "GLOBAL",
+            KeyContext::Stat => "STAT",
+            KeyContext::Inv => "INV / PODCASTS",
+            KeyContext::Data => "DATA / SEARCH",
+            KeyContext::SearchInput => "SEARCH INPUT",
+            KeyContext::Results => "SEARCH RESULTS",
+            KeyContext::Map => "MAP",
+            KeyContext::Files => "FILE BROWSER",
+            KeyContext::FileFilter => "FILE FILTER",
+            KeyContext::Radio => "RADIO / SCOPE",
+            KeyContext::TextInput => "TEXT INPUT",
+            KeyContext::Confirm => "CONFIRM",
+        }
+    }
+}
+
+pub struct Binding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub context: KeyContext,
+    pub description: &'static str,
+    pub hint: bool, // Shown in the footer too, not just the help overlay
+}
+
+impl Binding {
+    const fn hint(mut self) -> Self {
+        self.hint = true;
+        self
+    }
+
+    /// How the key is written in the help overlay, e.g. "Shift+↑" or "F"
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "Bksp".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            other => other.to_string(),
+        };
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            format!("Shift+{}", key)
+        } else if self.modifiers.contains(KeyModifiers::CONTROL) {
+            format!("Ctrl+{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+const fn key(code: KeyCode, context: KeyContext, description: &'static str) -> Binding {
+    Binding { code, modifiers: KeyModifiers::NONE, context, description, hint: false }
+}
+
+const fn with(modifiers: KeyModifiers, code: KeyCode, context: KeyContext, description: &'static str) -> Binding {
+    Binding { code, modifiers, context, description, hint: false }
+}
+
+use KeyCode::{Backspace, Char, Down, End, Enter, Esc, Home, Left, PageDown, PageUp, Right, Up};
+use KeyContext::{Confirm, Data, FileFilter, Files, Global, Inv, Map, Radio, Results, SearchInput, Stat, TextInput};
+
+/// Every binding the key handlers in main.rs and the scope displays know about, in the order the
+/// help lists them. A test checks the character keys against the handlers' source.
+pub const BINDINGS: &[Binding] = &[
+    key(Char('?'), Global, "Help").hint(),
+    key(Left, Global, "Switch tab").hint(),
+    key(Right, Global, "Switch tab").hint(),
+    key(KeyCode::Tab, Global, "Next tab"),
+    key(Char('1'), Global, "Go to STAT"),
+    key(Char('2'), Global, "Go to INV"),
+    key(Char('3'), Global, "Go to DATA"),
+    key(Char('4'), Global, "Go to MAP"),
+    key(Char('5'), Global, "Go to RADIO"),
+    key(Up, Global, "Previous station"),
+    key(Down, Global, "Next station"),
+    key(PageUp, Global, "Page up the list"),
+    key(PageDown, Global, "Page down the list"),
+    key(Home, Global, "First in the list"),
+    key(End, Global, "Last in the list"),
+    key(Char('+'), Global, "Volume up"),
+    key(Char('-'), Global, "Volume down"),
+    key(Char('.'), Global, "Next chapter"),
+    key(Char(','), Global, "Previous chapter"),
+    key(Char('f'), Global, "Favorite selection / current track"),
+    key(Char('o'), Global, "Toggle offline mode"),
+    key(Char('i'), Global, "Details of the playing track"),
+    key(Esc, Global, "Cancel download"),
+    key(Char('q'), Global, "Quit").hint(),
+
+    key(Up, Stat, "Select history entry").hint(),
+    key(Down, Stat, "Select history entry").hint(),
+    key(Char('h'), Stat, "Switch recent / most played / artists").hint(),
+    key(Enter, Stat, "Play history entry").hint(),
+    key(Char('b'), Stat, "Cycle audio quality"),
+    key(Char('k'), Stat, "Toggle SponsorBlock"),
+    key(Char('t'), Stat, "Next color theme"),
+    key(Char('m'), Stat, "Toggle monochrome"),
+    key(Char('c'), Stat, "Clear cache"),
+    key(Char('v'), Stat, "Check library"),
+
+    key(Up, Inv, "Select feed / episode").hint(),
+    key(Down, Inv, "Select feed / episode").hint(),
+    key(Enter, Inv, "Open feed / play episode").hint(),
+    key(Esc, Inv, "Back to feeds").hint(),
+    key(Char('/'), Inv, "Search library").hint(),
+    key(Char('a'), Inv, "Add feed"),
+    key(Char('d'), Inv, "Remove feed"),
+    key(Char('r'), Inv, "Refresh feeds"),
+    key(Char('x'), Inv, "Export list to M3U"),
+    key(Char('b'), Inv, "Back up library"),
+    key(Char('l'), Inv, "Restore library"),
+
+    key(Char('/'), Data, "Start typing a search or URL").hint(),
+    key(Enter, SearchInput, "Search / play URL").hint(),
+    with(KeyModifiers::CONTROL, Enter, SearchInput, "Search, skipping the cache"),
+    key(KeyCode::Tab, SearchInput, "Next search provider").hint(),
+    key(Up, SearchInput, "Older search"),
+    key(Down, SearchInput, "Newer search"),
+    key(Esc, SearchInput, "Stop typing").hint(),
+
+    key(Up, Results, "Select result").hint(),
+    key(Down, Results, "Select result").hint(),
+    key(Enter, Results, "Download and play").hint(),
+    key(Char('s'), Results, "Stream").hint(),
+    key(Char('f'), Results, "Favorite result").hint(),
+    key(Char('d'), Results, "Sort by duration"),
+    key(Char('t'), Results, "Sort by title"),
+    key(Char('h'), Results, "Hide results under 1:00"),
+    key(Char('i'), Results, "Result details"),
+    key(Esc, Results, "Close results").hint(),
+
+    key(Enter, Map, "Browse files").hint(),
+    key(Up, Files, "Select file").hint(),
+    key(Down, Files, "Select file").hint(),
+    key(Enter, Files, "Open directory / play file").hint(),
+    key(Backspace, Files, "Parent directory").hint(),
+    key(Char('a'), Files, "Queue file / directory").hint(),
+    key(Char('/'), Files, "Filter by name").hint(),
+    key(Char(' '), Files, "Pause / resume"),
+    key(Left, Files, "Switch tab"),
+    key(Right, Files, "Switch tab"),
+    key(Char('1'), Files, "Go to tab 1-5"),
+    key(Esc, Files, "Clear filter / stop browsing").hint(),
+
+    key(Up, FileFilter, "Select match").hint(),
+    key(Down, FileFilter, "Select match").hint(),
+    key(Enter, FileFilter, "Open / play match").hint(),
+    key(Esc, FileFilter, "Clear filter").hint(),
+    key(Backspace, FileFilter, "Delete character"),
+
+    key(Char('u'), Radio, "Focus stations / up next").hint(),
+    key(Enter, Radio, "Play station / jump to queued track").hint(),
+    key(Char('a'), Radio, "Add station").hint(),
+    key(Char('e'), Radio, "Edit station"),
+    key(Char('c'), Radio, "Cycle station color"),
+    key(Char('d'), Radio, "Delete station / remove from queue"),
+    key(Char('i'), Radio, "Details of queued / playing track"),
+    key(Char(' '), Radio, "Pause / resume").hint(),
+    key(Char('x'), Radio, "Stop"),
+    key(Char('r'), Radio, "Time played / left"),
+    key(Char('m'), Radio, "Mic on / off").hint(),
+    key(Char('R'), Radio, "Start / stop mic recording"),
+    key(Char('M'), Radio, "Left / right or mid / side"),
+    key(Char('D'), Radio, "Scope DC block"),
+    key(Char('b'), Radio, "Scope braille dots"),
+    key(Char('g'), Radio, "Scope reference lines"),
+    key(Char('U'), Radio, "Scope axis labels"),
+    key(Char(']'), Radio, "Scope gain up, first channel"),
+    key(Char('['), Radio, "Scope gain down, first channel"),
+    key(Char('}'), Radio, "Scope gain up, second channel"),
+    key(Char('{'), Radio, "Scope gain down, second channel"),
+    with(KeyModifiers::ALT, Char('1'), Radio, "Solo channel (Alt+number)"),
+    with(KeyModifiers::SHIFT, Char('1'), Radio, "Mute channel (Shift+number)"),
+    key(Char('s'), Radio, "Toggle scatter"),
+    key(Char('w'), Radio, "Phosphor trail length"),
+    with(KeyModifiers::CONTROL, Char('s'), Radio, "Save scope snapshot (WAV + CSV)"),
+    key(Char('v'), Radio, "Waveform / spectrum / both / vectors / waterfall").hint(),
+    with(KeyModifiers::SHIFT, Up, Radio, "Scale up"),
+    with(KeyModifiers::SHIFT, Down, Radio, "Scale down"),
+    with(KeyModifiers::SHIFT, Right, Radio, "More samples"),
+    with(KeyModifiers::SHIFT, Left, Radio, "Fewer samples"),
+    key(Char('t'), Radio, "Toggle triggering"),
+    with(KeyModifiers::ALT, Up, Radio, "Raise trigger threshold"),
+    with(KeyModifiers::ALT, Down, Radio, "Lower trigger threshold"),
+    key(Char('E'), Radio, "Trigger on rising / falling edge"),
+    key(Char('p'), Radio, "Toggle peak hold"),
+    key(Char('l'), Radio, "Spectrum log / linear bands"),
+    // The waveform's trigger depth; + and - turn the volume at the same time
+    key(Char('='), Radio, "Trigger depth up"),
+    key(Char('+'), Radio, "Trigger depth up by 10"),
+    key(Char('-'), Radio, "Trigger depth down"),
+    key(Char('_'), Radio, "Trigger depth down by 10"),
+    // On RADIO the page keys are the scope's, not the station list's
+    key(PageUp, Radio, "Raise trigger threshold"),
+    key(PageDown, Radio, "Lower trigger threshold"),
+
+    key(Enter, TextInput, "Confirm").hint(),
+    key(Esc, TextInput, "Cancel").hint(),
+    key(Left, TextInput, "Move cursor left"),
+    key(Right, TextInput, "Move cursor right"),
+    key(Backspace, TextInput, "Delete character"),
+
+    key(Char('y'), Confirm, "Yes").hint(),
+    key(Enter, Confirm, "Yes"),
+    key(Char('n'), Confirm, "No").hint(),
+    key(Esc, Confirm, "No").hint(),
+];
+
+pub fn bindings_for(context: KeyContext) -> impl Iterator<Item = &'static Binding> {
+    BINDINGS.iter().filter(move |b| b.context == context)
+}
+
+/// The context whose keys work right now, besides the global ones in Normal mode
+pub fn context_for(tab: Tab, mode: &InputMode) -> KeyContext {
+    match mode {
+        InputMode::Normal => match tab {
+            Tab::Stat => KeyContext::Stat,
+            Tab::Inv => KeyContext::Inv,
+            Tab::Data => KeyContext::Data,
+            Tab::Map => KeyContext::Map,
+            Tab::Radio => KeyContext::Radio,
+        },
+        InputMode::Editing => KeyContext::SearchInput,
+        InputMode::SearchResults => KeyContext::Results,
+        InputMode::FileBrowser => KeyContext::Files,
+        InputMode::FileFilter => KeyContext::FileFilter,
+        InputMode::Confirm => KeyContext::Confirm,
+        _ => KeyContext::TextInput,
+    }
+}
+
+/// (keys, description) for the footer: the hinted bindings of `contexts`,
+/// with neighbours that share a description folded into one entry ("↑↓ Select result", "N/Esc No")
+pub fn hints(contexts: &[KeyContext]) -> Vec<(String, &'static str)> {
+    let mut hints: Vec<(String, &'static str)> = Vec::new();
+    for binding in contexts.iter().flat_map(|c| bindings_for(*c)).filter(|b| b.hint) {
+        match hints.last_mut() {
+            Some((keys, description)) if *description == binding.description => {
+                // Arrows read fine run together, anything else gets a slash
+                let arrow = matches!(binding.code, Up | Down | Left | Right) && binding.modifiers.is_empty();
+                if !arrow {
+                    keys.push('/');
+                }
+                keys.push_str(&binding.label());
+            }
+            _ => hints.push((binding.label(), binding.description)),
+        }
+    }
+    hints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_keys_the_way_the_ui_writes_them() {
+        assert_eq!(key(Char('f'), Global, "").label(), "F");
+        assert_eq!(key(Char(' '), Radio, "").label(), "Space");
+        assert_eq!(with(KeyModifiers::SHIFT, Up, Radio, "").label(), "Shift+↑");
+        assert_eq!(with(KeyModifiers::CONTROL, Enter, Data, "").label(), "Ctrl+Enter");
+    }
+
+    #[test]
+    fn footer_hints_fold_arrow_pairs() {
+        let hints = hints(&[Results]);
+        assert_eq!(hints[0], ("↑↓".to_string(), "Select result"));
+        assert!(hints.iter().all(|(keys, _)| !keys.is_empty()));
+        assert!(!hints.iter().any(|(_, description)| *description == "Sort by title"));
+    }
+
+    #[test]
+    fn footer_hints_slash_other_keys() {
+        assert_eq!(hints(&[Confirm]), vec![("Y".to_string(), "Yes"), ("N/Esc".to_string(), "No")]);
+    }
+
+    // Each `KeyCode::Char(..)` arm in the handlers' source: the chars it matches and the tab it's guarded to
+    fn handler_arms() -> Vec<(Vec<char>, Option<KeyContext>)> {
+        let sources = [include_str!("../main.rs"), include_str!("../scope/display/oscilloscope.rs"), include_str!("../scope/display/spectroscope.rs")];
+        let mut arms = Vec::new();
+        for source in sources {
+            for (at, _) in source.match_indices("KeyCode::Char(") {
+                let rest = &source[at + "KeyCode::Char(".len()..];
+                let Some(end) = rest.find(')') else { continue };
+                let literals: Vec<char> = rest[..end].split('\'').skip(1).step_by(2).filter_map(|s| s.chars().next()).collect();
+                let chars = match literals.as_slice() {
+                    [from, to] if rest[..end].contains("..=") => (*from..=*to).collect(),
+                    // 'y' | 'Y' is one key as far as the help is concerned
+                    _ => literals.iter().copied().filter(|c| !(c.is_ascii_uppercase() && literals.contains(&c.to_ascii_lowercase()))).collect(),
+                };
+                let tab = rest[end + 1..].strip_prefix(" if app.current_tab == Tab::").map(|guard| {
+                    match guard.split(|c: char| !c.is_alphanumeric()).next() {
+                        Some("Stat") => Stat,
+                        Some("Inv") => Inv,
+                        Some("Data") => Data,
+                        Some("Map") => Map,
+                        _ => Radio,
+                    }
+                });
+                arms.push((chars, tab));
+            }
+        }
+        arms
+    }
+
+    #[test]
+    fn the_table_matches_the_handlers() {
+        let arms = handler_arms();
+        let documented = |c: char, context: Option<KeyContext>| {
+            BINDINGS.iter().any(|b| b.code == Char(c) && context.is_none_or(|context| b.context == context))
+        };
+        for (chars, tab) in &arms {
+            for c in chars {
+                assert!(documented(*c, *tab), "{:?} is handled on {:?} but not in BINDINGS", c, tab);
+            }
+        }
+        for binding in BINDINGS {
+            if let Char(c) = binding.code {
+                assert!(arms.iter().any(|(chars, _)| chars.contains(&c)), "{:?} is in BINDINGS but nothing handles it", c);
+            }
+        }
+    }
+
+    #[test]
+    fn no_key_is_bound_twice_in_one_context() {
+        for (i, a) in BINDINGS.iter().enumerate() {
+            for b in &BINDINGS[i + 1..] {
+                assert!(
+                    !(a.context == b.context && a.code == b.code && a.modifiers == b.modifiers),
+                    "{} bound twice in {:?}",
+                    a.label(),
+                    a.context
+                );
+            }
+        }
+    }
+}
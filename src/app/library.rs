@@ -0,0 +1,36 @@
+use super::favorites::Favorites;
+use super::history::History;
+use super::podcasts::Podcasts;
+
+// Something saved somewhere in the library that matched a search
+#[derive(Debug, Clone)]
+pub struct LibraryHit {
+    pub title: String,
+    pub url: String,
+    pub source: String, // Favorites, a feed title, or History
+}
+
+/// Case-insensitive match on title or URL across favorites, feed episodes and play history.
+/// A track found in several places is listed once, under the first of those.
+pub fn search(query: &str, podcasts: &Podcasts, favorites: &Favorites, history: &History) -> Vec<LibraryHit> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let favorites = favorites.items.iter().map(|f| (&f.title, &f.url, "Favorites"));
+    let episodes = podcasts
+        .feeds
+        .iter()
+        .flat_map(|feed| feed.episodes.iter().map(move |e| (&e.title, &e.url, feed.title.as_str())));
+    let plays = history.plays.iter().rev().map(|p| (&p.title, &p.url, "History"));
+
+    let mut hits: Vec<LibraryHit> = Vec::new();
+    for (title, url, source) in favorites.chain(episodes).chain(plays) {
+        let matches = title.to_lowercase().contains(&query) || url.to_lowercase().contains(&query);
+        if matches && !hits.iter().any(|h| &h.url == url) {
+            hits.push(LibraryHit { title: title.clone(), url: url.clone(), source: source.to_string() });
+        }
+    }
+    hits
+}
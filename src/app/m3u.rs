@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+use crate::audio::cache;
+use crate::audio::stream::audio_quality;
+
+// One line pair of an extended M3U
+pub struct M3uEntry {
+    pub title: String,
+    pub url: String,
+    pub duration: Option<f64>,
+}
+
+// EXTINF titles run to the end of the line, so only line breaks need to go
+fn clean_title(title: &str) -> String {
+    title.split(['\r', '\n']).map(str::trim).filter(|part| !part.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Render an extended M3U. `local` maps a URL to a file to list instead, when there is one.
+pub fn render(entries: &[M3uEntry], local: impl Fn(&str) -> Option<(String, Option<f64>)>) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for entry in entries {
+        let (location, cached_duration) = match local(&entry.url) {
+            Some((path, duration)) => (path, duration),
+            None => (entry.url.clone(), None),
+        };
+        // -1 is "unknown" to every player that reads these
+        let seconds = entry.duration.or(cached_duration).map_or(-1, |d| d.round() as i64);
+        out.push_str(&format!("#EXTINF:{},{}\n{}\n", seconds, clean_title(&entry.title), location));
+    }
+    out
+}
+
+// The cached file's absolute path, plus the duration from its metadata sidecar
+fn cached_location(url: &str) -> Option<(String, Option<f64>)> {
+    let path = cache::find_cached(url, audio_quality())?;
+    let duration = cache::read_metadata(&path).and_then(|m| m.duration);
+    let absolute = fs::canonicalize(&path).ok()?;
+    Some((absolute.to_string_lossy().into_owned(), duration))
+}
+
+/// Write `entries` to `path`, pointing at cached files where possible
+pub fn export(entries: &[M3uEntry], path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, render(entries, cached_location)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, url: &str, duration: Option<f64>) -> M3uEntry {
+        M3uEntry { title: title.to_string(), url: url.to_string(), duration }
+    }
+
+    #[test]
+    fn writes_header_and_extinf_lines() {
+        let entries = [
+            entry("Song, with a comma", "https://youtu.be/a", Some(225.4)),
+            entry("Two\nlines", "https://youtu.be/b", None),
+        ];
+        let out = render(&entries, |_| None);
+        assert_eq!(
+            out,
+            "#EXTM3U\n#EXTINF:225,Song, with a comma\nhttps://youtu.be/a\n#EXTINF:-1,Two lines\nhttps://youtu.be/b\n"
+        );
+    }
+
+    #[test]
+    fn prefers_cached_files() {
+        let entries = [entry("Cached", "https://youtu.be/a", None)];
+        let out = render(&entries, |_| Some(("/music/cache/abc.mp3".to_string(), Some(61.0))));
+        assert_eq!(out, "#EXTM3U\n#EXTINF:61,Cached\n/music/cache/abc.mp3\n");
+    }
+}
@@ -0,0 +1,140 @@
+use crate::audio::cache;
+use crate::format::format_bytes;
+use super::favorites::Favorite;
+use super::history::Play;
+use super::podcasts::Feed;
+
+// What a library check found and fixed
+#[derive(Debug, Default, PartialEq)]
+pub struct CheckReport {
+    pub feed_duplicates: usize, // Feeds subscribed twice, or episodes listed twice in a feed
+    pub favorite_duplicates: usize,
+    pub history_fixes: usize, // Duplicate plays, plus whether the order needed fixing
+    pub cache_orphans: usize,
+    pub reclaimed: u64,
+}
+
+impl CheckReport {
+    pub fn library_changed(&self) -> bool {
+        self.feed_duplicates + self.favorite_duplicates + self.history_fixes > 0
+    }
+
+    pub fn describe(&self) -> String {
+        if *self == CheckReport::default() {
+            return "Library check: nothing to fix".to_string();
+        }
+        format!(
+            "Fixed {} feed duplicates, {} favorite duplicates, {} history problems; removed {} orphaned cache files, reclaimed {}",
+            self.feed_duplicates,
+            self.favorite_duplicates,
+            self.history_fixes,
+            self.cache_orphans,
+            format_bytes(self.reclaimed)
+        )
+    }
+}
+
+/// Merge feeds subscribed twice and drop episodes listed twice. Returns how many were dropped.
+pub fn dedupe_feeds(feeds: &mut Vec<Feed>) -> usize {
+    let mut fixed = 0;
+    let mut kept: Vec<Feed> = Vec::with_capacity(feeds.len());
+    for feed in feeds.drain(..) {
+        match kept.iter_mut().find(|f| f.url == feed.url) {
+            Some(existing) => {
+                existing.episodes.extend(feed.episodes);
+                fixed += 1;
+            },
+            None => kept.push(feed),
+        }
+    }
+    for feed in &mut kept {
+        let before = feed.episodes.len();
+        let mut seen: Vec<String> = Vec::new();
+        feed.episodes.retain(|e| {
+            let new = !seen.contains(&e.url);
+            seen.push(e.url.clone());
+            new
+        });
+        fixed += before - feed.episodes.len();
+    }
+    *feeds = kept;
+    fixed
+}
+
+/// Keep the first favorite for each URL
+pub fn dedupe_favorites(favorites: &mut Vec<Favorite>) -> usize {
+    let before = favorites.len();
+    let mut seen: Vec<String> = Vec::new();
+    favorites.retain(|f| {
+        let new = !seen.contains(&f.url);
+        seen.push(f.url.clone());
+        new
+    });
+    before - favorites.len()
+}
+
+/// Put plays back in time order and drop exact duplicates (same URL, same second)
+pub fn repair_history(plays: &mut Vec<Play>) -> usize {
+    let unsorted = plays.windows(2).any(|w| w[0].played_at > w[1].played_at);
+    plays.sort_by_key(|p| p.played_at);
+    let before = plays.len();
+    plays.dedup_by(|a, b| a.url == b.url && a.played_at == b.played_at);
+    before - plays.len() + usize::from(unsorted)
+}
+
+/// Run every check, fixing what it finds in place
+pub fn check_library(feeds: &mut Vec<Feed>, favorites: &mut Vec<Favorite>, plays: &mut Vec<Play>) -> CheckReport {
+    let (cache_orphans, reclaimed) = cache::remove_orphan_sidecars();
+    CheckReport {
+        feed_duplicates: dedupe_feeds(feeds),
+        favorite_duplicates: dedupe_favorites(favorites),
+        history_fixes: repair_history(plays),
+        cache_orphans,
+        reclaimed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::podcasts::Episode;
+
+    fn episode(url: &str) -> Episode {
+        Episode { title: url.to_string(), url: url.to_string(), duration: None, published: None, played: false }
+    }
+
+    fn feed(url: &str, episodes: &[&str]) -> Feed {
+        Feed { url: url.to_string(), title: url.to_string(), episodes: episodes.iter().map(|e| episode(e)).collect() }
+    }
+
+    fn play(url: &str, played_at: u64) -> Play {
+        Play { url: url.to_string(), title: url.to_string(), played_at, artist: None, listened_secs: 0 }
+    }
+
+    #[test]
+    fn merges_duplicate_feeds_and_episodes() {
+        let mut feeds = vec![feed("a", &["1", "2", "2"]), feed("b", &["3"]), feed("a", &["2", "4"])];
+        assert_eq!(dedupe_feeds(&mut feeds), 3);
+        let episodes: Vec<&str> = feeds[0].episodes.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(episodes, ["1", "2", "4"]);
+        assert_eq!(feeds.len(), 2);
+    }
+
+    #[test]
+    fn keeps_first_favorite_per_url() {
+        let favorite = |url: &str, added_at| Favorite { url: url.to_string(), title: String::new(), added_at };
+        let mut favorites = vec![favorite("a", 1), favorite("b", 2), favorite("a", 3)];
+        assert_eq!(dedupe_favorites(&mut favorites), 1);
+        assert_eq!(favorites.iter().map(|f| f.added_at).collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn sorts_and_dedupes_history() {
+        let mut plays = vec![play("a", 5), play("b", 1), play("a", 5), play("a", 6)];
+        assert_eq!(repair_history(&mut plays), 2);
+        assert_eq!(plays.iter().map(|p| p.played_at).collect::<Vec<_>>(), [1, 5, 6]);
+
+        let mut clean = vec![play("a", 1), play("a", 2)];
+        assert_eq!(repair_history(&mut clean), 0);
+    }
+}
@@ -1 +1,26 @@
+pub mod artwork;
+pub mod backup;
+pub mod cli;
+pub mod download;
+pub mod favorites;
+pub mod files;
+pub mod fuzzy;
+pub mod history;
+pub mod input;
+pub mod keys;
+pub mod library;
+pub mod m3u;
+pub mod maintenance;
+pub mod mouse;
+pub mod nav;
+pub mod paths;
+pub mod podcasts;
+pub mod search_cache;
+pub mod search_history;
+pub mod settings;
+pub mod snapshot;
+pub mod stations;
+pub mod status;
+pub mod tab;
+pub mod track_details;
 pub mod state;
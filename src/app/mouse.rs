@@ -0,0 +1,138 @@
+use std::time::{Duration, Instant};
+use ratatui::layout::{Position, Rect};
+use super::history::HistoryFocus;
+
+// Two clicks on the same row closer together than this play it
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Parts of the screen that react to the mouse
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Region {
+    Progress,
+    ProgressLabel, // The time in the middle of the gauge, on top of Progress
+    Stations,
+    UpNext,
+    Results,
+    Podcasts,
+    LibraryHits,
+    History(HistoryFocus),
+    Files,
+}
+
+/// Where a click landed inside a region
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hit {
+    pub region: Region,
+    pub row: Option<usize>, // Index into the list, counting rows scrolled off the top
+    pub fraction: f64,      // How far across the inside of the box, 0.0 to 1.0
+}
+
+struct Registered {
+    region: Region,
+    area: Rect,
+    first_row: u16, // Screen row of the first list row, below the border (and a table's header)
+    offset: usize,  // List rows scrolled off the top
+}
+
+/// The screen areas of whatever's interactive, recorded by the layout every frame
+#[derive(Default)]
+pub struct HitRegions {
+    regions: Vec<Registered>,
+    last_click: Option<(Region, usize, Instant)>,
+}
+
+impl HitRegions {
+    /// Forget last frame's layout
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// A bordered widget with nothing to pick inside it
+    pub fn add(&mut self, region: Region, area: Rect) {
+        self.regions.push(Registered { region, area, first_row: area.y + 1, offset: 0 });
+    }
+
+    /// A bordered list whose first `header` rows aren't entries, scrolled down by `offset` entries
+    pub fn add_list(&mut self, region: Region, area: Rect, header: u16, offset: usize) {
+        self.regions.push(Registered { region, area, first_row: area.y + 1 + header, offset });
+    }
+
+    /// How many entries `region` had room for last frame, 0 if it wasn't drawn
+    pub fn rows(&self, region: Region) -> usize {
+        self.regions
+            .iter()
+            .rev()
+            .find(|r| r.region == region)
+            .map_or(0, |r| r.area.bottom().saturating_sub(r.first_row + 1) as usize)
+    }
+
+    pub fn hit(&self, column: u16, row: u16) -> Option<Hit> {
+        // Later regions are drawn on top
+        let registered = self.regions.iter().rev().find(|r| r.area.contains(Position::new(column, row)))?;
+        let inner_width = registered.area.width.saturating_sub(2).max(1);
+        let fraction = (column.saturating_sub(registered.area.x + 1) as f64 / inner_width as f64).min(1.0);
+        let inside = row >= registered.first_row && row + 1 < registered.area.bottom();
+        let row = inside.then(|| registered.offset + (row - registered.first_row) as usize);
+        Some(Hit { region: registered.region, row, fraction })
+    }
+
+    /// Record a click on `row` of `region`; true when it's the second of a double click
+    pub fn is_double_click(&mut self, region: Region, row: usize, now: Instant) -> bool {
+        let double = self
+            .last_click
+            .is_some_and(|(last_region, last_row, at)| last_region == region && last_row == row && now.duration_since(at) <= DOUBLE_CLICK);
+        // A third click starts over instead of counting as another double
+        self.last_click = if double { None } else { Some((region, row, now)) };
+        double
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_rows_through_the_border_header_and_scroll() {
+        let mut regions = HitRegions::default();
+        regions.add_list(Region::Results, Rect::new(0, 3, 40, 10), 1, 5);
+        // Border at y=3, header at y=4, first entry (the 6th, scrolled) at y=5
+        assert_eq!(regions.hit(10, 5).unwrap().row, Some(5));
+        assert_eq!(regions.hit(10, 7).unwrap().row, Some(7));
+        assert_eq!(regions.hit(10, 4).unwrap().row, None);
+        // Bottom border
+        assert_eq!(regions.hit(10, 12).unwrap().row, None);
+        assert!(regions.hit(10, 13).is_none());
+    }
+
+    #[test]
+    fn rows_count_what_fits_inside() {
+        let mut regions = HitRegions::default();
+        regions.add_list(Region::Results, Rect::new(0, 3, 40, 10), 1, 0);
+        regions.add_list(Region::Stations, Rect::new(0, 0, 40, 10), 0, 0);
+        // Ten high, less two borders and a header
+        assert_eq!(regions.rows(Region::Results), 7);
+        assert_eq!(regions.rows(Region::Stations), 8);
+        assert_eq!(regions.rows(Region::Files), 0);
+    }
+
+    #[test]
+    fn fraction_runs_across_the_inside() {
+        let mut regions = HitRegions::default();
+        regions.add(Region::Progress, Rect::new(10, 0, 12, 3));
+        assert_eq!(regions.hit(11, 1).unwrap().fraction, 0.0);
+        assert_eq!(regions.hit(16, 1).unwrap().fraction, 0.5);
+        assert_eq!(regions.hit(21, 1).unwrap().fraction, 1.0);
+        assert!(regions.hit(9, 1).is_none());
+    }
+
+    #[test]
+    fn double_click_needs_the_same_row_soon_after() {
+        let mut regions = HitRegions::default();
+        let start = Instant::now();
+        assert!(!regions.is_double_click(Region::Stations, 2, start));
+        assert!(regions.is_double_click(Region::Stations, 2, start + Duration::from_millis(200)));
+        assert!(!regions.is_double_click(Region::Stations, 2, start + Duration::from_millis(300)));
+        assert!(!regions.is_double_click(Region::Stations, 3, start + Duration::from_millis(350)));
+        assert!(!regions.is_double_click(Region::Stations, 3, start + Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,108 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use super::mouse::Region;
+use super::tab::Tab;
+
+/// How a key moves the selection in a list
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ListMove {
+    Next,
+    Previous,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+}
+
+impl ListMove {
+    pub fn from_key(code: KeyCode) -> Option<ListMove> {
+        match code {
+            KeyCode::Down => Some(ListMove::Next),
+            KeyCode::Up => Some(ListMove::Previous),
+            KeyCode::PageDown => Some(ListMove::PageDown),
+            KeyCode::PageUp => Some(ListMove::PageUp),
+            KeyCode::Home => Some(ListMove::First),
+            KeyCode::End => Some(ListMove::Last),
+            _ => None,
+        }
+    }
+
+    /// How `key` moves the focused `region` on `tab`, None when the key is left to the other handlers:
+    /// Shift arrows (and on RADIO, Alt arrows and the page keys) belong to the scope, and off RADIO,
+    /// where the station list isn't shown, only Up and Down still step through it
+    pub fn for_key(key: &KeyEvent, region: Region, tab: Tab) -> Option<ListMove> {
+        let movement = ListMove::from_key(key.code)?;
+        let on_radio = tab == Tab::Radio;
+        let scope_modifiers = if on_radio { KeyModifiers::SHIFT | KeyModifiers::ALT } else { KeyModifiers::SHIFT };
+        let step = matches!(movement, ListMove::Next | ListMove::Previous);
+        let page = matches!(movement, ListMove::PageUp | ListMove::PageDown);
+        match region {
+            _ if key.modifiers.intersects(scope_modifiers) => None,
+            Region::Stations if !on_radio && !step => None,
+            Region::Stations | Region::UpNext if on_radio && page => None,
+            _ => Some(movement),
+        }
+    }
+
+    /// The selection after this move in a list of `len` rows with `page` of them on screen,
+    /// None when the list is empty. Single steps wrap around the ends; pages stop at them.
+    pub fn apply(self, selected: Option<usize>, len: usize, page: usize) -> Option<usize> {
+        let last = len.checked_sub(1)?;
+        let page = page.max(1);
+        Some(match (self, selected) {
+            (ListMove::First, _) => 0,
+            (ListMove::Last, _) => last,
+            (_, None) => 0,
+            (ListMove::Next, Some(i)) => if i >= last { 0 } else { i + 1 },
+            (ListMove::Previous, Some(i)) => if i == 0 { last } else { (i - 1).min(last) },
+            (ListMove::PageDown, Some(i)) => (i + page).min(last),
+            (ListMove::PageUp, Some(i)) => i.saturating_sub(page).min(last),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_wrap_around() {
+        assert_eq!(ListMove::Next.apply(Some(4), 5, 3), Some(0));
+        assert_eq!(ListMove::Previous.apply(Some(0), 5, 3), Some(4));
+        assert_eq!(ListMove::Next.apply(None, 5, 3), Some(0));
+        assert_eq!(ListMove::Next.apply(None, 0, 3), None);
+    }
+
+    #[test]
+    fn pages_clamp_at_the_ends() {
+        assert_eq!(ListMove::PageDown.apply(Some(2), 100, 10), Some(12));
+        assert_eq!(ListMove::PageDown.apply(Some(95), 100, 10), Some(99));
+        assert_eq!(ListMove::PageUp.apply(Some(12), 100, 10), Some(2));
+        assert_eq!(ListMove::PageUp.apply(Some(3), 100, 10), Some(0));
+        // A list that hasn't been drawn yet still moves
+        assert_eq!(ListMove::PageDown.apply(Some(3), 100, 0), Some(4));
+    }
+
+    #[test]
+    fn keys_reach_only_the_lists_they_belong_to() {
+        let press = |code, modifiers| KeyEvent::new(code, modifiers);
+        let none = KeyModifiers::NONE;
+        // Ctrl arrows still step through the stations
+        assert_eq!(ListMove::for_key(&press(KeyCode::Down, KeyModifiers::CONTROL), Region::Stations, Tab::Data), Some(ListMove::Next));
+        assert_eq!(ListMove::for_key(&press(KeyCode::Up, KeyModifiers::CONTROL), Region::Stations, Tab::Radio), Some(ListMove::Previous));
+        assert_eq!(ListMove::for_key(&press(KeyCode::Up, KeyModifiers::SHIFT), Region::Stations, Tab::Radio), None);
+        assert_eq!(ListMove::for_key(&press(KeyCode::Up, KeyModifiers::ALT), Region::Stations, Tab::Radio), None);
+        // The hidden station list doesn't page or jump
+        assert_eq!(ListMove::for_key(&press(KeyCode::PageDown, none), Region::Stations, Tab::Map), None);
+        assert_eq!(ListMove::for_key(&press(KeyCode::End, none), Region::Stations, Tab::Data), None);
+        assert_eq!(ListMove::for_key(&press(KeyCode::End, none), Region::Stations, Tab::Radio), Some(ListMove::Last));
+        assert_eq!(ListMove::for_key(&press(KeyCode::PageUp, none), Region::Stations, Tab::Radio), None);
+        assert_eq!(ListMove::for_key(&press(KeyCode::PageUp, none), Region::Results, Tab::Data), Some(ListMove::PageUp));
+    }
+
+    #[test]
+    fn home_and_end_jump() {
+        assert_eq!(ListMove::First.apply(Some(40), 50, 10), Some(0));
+        assert_eq!(ListMove::Last.apply(None, 50, 10), Some(49));
+        assert_eq!(ListMove::Last.apply(None, 0, 10), None);
+    }
+}
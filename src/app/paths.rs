@@ -0,0 +1,59 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use directories::ProjectDirs;
+
+// Where settings, feeds, favorites and history live. Unset means the working directory.
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// What used to be written to the working directory, before there was a data directory
+pub const LIBRARY_FILES: [&str; 4] = [
+    super::settings::SETTINGS_FILE,
+    super::podcasts::FEEDS_FILE,
+    super::favorites::FAVORITES_FILE,
+    super::history::HISTORY_FILE,
+];
+
+/// --data-dir, then $SOUND_COWS_DATA_DIR, then the platform data directory
+/// (~/.local/share/sound_cows on Linux). Creates it if needed.
+pub fn init_data_dir(flag: Option<&str>) -> Result<PathBuf, String> {
+    let dir = flag
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("SOUND_COWS_DATA_DIR").map(PathBuf::from))
+        .or_else(|| ProjectDirs::from("", "", "sound_cows").map(|dirs| dirs.data_dir().to_path_buf()))
+        .ok_or_else(|| "No home directory; pass --data-dir".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let _ = DATA_DIR.set(dir.clone());
+    Ok(dir)
+}
+
+pub fn data_path(file: &str) -> PathBuf {
+    DATA_DIR.get().map_or_else(|| PathBuf::from(file), |dir| dir.join(file))
+}
+
+/// Library files in the working directory that the data directory doesn't have yet
+pub fn legacy_files() -> Vec<&'static str> {
+    let Some(dir) = DATA_DIR.get() else { return Vec::new() };
+    if same_dir(dir, Path::new(".")) {
+        return Vec::new();
+    }
+    LIBRARY_FILES.into_iter().filter(|file| Path::new(file).is_file() && !dir.join(file).exists()).collect()
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+/// Move the legacy files into the data directory
+pub fn migrate_legacy(files: &[&str]) -> Result<(), String> {
+    for file in files {
+        let target = data_path(file);
+        // rename fails across filesystems; copy and remove then
+        if fs::rename(file, &target).is_err() {
+            fs::copy(file, &target).map_err(|e| format!("Failed to move {}: {}", file, e))?;
+            let _ = fs::remove_file(file);
+        }
+    }
+    Ok(())
+}
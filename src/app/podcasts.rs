@@ -0,0 +1,317 @@
+use std::fs;
+use ratatui::widgets::ListState;
+use serde_derive::{Deserialize, Serialize};
+use super::nav::ListMove;
+use super::paths::data_path;
+
+// Subscriptions live next to the settings file
+pub const FEEDS_FILE: &str = "sound_cows_feeds.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Episode {
+    pub title: String,
+    pub url: String, // Enclosure URL, played through the direct download path
+    pub duration: Option<f64>,
+    pub published: Option<String>,
+    #[serde(default)]
+    pub played: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Feed {
+    pub url: String,
+    pub title: String,
+    pub episodes: Vec<Episode>,
+}
+
+impl Feed {
+    /// Summed episode lengths, counting unknown ones as zero
+    pub fn total_duration(&self) -> f64 {
+        self.episodes.iter().filter_map(|e| e.duration).sum()
+    }
+}
+
+/// Long lengths for list summaries: "3h12m", "45m", "0m"
+pub fn format_total_duration(secs: f64) -> String {
+    let minutes = (secs.max(0.0) / 60.0).round() as u64;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h{:02}m", h, m),
+    }
+}
+
+// itunes:duration is either plain seconds or [HH:]MM:SS
+fn parse_duration(text: &str) -> Option<f64> {
+    text.trim().split(':').try_fold(0.0, |total, part| Some(total * 60.0 + part.trim().parse::<f64>().ok()?))
+}
+
+/// Parse an RSS 2.0 podcast feed. Items without an audio enclosure are skipped.
+pub fn parse_feed(url: &str, xml: &str) -> Result<Feed, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| format!("Bad feed XML: {}", e))?;
+    let channel = doc
+        .descendants()
+        .find(|n| n.has_tag_name("channel"))
+        .ok_or_else(|| "Not an RSS feed (no channel)".to_string())?;
+    let child_text = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|c| c.tag_name().name() == name)
+            .and_then(|c| c.text())
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+    };
+
+    let episodes = channel
+        .children()
+        .filter(|n| n.has_tag_name("item"))
+        .filter_map(|item| {
+            let enclosure = item.children().find(|c| c.has_tag_name("enclosure"))?;
+            let is_audio = enclosure.attribute("type").is_none_or(|t| t.starts_with("audio/"));
+            if !is_audio {
+                return None;
+            }
+            Some(Episode {
+                title: child_text(item, "title").unwrap_or_else(|| "Untitled episode".to_string()),
+                url: enclosure.attribute("url")?.to_string(),
+                duration: child_text(item, "duration").as_deref().and_then(parse_duration),
+                published: child_text(item, "pubDate"),
+                played: false,
+            })
+        })
+        .collect();
+
+    Ok(Feed {
+        url: url.to_string(),
+        title: child_text(channel, "title").unwrap_or_else(|| url.to_string()),
+        episodes,
+    })
+}
+
+/// Download and parse a feed (blocking, run it off the UI thread)
+pub fn fetch_feed(url: &str) -> Result<Feed, String> {
+    let body = ureq::get(url)
+        .call()
+        .map_err(|e| format!("Feed request failed: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read feed: {}", e))?;
+    parse_feed(url, &body)
+}
+
+// The INV tab: a pinned Favorites row, the subscribed feeds below it, and whatever of those is opened
+#[derive(Default)]
+pub struct Podcasts {
+    pub feeds: Vec<Feed>,
+    pub feed_state: ListState, // Row 0 is Favorites, feeds start at row 1
+    pub episode_state: ListState,
+    pub open_feed: Option<usize>, // Showing this feed's episodes
+    pub favorites_open: bool,
+}
+
+impl Podcasts {
+    pub fn load() -> Self {
+        let feeds: Vec<Feed> = fs::read_to_string(data_path(FEEDS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        let mut podcasts = Podcasts { feeds, ..Default::default() };
+        podcasts.feed_state.select(Some(0));
+        podcasts
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.feeds).map_err(|e| format!("Feeds error: {}", e))?;
+        fs::write(data_path(FEEDS_FILE), json).map_err(|e| format!("Failed to save feeds: {}", e))
+    }
+
+    /// Add a new feed or replace a refreshed one, keeping which episodes were played
+    pub fn merge(&mut self, mut feed: Feed) {
+        match self.feeds.iter_mut().find(|f| f.url == feed.url) {
+            Some(existing) => {
+                for episode in &mut feed.episodes {
+                    episode.played = existing.episodes.iter().any(|e| e.url == episode.url && e.played);
+                }
+                *existing = feed;
+            },
+            None => self.feeds.push(feed),
+        }
+    }
+
+    // Index into `feeds` of the selected row, None on the Favorites row
+    fn selected_feed_index(&self) -> Option<usize> {
+        let i = self.feed_state.selected()?.checked_sub(1)?;
+        (i < self.feeds.len()).then_some(i)
+    }
+
+    /// The opened feed, or the one selected in the feed list
+    pub fn selected_feed(&self) -> Option<&Feed> {
+        match self.open_feed {
+            Some(i) => self.feeds.get(i),
+            None if self.favorites_open => None,
+            None => self.feeds.get(self.selected_feed_index()?),
+        }
+    }
+
+    pub fn remove_selected_feed(&mut self) -> Option<Feed> {
+        let i = self.selected_feed_index()?;
+        let feed = self.feeds.remove(i);
+        self.feed_state.select(Some((i + 1).min(self.feeds.len())));
+        Some(feed)
+    }
+
+    /// Returns true if `url` was an episode that wasn't marked played yet
+    pub fn mark_played(&mut self, url: &str) -> bool {
+        let episode = self.feeds.iter_mut().flat_map(|f| f.episodes.iter_mut()).find(|e| e.url == url);
+        match episode {
+            Some(episode) if !episode.played => {
+                episode.played = true;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    pub fn opened(&self) -> Option<&Feed> {
+        self.feeds.get(self.open_feed?)
+    }
+
+    /// Showing episodes or favorites rather than the feed list
+    pub fn is_open(&self) -> bool {
+        self.open_feed.is_some() || self.favorites_open
+    }
+
+    pub fn open_selected(&mut self, favorites_len: usize) {
+        let len = match self.selected_feed_index() {
+            Some(i) => {
+                self.open_feed = Some(i);
+                self.feeds[i].episodes.len()
+            },
+            None => {
+                self.favorites_open = true;
+                favorites_len
+            },
+        };
+        self.episode_state.select(if len == 0 { None } else { Some(0) });
+    }
+
+    pub fn close(&mut self) {
+        self.open_feed = None;
+        self.favorites_open = false;
+    }
+
+    pub fn selected_episode(&self) -> Option<&Episode> {
+        self.opened()?.episodes.get(self.episode_state.selected()?)
+    }
+
+    // Length and selection of whichever list is showing
+    fn showing(&mut self, favorites_len: usize) -> (usize, &mut ListState) {
+        let len = self.showing_len(favorites_len);
+        let state = if self.open_feed.is_some() || self.favorites_open { &mut self.episode_state } else { &mut self.feed_state };
+        (len, state)
+    }
+
+    /// Rows in whichever list is showing: an open feed's episodes, the favorites, or the feeds
+    pub fn showing_len(&self, favorites_len: usize) -> usize {
+        match self.open_feed {
+            Some(i) => self.feeds.get(i).map_or(0, |f| f.episodes.len()),
+            None if self.favorites_open => favorites_len,
+            None => self.feeds.len() + 1,
+        }
+    }
+
+    /// Select the `index`th row of whichever list is showing, if there is one. Returns whether it did.
+    pub fn select(&mut self, index: usize, favorites_len: usize) -> bool {
+        let (len, state) = self.showing(favorites_len);
+        if index >= len {
+            return false;
+        }
+        state.select(Some(index));
+        true
+    }
+
+    // Move the selection in whichever list is showing
+    pub fn move_selection(&mut self, movement: ListMove, page: usize, favorites_len: usize) {
+        let (len, state) = self.showing(favorites_len);
+        if let Some(i) = movement.apply(state.selected(), len, page) {
+            state.select(Some(i));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_total_durations() {
+        assert_eq!(format_total_duration(0.0), "0m");
+        assert_eq!(format_total_duration(29.0), "0m");
+        assert_eq!(format_total_duration(45.0 * 60.0), "45m");
+        assert_eq!(format_total_duration(3599.0), "1h00m");
+        assert_eq!(format_total_duration(3.0 * 3600.0 + 12.0 * 60.0), "3h12m");
+        assert_eq!(format_total_duration(100.0 * 3600.0), "100h00m");
+    }
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title> Cow Talk </title>
+    <item>
+      <title>Episode 2: Moo</title>
+      <pubDate>Tue, 02 Jan 2024 08:00:00 GMT</pubDate>
+      <itunes:duration>1:02:03</itunes:duration>
+      <enclosure url="https://example.com/2.mp3?src=rss" type="audio/mpeg" length="123"/>
+    </item>
+    <item>
+      <title>Show notes only</title>
+    </item>
+    <item>
+      <title>Video episode</title>
+      <enclosure url="https://example.com/v.mp4" type="video/mp4"/>
+    </item>
+    <item>
+      <itunes:duration>95</itunes:duration>
+      <enclosure url="https://example.com/1.m4a"/>
+    </item>
+    <item>
+      <title></title>
+      <enclosure url="https://example.com/0.mp3" type="audio/mpeg"/>
+    </item>
+    <item>
+      <title>No URL</title>
+      <enclosure type="audio/mpeg"/>
+    </item>
+  </channel>
+</rss>"#;
+
+    #[test]
+    fn parses_episodes_with_audio_enclosures() {
+        let feed = parse_feed("https://example.com/feed", FEED).unwrap();
+        assert_eq!(feed.title, "Cow Talk");
+        let episodes: Vec<(&str, &str, Option<f64>)> = feed.episodes.iter().map(|e| (e.title.as_str(), e.url.as_str(), e.duration)).collect();
+        assert_eq!(
+            episodes,
+            [
+                ("Episode 2: Moo", "https://example.com/2.mp3?src=rss", Some(3723.0)),
+                ("Untitled episode", "https://example.com/1.m4a", Some(95.0)),
+                ("Untitled episode", "https://example.com/0.mp3", None),
+            ]
+        );
+        assert_eq!(feed.episodes[0].published.as_deref(), Some("Tue, 02 Jan 2024 08:00:00 GMT"));
+        assert!(feed.episodes.iter().all(|e| !e.played));
+    }
+
+    #[test]
+    fn untitled_and_broken_feeds() {
+        let untitled = parse_feed("https://example.com/feed", "<rss><channel><item/></channel></rss>").unwrap();
+        assert_eq!(untitled.title, "https://example.com/feed");
+        assert!(untitled.episodes.is_empty());
+        assert_eq!(parse_feed("u", "<feed xmlns=\"http://www.w3.org/2005/Atom\"/>").unwrap_err(), "Not an RSS feed (no channel)");
+        assert!(parse_feed("u", "<rss><channel>").unwrap_err().starts_with("Bad feed XML"));
+    }
+
+    #[test]
+    fn total_duration_skips_unknown_lengths() {
+        let episode = |duration| Episode { title: String::new(), url: String::new(), duration, published: None, played: false };
+        let feed = Feed { url: String::new(), title: String::new(), episodes: vec![episode(Some(60.0)), episode(None), episode(Some(30.0))] };
+        assert_eq!(feed.total_duration(), 90.0);
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde_derive::{Deserialize, Serialize};
+use crate::audio::cache::CACHE_DIR;
+use crate::audio::stream::{Provider, YtDlpResult};
+
+// Lives with the downloads; it's just as disposable
+const SEARCH_CACHE_FILE: &str = "searches.json";
+// Oldest entries go first past this many queries
+const MAX_ENTRIES: usize = 200;
+
+#[derive(Serialize, Deserialize)]
+struct CachedSearch {
+    timestamp: u64, // Unix seconds
+    results: Vec<YtDlpResult>,
+}
+
+/// Past search results keyed by provider + normalized query
+#[derive(Default)]
+pub struct SearchCache {
+    entries: HashMap<String, CachedSearch>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// "Daft  Punk " and "daft punk" are the same search
+pub fn search_key(provider: Provider, query: &str) -> String {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    format!("{}:{}", provider.tag(), words.join(" "))
+}
+
+impl SearchCache {
+    pub fn load() -> Self {
+        let entries = fs::read_to_string(Path::new(CACHE_DIR).join(SEARCH_CACHE_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        SearchCache { entries }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        fs::create_dir_all(CACHE_DIR).map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        let json = serde_json::to_string(&self.entries).map_err(|e| format!("Search cache error: {}", e))?;
+        fs::write(Path::new(CACHE_DIR).join(SEARCH_CACHE_FILE), json)
+            .map_err(|e| format!("Failed to save search cache: {}", e))
+    }
+
+    /// Results for `key` if they're younger than `ttl`
+    pub fn get(&self, key: &str, ttl: Duration) -> Option<&[YtDlpResult]> {
+        let entry = self.entries.get(key)?;
+        let age = now_secs().saturating_sub(entry.timestamp);
+        (age < ttl.as_secs()).then_some(entry.results.as_slice())
+    }
+
+    pub fn insert(&mut self, key: String, results: Vec<YtDlpResult>) -> Result<(), String> {
+        self.entries.insert(key, CachedSearch { timestamp: now_secs(), results });
+        while self.entries.len() > MAX_ENTRIES {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, e)| e.timestamp).map(|(k, _)| k.clone()) else { break };
+            self.entries.remove(&oldest);
+        }
+        self.save()
+    }
+}
@@ -0,0 +1,101 @@
+// Oldest queries drop off past this many
+const MAX_QUERIES: usize = 50;
+
+/// Submitted search queries, recalled with Up/Down while typing like a shell's history
+#[derive(Debug, Default)]
+pub struct SearchHistory {
+    pub queries: Vec<String>, // Oldest first
+    cursor: Option<usize>,    // How far back Up has gone, 0 being the newest; None is the text being typed
+    draft: String,            // What was being typed before Up was first pressed
+}
+
+impl SearchHistory {
+    pub fn new(queries: Vec<String>) -> Self {
+        let skip = queries.len().saturating_sub(MAX_QUERIES);
+        SearchHistory { queries: queries.into_iter().skip(skip).collect(), ..Default::default() }
+    }
+
+    /// Remember a submitted query; one the same as the last is only kept once
+    pub fn record(&mut self, query: &str) {
+        self.reset();
+        let query = query.trim();
+        if query.is_empty() || self.queries.last().is_some_and(|last| last == query) {
+            return;
+        }
+        self.queries.push(query.to_string());
+        if self.queries.len() > MAX_QUERIES {
+            self.queries.remove(0);
+        }
+    }
+
+    /// Back to the text being typed, for a fresh prompt
+    pub fn reset(&mut self) {
+        self.cursor = None;
+        self.draft.clear();
+    }
+
+    /// The query before the one shown, None when there's nothing older. `typing` is kept to come back to.
+    pub fn older(&mut self, typing: &str) -> Option<&str> {
+        let back = match self.cursor {
+            None => {
+                self.draft = typing.to_string();
+                0
+            },
+            Some(back) => back + 1,
+        };
+        let index = self.queries.len().checked_sub(back + 1)?;
+        self.cursor = Some(back);
+        Some(&self.queries[index])
+    }
+
+    /// The query after the one shown, ending with what was being typed; None when that's already shown
+    pub fn newer(&mut self) -> Option<&str> {
+        match self.cursor? {
+            0 => {
+                self.cursor = None;
+                Some(&self.draft)
+            },
+            back => {
+                self.cursor = Some(back - 1);
+                Some(&self.queries[self.queries.len() - back])
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_back_and_returns_to_the_draft() {
+        let mut history = SearchHistory::new(vec!["lofi".to_string(), "jazz".to_string()]);
+        assert_eq!(history.older("ro"), Some("jazz"));
+        assert_eq!(history.older("ignored"), Some("lofi"));
+        assert_eq!(history.older("ignored"), None);
+        assert_eq!(history.newer(), Some("jazz"));
+        assert_eq!(history.newer(), Some("ro"));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn repeats_collapse_and_old_queries_drop_off() {
+        let mut history = SearchHistory::default();
+        history.record("jazz");
+        history.record(" jazz ");
+        history.record("");
+        assert_eq!(history.queries, ["jazz"]);
+        for i in 0..MAX_QUERIES {
+            history.record(&i.to_string());
+        }
+        assert_eq!(history.queries.len(), MAX_QUERIES);
+        assert_eq!(history.queries[0], "0");
+    }
+
+    #[test]
+    fn empty_history_leaves_the_input_alone() {
+        let mut history = SearchHistory::default();
+        assert_eq!(history.older("typing"), None);
+        assert_eq!(history.newer(), None);
+    }
+}
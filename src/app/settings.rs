@@ -0,0 +1,105 @@
+use std::fs;
+use serde_derive::{Deserialize, Serialize};
+use super::paths::data_path;
+use super::tab::Tab;
+use crate::audio::stream::AudioQuality;
+
+// Small JSON file in the data directory
+pub const SETTINGS_FILE: &str = "sound_cows.json";
+
+// The track that was playing when the app last saved, so it can be resumed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LastTrack {
+    pub title: String,
+    pub url: String,
+    pub path: String,
+    pub position_secs: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub volume: f32,
+    pub current_tab: usize,
+    pub last_track: Option<LastTrack>,
+    pub yt_dlp_path: Option<String>,
+    pub cache_max_mb: u64,
+    pub audio_quality: Option<AudioQuality>, // None picks one based on the cache size
+    pub sponsorblock: bool,
+    pub offline: bool,
+    pub search_cache_ttl_mins: u64,
+    pub cookies_path: Option<String>,
+    pub cookies_from_browser: Option<String>,
+    pub scope_scale: f64,
+    pub scope_samples: u32,
+    pub scope_scatter: bool,
+    pub scope_persistence: u32, // Frames of trail behind the waveform
+    pub scope_braille: bool,    // Braille dots rather than plain ones, for finer traces
+    pub scope_references: bool, // Zero lines under the trace
+    pub scope_labels: bool,     // Axis titles
+    pub peak_hold_secs: f64,    // How long the scope's held peaks stay before falling
+    pub theme: String, // Name of one of ui::theme::THEMES
+    pub music_dir: Option<String>, // Where the MAP tab starts; None is ~/Music
+    pub show_remaining: bool, // The progress gauge counts down
+    pub search_history: Vec<String>, // Submitted queries, oldest first
+    pub legacy_files_declined: bool, // Said no to moving files from the working directory into the data directory
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            volume: 1.0,
+            current_tab: Tab::Radio.index(),
+            last_track: None,
+            yt_dlp_path: None,
+            cache_max_mb: 1024,
+            audio_quality: None,
+            sponsorblock: true,
+            offline: false,
+            search_cache_ttl_mins: 60,
+            cookies_path: None,
+            cookies_from_browser: None,
+            scope_scale: 1.0,
+            scope_samples: 200,
+            scope_scatter: false,
+            scope_persistence: 0,
+            scope_braille: false,
+            scope_references: false,
+            scope_labels: false,
+            peak_hold_secs: 2.0,
+            theme: "green".to_string(),
+            music_dir: None,
+            show_remaining: false,
+            search_history: Vec::new(),
+            legacy_files_declined: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn cache_max_bytes(&self) -> u64 {
+        self.cache_max_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// The configured quality, or a cache-friendly default when the cache is small
+    pub fn quality(&self) -> AudioQuality {
+        match self.audio_quality {
+            Some(quality) => quality,
+            None if self.cache_max_mb < 512 => AudioQuality::Low,
+            None => AudioQuality::High,
+        }
+    }
+
+    /// Load settings from disk, falling back to defaults if the file is missing or unreadable
+    pub fn load() -> Self {
+        fs::read_to_string(data_path(SETTINGS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Settings error: {}", e))?;
+        fs::write(data_path(SETTINGS_FILE), json).map_err(|e| format!("Failed to save settings: {}", e))
+    }
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audio::wav;
+use crate::scope::Matrix;
+
+// Under the data directory
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// "20261016-142233" for a Unix time, in UTC
+pub fn stamp(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs = unix_secs % 86_400;
+    // Days to a civil date, after Howard Hinnant's days_from_civil inverse
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", year, month, day, secs / 3_600, secs / 60 % 60, secs % 60)
+}
+
+/// One row per sample drawn, one column per channel under its name
+pub fn csv(drawn: &Matrix<f64>, names: &[String]) -> String {
+    let mut out = format!("sample,{}\n", names.join(","));
+    let len = drawn.iter().map(Vec::len).min().unwrap_or(0);
+    for i in 0..len {
+        let row: Vec<String> = drawn.iter().map(|channel| channel[i].to_string()).collect();
+        out.push_str(&format!("{},{}\n", i, row.join(",")));
+    }
+    out
+}
+
+/// Write scope-<stamp>.wav (the whole window) and scope-<stamp>.csv (what was drawn of it) into `dir`
+pub fn save(dir: &Path, stamp: &str, window: &Matrix<f64>, sample_rate: u32, drawn: &Matrix<f64>, names: &[String]) -> Result<(PathBuf, PathBuf), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let wav_path = dir.join(format!("scope-{}.wav", stamp));
+    let csv_path = dir.join(format!("scope-{}.csv", stamp));
+    fs::write(&wav_path, wav::encode(window, sample_rate)).map_err(|e| format!("Failed to write {}: {}", wav_path.display(), e))?;
+    fs::write(&csv_path, csv(drawn, names)).map_err(|e| format!("Failed to write {}: {}", csv_path.display(), e))?;
+    Ok((wav_path, csv_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_read_as_utc_dates() {
+        assert_eq!(stamp(0), "19700101-000000");
+        assert_eq!(stamp(951_825_600), "20000229-120000");
+        assert_eq!(stamp(1_792_156_953), "20261016-132233");
+    }
+
+    #[test]
+    fn csv_has_a_column_per_channel() {
+        let csv = csv(&vec![vec![0.5, -0.25], vec![0.0, 1.0]], &["L".to_string(), "R".to_string()]);
+        assert_eq!(csv, "sample,L,R\n0,0.5,0\n1,-0.25,1\n");
+    }
+}
@@ -1,43 +1,313 @@
-use ratatui::{style::Color, widgets::ListState};
-use crate::audio::player::AudioPlayer;
-use crate::scope::display::{oscilloscope::Oscilloscope, GraphConfig};
-use crate::ui::theme::{PIPBOY_GREEN, COLOR_RED};
+use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind};
+use image::RgbImage;
+use ratatui::{style::Color, symbols::Marker, widgets::{ListState, TableState}};
+use crate::audio::cache::{self, CacheUsage};
+use crate::audio::http::StreamReader;
+use crate::audio::mic::Mic;
+use crate::audio::player::{AudioPlayer, PlaybackState};
+use crate::audio::sponsorblock::SkipSegment;
+use crate::audio::stream::{
+    audio_quality, is_offline, set_audio_quality, set_configured_yt_dlp, set_cookies, set_offline, yt_dlp_version, Chapter, CookieSource, DownloadManager, Provider,
+    Listing, YtDlpResult,
+};
+use crate::app::cli::CliArgs;
+use crate::scope::channels::{channel_key, channel_label, mid_side, ChannelFilter};
+use crate::scope::pitch::{self, PitchTracker};
+use crate::scope::processing::Processing;
+use crate::scope::Matrix;
+use crate::scope::display::{oscilloscope::Oscilloscope, persistence::{Persistence, MAX_PERSISTENCE}, spectrogram::Spectrogram, spectroscope::Spectroscope, vectorscope::Vectorscope, DisplayMode, GraphConfig};
+use crate::audio::tags::read_tags;
+use crate::format::{format_bytes, format_time};
+use crate::scope::style::dim_color;
+use crate::ui::theme::{color_by_name, color_name, next_named_color, Theme, MONO};
+use super::artwork::{self, ArtworkSource};
+use super::backup::Backup;
+use super::download::Download;
+use super::favorites::Favorites;
+use super::files::{self, DirEntry, FileBrowserState};
+use super::history::{format_ago, now_secs, History, HistoryFocus};
+use super::input::TextInput;
+use super::library::{self, LibraryHit};
+use super::m3u::{self, M3uEntry};
+use super::maintenance;
+use super::mouse::{Hit, HitRegions, Region};
+use super::nav::ListMove;
+use super::podcasts::{fetch_feed, Feed, Podcasts};
+use super::search_cache::{search_key, SearchCache};
+use super::search_history::SearchHistory;
+use super::paths::data_path;
+use super::settings::{LastTrack, Settings};
+use super::snapshot::{self, SNAPSHOTS_DIR};
+use super::stations::{self, Station};
+use super::tab::Tab;
+use super::track_details::TrackDetails;
+use super::status::{Status, StatusLevel};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use rodio::Decoder;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InputMode {
     Normal,
     Editing,
     SearchResults,
+    FeedUrl,    // Typing a podcast feed URL on the INV tab
+    ExportPath, // Typing where to write an M3U of the selected INV list
+    BackupPath, // Where to write a library backup
+    RestorePath, // Which backup to merge in
+    LibrarySearch, // Typing a query over everything saved (INV tab)
+    StationName, // Adding/editing a radio station, first the name...
+    StationUrl,  // ...then its stream URL
+    Confirm,     // A yes/no question about `pending_confirm` is up
+    FileBrowser, // Moving around the MAP tab's directory listing
+    FileFilter,  // Typing a fuzzy filter over that listing
+}
+
+impl InputMode {
+    /// Where a finished search leaves the UI: on the results if there are any. A prompt or question
+    /// opened while the search ran stays up, its state is still waiting for an answer.
+    pub fn after_search(self, found: bool) -> InputMode {
+        match self {
+            InputMode::Normal | InputMode::SearchResults if found => InputMode::SearchResults,
+            InputMode::Normal | InputMode::SearchResults => InputMode::Normal,
+            other => other,
+        }
+    }
+
+    /// The mode after leaving the MAP tab: browsing or filtering its listing can't go on elsewhere
+    pub fn off_file_browser(self) -> InputMode {
+        match self {
+            InputMode::FileBrowser | InputMode::FileFilter => InputMode::Normal,
+            other => other,
+        }
+    }
+}
+
+// Things that can't be undone, asked about before they happen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfirmAction {
+    RemoveFeed,
+    DeleteStation,
+    ClearCache,
+    Quit, // Only asked while something is playing or downloading
 }
 
 // Events sent from background threads to the main UI thread
 pub enum AppEvent {
     AudioLoaded(String), // Path to file
     AudioError(String),
-    SearchFinished(Vec<(String, String)>), // Results
-    SearchError(String),
+    SearchFinished(Provider, String, Listing), // (provider, query, results)
+    PlaylistListed(Provider, Listing), // Entries of a playlist URL, played in order
+    PlaylistProgress(usize), // Entries listed so far
+    StreamReady(Box<Decoder<StreamReader>>, Option<Duration>), // Decoder over the live HTTP stream
+    StreamFallback(String), // Why streaming failed; a download follows
+    DownloadRetry(usize, usize), // (retry, max retries)
+    DownloadProgress(u64, Option<u64>), // (bytes so far, total) of the download being waited on
+    SearchError(Provider, String, String), // (provider, query, error)
+    PlaylistError(String),
+    YtDlpChecked(Result<String, String>), // Version or lookup error
+    PrefetchComplete(String, String), // (url, path)
+    PrefetchFailed(String, String),   // (url, error)
+    FeedRefreshed(String, Result<Feed, String>), // (feed url, parsed feed or error)
+    DirectoryListed(PathBuf, Result<Vec<DirEntry>, String>), // (directory, entries or error) for the MAP tab
+    FilesCollected(Vec<PathBuf>), // Audio files under what `a` was pressed on, to queue
+    ArtworkReady(String, Option<RgbImage>), // (url of the track it's for, cover scaled by artwork::decode or None)
+    MetadataReady(String), // Path of a cached file whose sidecar was just written
+    MicError(String), // The input device failed while open
+}
+
+// Suggested file for library backups
+const DEFAULT_BACKUP_PATH: &str = "sound_cows_backup.json";
+// Library search waits for typing to pause this long
+const LIBRARY_SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+// Knob changes are written once they've settled for this long
+const SETTINGS_DEBOUNCE: Duration = Duration::from_secs(2);
+// How long exit waits for cancelled downloads to wind down
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+// How many upcoming queue entries to download ahead
+const PREFETCH_AHEAD: usize = 2;
+// Results shorter than this are usually shorts/teasers
+const SHORT_RESULT_SECS: f64 = 60.0;
+// Queue entries shown in the RADIO tab's UP NEXT panel
+pub const UP_NEXT_LEN: usize = 5;
+// Playback needed before a track counts as played (or half the track, if that's shorter)
+const HISTORY_THRESHOLD: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResultSort {
+    Relevance, // yt-dlp's order
+    Duration,
+    Title,
+}
+
+impl ResultSort {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResultSort::Relevance => "relevance",
+            ResultSort::Duration => "duration",
+            ResultSort::Title => "title",
+        }
+    }
+}
+
+// What the RADIO tab's scope panel shows, V cycles through them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeView {
+    Waveform,
+    Spectrum,
+    Both,
+    Vectors,
+    Waterfall,
+}
+
+impl ScopeView {
+    pub fn next(&self) -> Self {
+        match self {
+            ScopeView::Waveform => ScopeView::Spectrum,
+            ScopeView::Spectrum => ScopeView::Both,
+            ScopeView::Both => ScopeView::Vectors,
+            ScopeView::Vectors => ScopeView::Waterfall,
+            ScopeView::Waterfall => ScopeView::Waveform,
+        }
+    }
+}
+
+// A track picked by the user, remembered so the session can be resumed
+#[derive(Clone, Debug)]
+pub struct TrackInfo {
+    pub title: String,
+    pub artist: Option<String>, // Uploader or feed name, when we know one
+    pub url: String,
+    pub chapters: Vec<Chapter>,
+    pub skip_segments: Vec<SkipSegment>,
+}
+
+impl TrackInfo {
+    pub fn new(title: String, url: String) -> Self {
+        TrackInfo { title, artist: None, url, chapters: Vec::new(), skip_segments: Vec::new() }
+    }
+
+    pub fn with_artist(mut self, artist: Option<String>) -> Self {
+        self.artist = artist;
+        self
+    }
+
+    /// Pick up chapters and skip ranges from the cached file's metadata sidecar
+    pub fn load_metadata(&mut self, path: &Path) {
+        let metadata = cache::read_metadata(path).unwrap_or_default();
+        self.chapters = metadata.chapters;
+        self.skip_segments = metadata.skip_segments;
+    }
+}
+
+// What's in the player right now, for the now playing panel
+#[derive(Clone, Debug)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: Option<String>,
+    pub url: String,
+    pub started_at: Instant,
 }
 
 pub struct App {
-    pub current_tab: usize,
+    pub current_tab: Tab,
     pub radio_state: ListState,
-    pub radio_stations: Vec<String>,
+    pub radio_stations: Vec<Station>,
+    station_draft: Option<(Option<usize>, String)>, // (station being edited, new name) while typing its URL
 
     // Components
     pub player: AudioPlayer,
     pub oscilloscope: Oscilloscope,
+    pub spectroscope: Spectroscope,
+    pub vectorscope: Vectorscope,
+    pub spectrogram: Spectrogram,
+    pub persistence: Persistence, // The waveform's trails
+    pub channel_filter: ChannelFilter,
+    pub processing: Processing, // DC block and per-channel gain, for the scope only
+    pub pitch: PitchTracker,      // SCOPE CTRL's note readout
+    pub scope_data: Matrix<f64>,  // The scope's window, refilled every frame by fill_scope_window
+    pub scope_view: ScopeView,
     pub graph_config: GraphConfig,
+    pub draw_config: GraphConfig, // graph_config as this frame draws it, see update_draw_config
+    pub theme: Theme,
 
     // Search State
     pub input_mode: InputMode,
-    pub search_input: String,
-    pub cursor_position: usize,
-    pub loading_status: Option<String>,
-    pub is_loading: bool, // General loading spinner flag
+    pub search_input: TextInput, // Shared by the other text prompts, only one is open at a time
+    pub feed_input: TextInput,   // The INV tab's feed URL, kept apart so it never wipes a half-typed search
+    pub search_provider: Provider,
+    pub status: Status,
+    pub is_loading: bool, // A download or playlist listing is running (searches have `searching`)
+    pub download: Option<Download>, // What's being waited on, once it reports progress
+    pub show_help: bool,
+    pub mic: Option<Mic>, // MIC mode: the scope shows the default input device, playback stays stopped
+    format_before_mic: (u32, usize), // The player's sample rate and channels, put back when the mic closes
+    pub track_info: Option<TrackDetails>, // The `i` popup, drawn over everything while it's open
+    pub pending_confirm: Option<ConfirmAction>,
+    pub help_log: bool, // The help overlay shows the message log instead of the keys
+    pub help_scroll: u16,
+    pub tick: u64, // 16ms steps since startup, as of the last frame; drives animations
+    pub show_remaining: bool, // The progress gauge shows time left instead of time played
+    pub hit_regions: HitRegions, // Where the clickable widgets were drawn last frame
 
     // Search Results
-    pub search_results: Vec<(String, String)>,
-    pub search_results_state: ListState,
+    pub search_results: Vec<YtDlpResult>, // What's shown, after sorting/filtering
+    pub all_results: Vec<YtDlpResult>,
+    pub results_sort: ResultSort,
+    pub hide_short_results: bool,
+    pub results_provider: Provider,
+    pub search_results_state: TableState,
+    pub results_are_playlist: bool,
+    pub search_cache: SearchCache,
+    pub searching: Option<String>,         // Key of the search whose results are awaited; others only go in the cache
+    pub refreshing_search: Option<String>, // Key of cached results being refreshed in the background
+    pub last_search: Option<String>,
+    pub search_history: SearchHistory,
+
+    // Play queue (a listed playlist, or just the picked search result)
+    pub play_queue: Vec<YtDlpResult>,
+    pub queue_index: Option<usize>,
+    pub up_next_state: ListState, // Selection within up_next()
+    pub queue_focused: bool,      // RADIO's keys act on UP NEXT instead of the stations
+    pub prefetch: DownloadManager,
+    pub prefetched: HashMap<String, String>, // url -> cached path
+    pub awaiting_prefetch: Option<String>,   // Wanted now, but a prefetch worker already has it
+
+    // Session
+    pub requested_track: Option<TrackInfo>, // Being downloaded
+    pub current_track: Option<TrackInfo>,   // Loaded into the player
+    pub current_path: Option<String>,
+    pub now_playing: Option<NowPlaying>,
+    pub artwork: Option<RgbImage>,          // Cover of the playing track, once it's loaded
+    pub pending_resume: Option<Duration>,   // Seek here (paused) once the download finishes
+    pub history: History,
+    pub history_recorded: bool,             // The current track already counts as played
+    listened: Duration,                     // Heard of the current track and not yet added to its play
+    listen_checked: Option<Instant>,        // Last check_history while playing
+    pub download_cancel: Arc<AtomicBool>,   // Flag for the download in flight
+
+    // Persisted settings, updated and written back by save_session
+    pub settings: Settings,
+    saved_knobs: (f32, Tab, f64, u32, [bool; 4], u32), // As last written, see knobs()
+    knobs_changed_at: Option<Instant>,
+    pub yt_dlp_status: Option<Result<String, String>>,
+    pub cookies: Option<CookieSource>,
+
+    // Podcast subscriptions and favorites (INV tab)
+    pub podcasts: Podcasts,
+    pub favorites: Favorites,
+    pub library_hits: Vec<LibraryHit>,
+    pub library_state: ListState,
+    library_search_due: Option<Instant>,
+    pub cache_usage: CacheUsage,
+
+    // Local files (MAP tab)
+    pub file_browser: FileBrowserState,
 
     // Async Communication
     pub event_tx: Sender<AppEvent>,
@@ -45,161 +315,1881 @@ pub struct App {
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(cli: &CliArgs) -> App {
+        let radio_stations = stations::load();
         let mut radio_state = ListState::default();
-        radio_state.select(Some(3)); // Radio Freedom
+        radio_state.select(if radio_stations.is_empty() { None } else { Some(0) });
+
+        let settings = Settings::load();
 
-        let player = AudioPlayer::new();
+        if let Some(path) = &settings.yt_dlp_path {
+            set_configured_yt_dlp(path.clone());
+        }
+
+        // Flags win over the settings file, a cookies file wins over a browser
+        let cookies = cli.cookies.clone().map(CookieSource::File)
+            .or_else(|| cli.cookies_from_browser.clone().map(CookieSource::Browser))
+            .or_else(|| settings.cookies_path.clone().map(CookieSource::File))
+            .or_else(|| settings.cookies_from_browser.clone().map(CookieSource::Browser));
+        if let Some(source) = &cookies {
+            set_cookies(source.clone());
+        }
+        set_audio_quality(settings.quality());
+        set_offline(cli.offline || settings.offline);
+
+        let mut player = AudioPlayer::new();
+        player.set_volume(settings.volume);
         // Load default sync for now, async search will use the channel
         // player.load_source("audio.mp3"); // Removed default local file loading
 
+        let theme = if cli.no_color { MONO } else { Theme::by_name(&settings.theme) };
         let graph_config = GraphConfig {
-            samples: 200,
+            samples: settings.scope_samples,
             sampling_rate: player.sample_rate,
-            scale: 1.0,
-            width: 200,
-            show_ui: false,
-            labels_color: PIPBOY_GREEN,
-            axis_color: Color::DarkGray,
-            palette: vec![PIPBOY_GREEN, COLOR_RED],
+            scale: settings.scope_scale,
+            scatter: settings.scope_scatter,
+            persistence: settings.scope_persistence.min(MAX_PERSISTENCE),
+            width: 4096, // Up to twice this many samples; past the chart's width they're decimated
+            references: settings.scope_references,
+            show_ui: settings.scope_labels,
+            marker_type: if settings.scope_braille { Marker::Braille } else { Marker::Dot },
+            labels_color: theme.primary,
+            axis_color: axis_color(&theme),
+            palette: vec![theme.primary, theme.error],
             ..Default::default()
         };
 
         let (event_tx, event_rx) = channel();
+        let prefetch_tx = event_tx.clone();
+        let prefetch = DownloadManager::new(move |url, cancel| AudioPlayer::prefetch(url, &prefetch_tx, cancel));
 
-        App {
-            current_tab: 4, // RADIO tab
+        let mut app = App {
+            current_tab: Tab::from_index(settings.current_tab),
             radio_state,
-            radio_stations: vec![
-                "Classical Radio".to_string(),
-                "Diamond City Radio".to_string(),
-                "Nuka-Cola Family Radio".to_string(),
-                "Radio Freedom".to_string(),
-                "Distress Signal".to_string(),
-                "Distress Signal".to_string(),
-                "Distress Signal".to_string(),
-                "Emergency Frequency RJ1138".to_string(),
-                "Military Frequency AF95".to_string(),
-                "Silver Shroud Radio".to_string(),
-            ],
+            radio_stations,
+            station_draft: None,
             player,
             oscilloscope: Oscilloscope::default(),
+            spectroscope: Spectroscope::default(),
+            vectorscope: Vectorscope::default(),
+            spectrogram: Spectrogram::default(),
+            persistence: Persistence::default(),
+            channel_filter: ChannelFilter::default(),
+            processing: Processing::default(),
+            pitch: PitchTracker::default(),
+            scope_data: Vec::new(),
+            scope_view: ScopeView::Waveform,
+            draw_config: graph_config.clone(),
             graph_config,
+            theme,
             input_mode: InputMode::Normal,
-            search_input: String::new(),
-            cursor_position: 0,
-            loading_status: None,
+            search_input: TextInput::default(),
+            feed_input: TextInput::default(),
+            search_provider: Provider::default(),
+            status: Status::default(),
             is_loading: false,
+            download: None,
+            show_help: false,
+            mic: None,
+            format_before_mic: (0, 0),
+            track_info: None,
+            pending_confirm: None,
+            help_log: false,
+            help_scroll: 0,
+            tick: 0,
+            show_remaining: settings.show_remaining,
+            hit_regions: HitRegions::default(),
             search_results: Vec::new(),
-            search_results_state: ListState::default(),
+            all_results: Vec::new(),
+            results_sort: ResultSort::Relevance,
+            hide_short_results: false,
+            results_provider: Provider::default(),
+            search_results_state: TableState::default(),
+            results_are_playlist: false,
+            search_cache: SearchCache::load(),
+            searching: None,
+            refreshing_search: None,
+            last_search: None,
+            search_history: SearchHistory::new(settings.search_history.clone()),
+            play_queue: Vec::new(),
+            queue_index: None,
+            up_next_state: ListState::default(),
+            queue_focused: false,
+            prefetch,
+            prefetched: HashMap::new(),
+            awaiting_prefetch: None,
+            requested_track: None,
+            current_track: None,
+            current_path: None,
+            now_playing: None,
+            artwork: None,
+            pending_resume: None,
+            history: History::load(),
+            history_recorded: false,
+            listened: Duration::ZERO,
+            listen_checked: None,
+            download_cancel: Arc::new(AtomicBool::new(false)),
+            settings: settings.clone(),
+            saved_knobs: (0.0, Tab::default(), 0.0, 0, [false; 4], 0),
+            knobs_changed_at: None,
+            yt_dlp_status: None,
+            cookies,
+            podcasts: Podcasts::load(),
+            favorites: Favorites::load(),
+            library_hits: Vec::new(),
+            library_state: ListState::default(),
+            library_search_due: None,
+            cache_usage: CacheUsage::default(),
+            file_browser: FileBrowserState::new(files::music_dir(settings.music_dir.as_deref())),
             event_tx,
             event_rx,
+        };
+
+        let hold = Duration::from_secs_f64(app.settings.peak_hold_secs.clamp(0.0, 60.0));
+        app.oscilloscope.hold.hold = hold;
+        app.spectroscope.hold.hold = hold;
+        app.saved_knobs = app.knobs();
+        cache::remove_orphans();
+        app.cache_changed();
+        app.check_yt_dlp();
+        app.restore_session(settings.last_track);
+        if app.current_tab == Tab::Map {
+            app.browse_files();
         }
+        app
     }
 
-    pub fn next_station(&mut self) {
-        let i = match self.radio_state.selected() {
-            Some(i) => {
-                if i >= self.radio_stations.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+    pub fn push_status(&mut self, text: impl Into<String>) {
+        self.status.push(text.into(), StatusLevel::Info, Instant::now());
+    }
+
+    /// For steps of something still going; each one replaces the last instead of queueing
+    pub fn push_progress(&mut self, text: impl Into<String>) {
+        self.status.push(text.into(), StatusLevel::Progress, Instant::now());
+    }
+
+    pub fn push_error(&mut self, text: impl Into<String>) {
+        self.status.push(text.into(), StatusLevel::Error, Instant::now());
+    }
+
+    /// Fill in the now playing panel for the track just handed to the player.
+    /// The file's ID3 tags win over what the search result or feed said.
+    pub fn track_started(&mut self) {
+        // Something's playing, so the mic has to go
+        // The new track already set the player's format, nothing to put back
+        if self.mic.is_some() {
+            self.finish_mic();
+            self.push_status("Mic off for playback");
+        }
+        self.sync_sample_rate();
+        self.listened = Duration::ZERO;
+        self.listen_checked = None;
+        let previous = self.now_playing.take().map(|n| n.url);
+        let Some(track) = &self.current_track else { return };
+        let tags = self.player.now_playing.clone().unwrap_or_default();
+        self.now_playing = Some(NowPlaying {
+            title: tags.title.unwrap_or_else(|| track.title.clone()),
+            artist: tags.artist.or_else(|| track.artist.clone()),
+            url: track.url.clone(),
+            started_at: Instant::now(),
+        });
+        let url = track.url.clone();
+        if previous.as_deref() != Some(url.as_str()) {
+            self.load_artwork(url);
+            // The last track's peaks say nothing about this one
+            self.oscilloscope.hold.reset();
+            self.spectroscope.hold.reset();
+            self.pitch.clear();
+        }
+        if let Some(previous) = previous {
+            self.follow_playing(&previous);
+        }
+        self.update_scope_palette();
+    }
+
+    // Look for the new track's cover in the background, showing the placeholder meanwhile
+    fn load_artwork(&mut self, url: String) {
+        self.artwork = None;
+        let file = self.current_path.as_ref().map(PathBuf::from);
+        let metadata = file.as_deref().and_then(cache::read_metadata).unwrap_or_default();
+        // Sidecars from before thumbnails were kept still know the video id
+        let thumbnail = metadata.thumbnail.clone().or_else(|| metadata.youtube_id().map(|id| format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id)));
+        artwork::load_async(url, ArtworkSource { file, thumbnail }, self.event_tx.clone());
+    }
+
+    pub fn artwork_ready(&mut self, url: String, artwork: Option<RgbImage>) {
+        if self.playing_url() == Some(url.as_str()) {
+            self.artwork = artwork;
+        }
+    }
+
+    /// Chapters and skips for the playing file, looked up after it started
+    pub fn metadata_ready(&mut self, path: String) {
+        if self.current_path.as_ref() != Some(&path) {
+            return;
+        }
+        if let Some(track) = self.current_track.as_mut() {
+            track.load_metadata(Path::new(&path));
+            // The thumbnail may only be known now
+            if self.artwork.is_none() {
+                let url = track.url.clone();
+                self.load_artwork(url);
             }
-            None => 0,
+        }
+    }
+
+    /// The URL (or local path) of the track in the player, for marking it in lists
+    pub fn playing_url(&self) -> Option<&str> {
+        self.current_track.as_ref().map(|t| t.url.as_str())
+    }
+
+    // A list whose selection sat on the track that just finished moves it to the one that started,
+    // which also scrolls it into view. Lists the user has moved away in are left alone.
+    fn follow_playing(&mut self, previous: &str) {
+        let Some(url) = self.playing_url().map(str::to_string) else { return };
+        let selected_result = self.search_results_state.selected().and_then(|i| self.search_results.get(i));
+        if selected_result.is_some_and(|r| r.url == previous)
+            && let Some(i) = self.search_results.iter().position(|r| r.url == url) {
+            self.search_results_state.select(Some(i));
+        }
+        if self.file_browser.selected().is_some_and(|e| e.path.to_string_lossy() == previous) {
+            self.file_browser.select_path(&url);
+        }
+    }
+
+    // Report the detected yt-dlp version (or why it wasn't found) without blocking startup
+    fn check_yt_dlp(&self) {
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _ = tx.send(AppEvent::YtDlpChecked(yt_dlp_version()));
+        });
+    }
+
+    // Reload the last track paused at its saved position, re-downloading it if the cache file is gone
+    fn restore_session(&mut self, last_track: Option<LastTrack>) {
+        let Some(last) = last_track else { return };
+        let offset = Duration::from_secs_f64(last.position_secs.max(0.0));
+        let mut track = TrackInfo::new(last.title, last.url);
+
+        if Path::new(&last.path).exists() {
+            track.load_metadata(Path::new(&last.path));
+            let resumed = self.player.play_file_from(Path::new(&last.path), offset);
+            self.pause_for_resume();
+            match resumed {
+                Ok(()) => self.push_status(format!("Resumed: {} (Space to play)", track.title)),
+                Err(e) => self.push_error(e),
+            }
+            self.current_track = Some(track);
+            self.current_path = Some(last.path);
+            self.history_recorded = true; // Counted last session
+            self.track_started();
+        } else if !track.url.is_empty() {
+            self.push_progress(format!("Re-downloading: {}...", track.title));
+            self.is_loading = true;
+            self.pending_resume = Some(offset);
+            self.player.begin_loading();
+            AudioPlayer::load_source_async(track.url.clone(), self.event_tx.clone(), self.new_download_token());
+            self.requested_track = Some(track);
+        }
+    }
+
+    /// Evict old downloads once the cache grows past its limit, never touching `keep`
+    pub fn enforce_cache_limit(&mut self, keep: &Path) {
+        cache::enforce_limit(self.settings.cache_max_bytes(), &[keep]);
+        self.cache_changed();
+    }
+
+    /// Re-read the cache's size and which results are in it, after downloads or deletions
+    pub fn cache_changed(&mut self) {
+        self.cache_usage = cache::usage();
+        mark_cached(&mut self.all_results);
+        mark_cached(&mut self.search_results);
+    }
+
+    /// Switch to the next download quality. Only affects new downloads.
+    pub fn cycle_quality(&mut self) {
+        let quality = self.settings.quality().next();
+        self.settings.audio_quality = Some(quality);
+        set_audio_quality(quality);
+        // Prefetched files were downloaded at the old quality
+        self.prefetched.clear();
+        if let Some(index) = self.queue_index {
+            self.prefetch_upcoming(index);
+        }
+        match self.save_session() {
+            Ok(()) => self.push_status(format!("Download quality: {}", quality.name())),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.graph_config.labels_color = theme.primary;
+        self.graph_config.axis_color = axis_color(&theme);
+        self.update_scope_palette();
+    }
+
+    /// The scope's first line takes the playing station's color, if it has one
+    pub fn update_scope_palette(&mut self) {
+        let first = self
+            .playing_station()
+            .and_then(|i| self.radio_stations[i].color.as_deref().and_then(color_by_name))
+            .filter(|_| !self.theme.mono)
+            .unwrap_or(self.theme.primary);
+        self.graph_config.palette = vec![first, self.theme.error];
+    }
+
+    /// One more frame of trail behind the waveform, back to none past the longest
+    pub fn cycle_persistence(&mut self) {
+        let frames = if self.graph_config.persistence >= MAX_PERSISTENCE { 0 } else { self.graph_config.persistence + 1 };
+        self.graph_config.persistence = frames;
+        self.push_status(match frames {
+            0 => "Persistence: off".to_string(),
+            frames => format!("Persistence: {} frames", frames),
+        });
+    }
+
+    /// The newest `len` samples for the scope into `scope_data`, reusing its buffers: DC blocked and
+    /// gained, as mid and side if that's on, soloed and muted channels zeroed
+    pub fn fill_scope_window(&mut self, len: usize) {
+        let mut window = std::mem::take(&mut self.scope_data);
+        self.player.fill_window(len, &mut window);
+        self.shape_scope_window(&mut window);
+        self.scope_data = window;
+    }
+
+    /// A copy of what fill_scope_window gives, to keep
+    pub fn scope_window(&self, len: usize) -> Matrix<f64> {
+        let mut window = self.player.get_window(len);
+        self.shape_scope_window(&mut window);
+        window
+    }
+
+    fn shape_scope_window(&self, window: &mut Matrix<f64>) {
+        self.processing.apply(window, self.player.sample_rate);
+        if self.graph_config.mid_side {
+            mid_side(window);
+        }
+        self.channel_filter.apply(window);
+    }
+
+    /// Bring `draw_config` up to date for a chart `columns` points across: mid and side get colors
+    /// of their own, silenced channels get theirs dimmed. Done in place, so a frame doesn't allocate.
+    pub fn update_draw_config(&mut self, columns: u32) {
+        let mut palette = std::mem::take(&mut self.draw_config.palette);
+        palette.clear();
+        if self.graph_config.mid_side && !self.theme.mono {
+            palette.extend([self.theme.accent, self.theme.warning]);
+        } else {
+            palette.extend_from_slice(&self.graph_config.palette);
+        }
+        for (n, color) in palette.iter_mut().enumerate() {
+            if self.channel_filter.is_silenced(n) {
+                *color = dim_color(*color, 0.35);
+            }
+        }
+        self.draw_config = GraphConfig { palette, columns, ..self.graph_config };
+    }
+
+    /// Bring the scope up to the rate of the source the player just opened
+    pub fn sync_sample_rate(&mut self) {
+        if self.graph_config.sampling_rate != self.player.sample_rate {
+            self.graph_config.sampling_rate = self.player.sample_rate;
+            // Trails drawn at the old rate would be stretched against the new one
+            self.persistence.clear();
+            self.pitch.clear();
+        }
+    }
+
+    /// `D` on RADIO
+    pub fn toggle_dc_block(&mut self) {
+        self.processing.dc_block = !self.processing.dc_block;
+        self.push_status(if self.processing.dc_block { "Scope DC block on" } else { "Scope DC block off" });
+    }
+
+    /// `[`/`]` for the first channel's scope gain, `{`/`}` for the second's
+    pub fn step_scope_gain(&mut self, channel: usize, up: bool) {
+        if channel < self.player.channels {
+            self.processing.step_gain(channel, up);
+        }
+    }
+
+    /// `M` on RADIO
+    pub fn toggle_mid_side(&mut self) {
+        self.graph_config.mid_side = !self.graph_config.mid_side;
+        self.persistence.clear();
+        self.push_status(if self.graph_config.mid_side { "Scope: mid / side" } else { "Scope: left / right" });
+    }
+
+    /// `b` on RADIO: braille dots, with twice the resolution across and four times down, or plain ones
+    pub fn toggle_braille(&mut self) {
+        let braille = self.graph_config.marker_type != Marker::Braille;
+        self.graph_config.marker_type = if braille { Marker::Braille } else { Marker::Dot };
+        self.persistence.clear();
+        self.push_status(if braille { "Scope: braille" } else { "Scope: dots" });
+    }
+
+    /// `g` on RADIO
+    pub fn toggle_references(&mut self) {
+        self.graph_config.references = !self.graph_config.references;
+        self.push_status(if self.graph_config.references { "Scope reference lines on" } else { "Scope reference lines off" });
+    }
+
+    /// `U` on RADIO
+    pub fn toggle_scope_labels(&mut self) {
+        self.graph_config.show_ui = !self.graph_config.show_ui;
+        self.push_status(if self.graph_config.show_ui { "Scope axis labels on" } else { "Scope axis labels off" });
+    }
+
+    /// Alt+number solos a channel, Shift+number mutes it
+    pub fn channel_key_pressed(&mut self, c: char) {
+        let Some((channel, shifted)) = channel_key(c) else { return };
+        let channels = self.player.channels;
+        if shifted {
+            self.channel_filter.toggle_mute(channel, channels);
+        } else {
+            self.channel_filter.toggle_solo(channel, channels);
+        }
+    }
+
+    /// Ctrl+S: the scope's window as a WAV and what's drawn of it as a CSV, in the snapshots directory
+    pub fn save_scope_snapshot(&mut self) {
+        if !self.player.scope_live() || (self.player.is_streaming_mode && self.mic.is_none()) {
+            self.push_status("Nothing on the scope to save");
+            return;
+        }
+        let samples = self.graph_config.samples as usize;
+        let window = self.scope_window(self.oscilloscope.window_len(self.graph_config.samples));
+        let start = self.oscilloscope.trigger_offset(&window);
+        let drawn: Vec<Vec<f64>> = window.iter().map(|channel| channel.iter().skip(start).take(samples).copied().collect()).collect();
+        let names: Vec<String> = (0..window.len()).map(|n| channel_label(n, self.graph_config.mid_side).into_owned()).collect();
+        match snapshot::save(&data_path(SNAPSHOTS_DIR), &snapshot::stamp(now_secs()), &window, self.player.sample_rate, &drawn, &names) {
+            Ok((wav, csv)) => self.push_status(format!("Saved {} and {}", wav.display(), csv.display())),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// `m` on RADIO: point the scope at the microphone, or back at playback
+    pub fn toggle_mic(&mut self) {
+        if self.mic.is_some() {
+            self.close_mic();
+            self.push_status("Mic off");
+            return;
+        }
+        let tx = self.event_tx.clone();
+        match Mic::open(move |e| {
+            let _ = tx.send(AppEvent::MicError(e));
+        }) {
+            Ok(mic) => {
+                // Playing through the speakers while listening would feed back
+                self.player.stop();
+                self.format_before_mic = (self.player.sample_rate, self.player.channels);
+                self.player.sample_rate = mic.sample_rate;
+                self.player.channels = mic.channels;
+                self.player.live_input = Some(mic.input.clone());
+                self.mic = Some(mic);
+                self.sync_sample_rate();
+                self.push_status("Mic on (R records, m to leave)");
+            }
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// Leave MIC mode, finishing any recording, and go back to the player's own format
+    pub fn close_mic(&mut self) {
+        if self.mic.is_none() {
+            return;
+        }
+        (self.player.sample_rate, self.player.channels) = self.format_before_mic;
+        self.finish_mic();
+        self.sync_sample_rate();
+    }
+
+    // Stop listening and finish the recording so its WAV header gets the real sizes
+    fn finish_mic(&mut self) {
+        let Some(mut mic) = self.mic.take() else { return };
+        self.player.live_input = None;
+        match mic.stop_recording() {
+            Ok(Some(path)) => self.push_status(format!("Recorded {}", path.display())),
+            Ok(None) => {}
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn mic_failed(&mut self, error: String) {
+        self.close_mic();
+        self.push_error(error);
+    }
+
+    /// `R` in MIC mode: record what comes in to a WAV under the cache directory, or stop
+    pub fn toggle_mic_recording(&mut self) {
+        let Some(mic) = &mut self.mic else {
+            self.push_status("Recording needs the mic on (m)");
+            return;
         };
-        self.radio_state.select(Some(i));
+        if mic.recording_for().is_some() {
+            match mic.stop_recording() {
+                Ok(path) => self.push_status(format!("Recorded {}", path.unwrap_or_default().display())),
+                Err(e) => self.push_error(e),
+            }
+            return;
+        }
+        let dir = Path::new(cache::CACHE_DIR).join("recordings");
+        let path = dir.join(format!("mic-{}.wav", snapshot::stamp(now_secs())));
+        let started = std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))
+            .and_then(|()| mic.start_recording(&path));
+        match started {
+            Ok(()) => self.push_status(format!("Recording to {}", path.display())),
+            Err(e) => self.push_error(e),
+        }
     }
 
-    pub fn previous_station(&mut self) {
-        let i = match self.radio_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.radio_stations.len() - 1
-                } else {
-                    i - 1
+    /// Write out what the mic has recorded since last frame
+    pub fn flush_mic(&mut self) {
+        if let Some(Err(e)) = self.mic.as_mut().map(Mic::flush) {
+            self.mic_failed(e);
+        }
+    }
+
+    /// A fresh pitch reading every so often while RADIO is showing, from what's playing before any scope processing
+    pub fn update_pitch(&mut self) {
+        let now = Instant::now();
+        if self.current_tab != Tab::Radio || !self.pitch.due(now) {
+            return;
+        }
+        // Two periods of the lowest note take more samples the higher the rate
+        let window = self.player.get_window(pitch::window(self.player.sample_rate));
+        self.pitch.update(&window, self.player.sample_rate, now);
+    }
+
+    pub fn cycle_scope_view(&mut self) {
+        self.scope_view = self.scope_view.next();
+        self.persistence.clear();
+    }
+
+    /// Scope keys go to whichever display modes are showing
+    pub fn handle_scope_event(&mut self, event: Event) {
+        match self.scope_view {
+            ScopeView::Waveform => self.oscilloscope.handle(event),
+            ScopeView::Spectrum | ScopeView::Waterfall => self.spectroscope.handle(event),
+            ScopeView::Both => {
+                self.oscilloscope.handle(event.clone());
+                self.spectroscope.handle(event);
+            }
+            ScopeView::Vectors => self.vectorscope.handle(event),
+        }
+    }
+
+    pub fn cycle_theme(&mut self) {
+        // From monochrome, back to colors where they were left
+        let theme = if self.theme.mono { Theme::by_name(&self.settings.theme) } else { self.theme.next() };
+        self.set_theme(theme);
+        self.settings.theme = self.theme.name.to_string();
+        match self.save_session() {
+            Ok(()) => self.push_status(format!("Theme: {}", self.theme.name)),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// Switch between the color theme and no colors at all. Not saved: --no-color and
+    /// NO_COLOR decide how the next run starts.
+    pub fn toggle_monochrome(&mut self) {
+        if self.theme.mono {
+            self.set_theme(Theme::by_name(&self.settings.theme));
+            self.push_status(format!("Theme: {}", self.theme.name));
+        } else {
+            self.set_theme(MONO);
+            self.push_status("Monochrome: on");
+        }
+    }
+
+    /// Rough bitrate of the loaded file, from its size and duration
+    pub fn current_bitrate_kbps(&self) -> Option<u64> {
+        let size = std::fs::metadata(self.current_path.as_ref()?).ok()?.len();
+        let seconds = self.player.total_duration?.as_secs_f64();
+        (seconds > 0.0).then(|| (size as f64 * 8.0 / seconds / 1000.0).round() as u64)
+    }
+
+    /// Find and fix duplicates in the library and leftovers in the cache
+    pub fn check_library(&mut self) {
+        let report = maintenance::check_library(&mut self.podcasts.feeds, &mut self.favorites.items, &mut self.history.plays);
+        self.cache_changed();
+        let saved = if report.library_changed() {
+            self.history.save_async();
+            self.podcasts.save().and_then(|()| self.favorites.save())
+        } else {
+            Ok(())
+        };
+        match saved {
+            Ok(()) => self.push_status(report.describe()),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn clear_cache(&mut self) {
+        let keep: Vec<&Path> = self.current_path.iter().map(Path::new).collect();
+        let removed = cache::clear(&keep);
+        self.cache_changed();
+        self.push_status(format!("Cleared {} cached files", removed));
+    }
+
+    /// Cancel whatever download is still running and hand out a flag for the next one
+    pub fn new_download_token(&mut self) -> Arc<AtomicBool> {
+        self.download = None;
+        self.download_cancel.store(true, Ordering::Relaxed);
+        self.download_cancel = Arc::new(AtomicBool::new(false));
+        Arc::clone(&self.download_cancel)
+    }
+
+    pub fn download_progressed(&mut self, downloaded: u64, total: Option<u64>) {
+        let title = self.requested_track.as_ref().map(|t| t.title.clone()).unwrap_or_default();
+        self.download.get_or_insert_with(|| Download::new(title)).progressed(downloaded, total);
+    }
+
+    /// Move to RADIO for the track that just loaded; from DATA, once its gauge has shown the download done
+    pub fn download_finished(&mut self) {
+        match self.download.as_mut() {
+            Some(download) if self.current_tab == Tab::Data => download.finish(),
+            _ => {
+                self.download = None;
+                self.switch_tab(Tab::Radio);
+            },
+        }
+    }
+
+    /// Called every loop; true once a finished download's gauge has been taken down
+    pub fn end_download_flash(&mut self) -> bool {
+        if !self.download.as_ref().is_some_and(Download::flash_over) {
+            return false;
+        }
+        self.download = None;
+        self.switch_tab(Tab::Radio);
+        true
+    }
+
+    /// Downloads still to go, counting the one being waited on once even when a prefetch worker has it
+    pub fn downloads_queued(&self) -> usize {
+        let waiting = self.requested_track.as_ref().map_or("", |track| track.url.as_str());
+        self.prefetch.outstanding_with(waiting)
+    }
+
+    pub fn cancel_download(&mut self) {
+        self.download_cancel.store(true, Ordering::Relaxed);
+        // Can't stop a prefetch worker, just stop waiting for it
+        if self.awaiting_prefetch.take().is_some() {
+            let _ = self.event_tx.send(AppEvent::AudioError("Download cancelled".to_string()));
+        }
+    }
+
+    /// What the open path or URL prompt types into
+    pub fn prompt_input(&mut self) -> &mut TextInput {
+        match self.input_mode {
+            InputMode::FeedUrl => &mut self.feed_input,
+            _ => &mut self.search_input,
+        }
+    }
+
+    /// Subscribe to a feed; it shows up once the first fetch finishes
+    pub fn add_feed(&mut self, url: String) {
+        if is_offline() {
+            self.push_status("Offline: can't fetch feeds");
+            return;
+        }
+        self.push_progress(format!("Fetching feed: {}...", url));
+        self.fetch_feed_async(url);
+    }
+
+    pub fn refresh_feeds(&mut self) {
+        if is_offline() {
+            self.push_status("Offline: can't refresh feeds");
+            return;
+        }
+        let urls: Vec<String> = match self.podcasts.opened() {
+            Some(feed) => vec![feed.url.clone()],
+            None => self.podcasts.feeds.iter().map(|f| f.url.clone()).collect(),
+        };
+        self.push_progress(format!("Refreshing {} feeds...", urls.len()));
+        for url in urls {
+            self.fetch_feed_async(url);
+        }
+    }
+
+    fn fetch_feed_async(&self, url: String) {
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let result = fetch_feed(&url);
+            let _ = tx.send(AppEvent::FeedRefreshed(url, result));
+        });
+    }
+
+    pub fn feed_refreshed(&mut self, url: String, result: Result<Feed, String>) {
+        match result {
+            Ok(feed) => {
+                self.push_status(format!("{}: {} episodes", feed.title, feed.episodes.len()));
+                self.podcasts.merge(feed);
+                if let Err(e) = self.podcasts.save() {
+                    self.push_error(e);
                 }
+            },
+            Err(e) => self.push_error(format!("Feed {} failed: {}", url, e)),
+        }
+    }
+
+    /// Ask before running `action`; nothing is asked when there's nothing for it to act on
+    pub fn confirm(&mut self, action: ConfirmAction) {
+        match self.confirm_question(action) {
+            Some(_) => {
+                self.pending_confirm = Some(action);
+                self.input_mode = InputMode::Confirm;
             }
-            None => 0,
+            None if action == ConfirmAction::ClearCache => self.push_status("Cache is already empty"),
+            None => {}
+        }
+    }
+
+    pub fn confirm_question(&self, action: ConfirmAction) -> Option<String> {
+        match action {
+            ConfirmAction::RemoveFeed => {
+                let feed = self.podcasts.selected_feed().filter(|_| !self.podcasts.is_open())?;
+                Some(format!("Unsubscribe from {}?", feed.title))
+            }
+            ConfirmAction::DeleteStation => {
+                let station = self.radio_stations.get(self.radio_state.selected()?)?;
+                Some(format!("Delete station {}?", station.name))
+            }
+            ConfirmAction::ClearCache => (self.cache_usage.files > 0).then(|| format!("Delete {} cached files?", self.cache_usage.files)),
+            ConfirmAction::Quit if self.is_loading => Some("A download is still running. Quit anyway?".to_string()),
+            ConfirmAction::Quit => Some("Stop playback and quit?".to_string()),
+        }
+    }
+
+    /// Run the action that was just confirmed. Quit is left to the caller.
+    pub fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::RemoveFeed => self.remove_selected_feed(),
+            ConfirmAction::DeleteStation => self.delete_selected_station(),
+            ConfirmAction::ClearCache => self.clear_cache(),
+            ConfirmAction::Quit => {}
+        }
+    }
+
+    pub fn remove_selected_feed(&mut self) {
+        if let Some(feed) = self.podcasts.remove_selected_feed() {
+            match self.podcasts.save() {
+                Ok(()) => self.push_status(format!("Unsubscribed from {}", feed.title)),
+                Err(e) => self.push_error(e),
+            }
+        }
+    }
+
+    /// Enter on the INV tab: open the selected row, or play the selected episode/favorite
+    pub fn podcast_enter(&mut self) {
+        if !self.podcasts.is_open() {
+            self.podcasts.open_selected(self.favorites.items.len());
+            return;
+        }
+        if self.podcasts.favorites_open {
+            // Favorites play through like a playlist
+            let Some(selected) = self.podcasts.episode_state.selected() else { return };
+            self.play_queue = self
+                .favorites
+                .items
+                .iter()
+                .map(|f| YtDlpResult { title: f.title.clone(), url: f.url.clone(), webpage_url: None, duration: None, uploader: None, cached: false })
+                .collect();
+            self.play_queue_entry(selected, false);
+            return;
+        }
+        let Some(episode) = self.podcasts.selected_episode().cloned() else { return };
+        self.push_progress(format!("Downloading: {}...", episode.title));
+        self.is_loading = true;
+        self.player.begin_loading();
+        self.queue_index = None;
+        let feed = self.podcasts.selected_feed().map(|f| f.title.clone());
+        self.requested_track = Some(TrackInfo::new(episode.title, episode.url.clone()).with_artist(feed));
+        AudioPlayer::load_source_async(episode.url, self.event_tx.clone(), self.new_download_token());
+    }
+
+    pub fn toggle_favorite(&mut self, url: &str, title: &str) {
+        let added = self.favorites.toggle(url, title);
+        if self.podcasts.favorites_open {
+            let len = self.favorites.items.len();
+            let selected = self.podcasts.episode_state.selected().map(|i| i.min(len.saturating_sub(1)));
+            self.podcasts.episode_state.select(selected.filter(|_| len > 0));
+        }
+        match self.favorites.save() {
+            Ok(()) if added => self.push_status(format!("★ Added to favorites: {}", title)),
+            Ok(()) => self.push_status(format!("Removed from favorites: {}", title)),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// F outside the results list: the selected history entry on STAT or episode/favorite on INV, otherwise whatever is playing
+    pub fn toggle_favorite_selected(&mut self) {
+        let selection = if self.current_tab == Tab::Stat {
+            self.history.selected().map(|(title, url)| (url, title))
+        } else if self.current_tab != Tab::Inv {
+            None
+        } else if self.podcasts.favorites_open {
+            self.podcasts
+                .episode_state
+                .selected()
+                .and_then(|i| self.favorites.items.get(i))
+                .map(|f| (f.url.clone(), f.title.clone()))
+        } else {
+            self.podcasts.selected_episode().map(|e| (e.url.clone(), e.title.clone()))
         };
-        self.radio_state.select(Some(i));
+        let target = selection.or_else(|| self.current_track.as_ref().map(|t| (t.url.clone(), t.title.clone())));
+        if let Some((url, title)) = target {
+            self.toggle_favorite(&url, &title);
+        }
     }
 
-    pub fn next_tab(&mut self) {
-        self.current_tab = (self.current_tab + 1) % 5;
+    pub fn toggle_favorite_result(&mut self) {
+        let Some(result) = self.search_results_state.selected().and_then(|i| self.search_results.get(i)) else { return };
+        let (url, title) = (result.url.clone(), result.title.clone());
+        self.toggle_favorite(&url, &title);
     }
 
-    pub fn previous_tab(&mut self) {
-        if self.current_tab == 0 {
-            self.current_tab = 4;
+    // Name and entries of the Favorites list or feed selected on the INV tab
+    fn selected_inv_list(&self) -> (String, Vec<M3uEntry>) {
+        match self.podcasts.selected_feed() {
+            Some(feed) => (
+                feed.title.clone(),
+                feed.episodes
+                    .iter()
+                    .map(|e| M3uEntry { title: e.title.clone(), url: e.url.clone(), duration: e.duration })
+                    .collect(),
+            ),
+            None => (
+                "Favorites".to_string(),
+                self.favorites
+                    .items
+                    .iter()
+                    .map(|f| M3uEntry { title: f.title.clone(), url: f.url.clone(), duration: None })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Ask where to export the selected INV list, suggesting a file named after it
+    pub fn start_export(&mut self) {
+        let (name, _) = self.selected_inv_list();
+        let file: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.search_input.set(format!("{}.m3u", file.trim_matches('_')));
+        self.input_mode = InputMode::ExportPath;
+    }
+
+    pub fn export_m3u(&mut self, path: &str) {
+        let (name, entries) = self.selected_inv_list();
+        match m3u::export(&entries, Path::new(path)) {
+            Ok(()) => self.push_status(format!("Exported {} ({} tracks) to {}", name, entries.len(), path)),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// Ask for a backup/restore path, starting from the usual file name
+    pub fn start_backup_input(&mut self, mode: InputMode) {
+        self.search_input.set(DEFAULT_BACKUP_PATH);
+        self.input_mode = mode;
+    }
+
+    pub fn backup_library(&mut self, path: &str) {
+        let backup = Backup::collect(&self.podcasts, &self.favorites, &self.history);
+        match backup.write(Path::new(path)) {
+            Ok(()) => self.push_status(format!(
+                "Backed up {} feeds, {} favorites, {} plays to {}",
+                backup.feeds.len(),
+                backup.favorites.len(),
+                backup.history.len(),
+                path
+            )),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn restore_library(&mut self, path: &str) {
+        let backup = match Backup::read(Path::new(path)) {
+            Ok(backup) => backup,
+            Err(e) => {
+                self.push_error(e);
+                return;
+            },
+        };
+        let summary = backup.merge_into(&mut self.podcasts, &mut self.favorites, &mut self.history);
+        self.history.save_async();
+        match self.podcasts.save().and_then(|()| self.favorites.save()) {
+            Ok(()) => self.push_status(summary.describe()),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn start_library_search(&mut self) {
+        self.search_input.clear();
+        self.library_hits.clear();
+        self.library_state.select(None);
+        self.input_mode = InputMode::LibrarySearch;
+    }
+
+    /// The query changed; search once typing pauses
+    pub fn library_query_changed(&mut self) {
+        self.library_search_due = Some(Instant::now() + LIBRARY_SEARCH_DEBOUNCE);
+    }
+
+    /// Run a pending library search if it's due. Called every frame.
+    pub fn update_library_search(&mut self) {
+        if self.library_search_due.is_none_or(|due| Instant::now() < due) {
+            return;
+        }
+        self.library_search_due = None;
+        self.library_hits = library::search(self.search_input.as_str(), &self.podcasts, &self.favorites, &self.history);
+        self.library_state.select(if self.library_hits.is_empty() { None } else { Some(0) });
+    }
+
+    pub fn play_library_hit(&mut self) {
+        let Some(hit) = self.library_state.selected().and_then(|i| self.library_hits.get(i)).cloned() else { return };
+        self.play_queue = vec![YtDlpResult { title: hit.title, url: hit.url, webpage_url: None, duration: None, uploader: None, cached: false }];
+        self.play_queue_entry(0, false);
+    }
+
+    /// Stop downloads and delete what they left half-written. Called on every way out of the app.
+    pub fn shutdown(&mut self) {
+        // A recording's WAV header only gets its sizes once it's finished
+        self.close_mic();
+        // Nowhere to show an error anymore
+        let _ = self.save_session();
+        let _ = self.history.save(); // Listening time of the last track
+        self.cancel_download();
+        self.prefetch.shutdown();
+
+        // Cancelled downloads kill yt-dlp and clean up within a poll interval or two
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while cache::downloads_in_flight() > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        // Whatever is still going gets its files removed out from under it, never the one the sink is playing
+        let keep: Vec<&Path> = self.current_path.iter().map(Path::new).collect();
+        cache::remove_in_flight(&keep);
+    }
+
+    /// Search, answering from the cache when it has fresh results (and refreshing them behind the scenes).
+    /// `force` skips the cache.
+    pub fn start_search(&mut self, query: String, provider: Provider, force: bool) {
+        self.last_search = Some(query.clone());
+        let key = search_key(provider, &query);
+        let ttl = Duration::from_secs(self.settings.search_cache_ttl_mins * 60);
+        let cached = if force { None } else { self.search_cache.get(&key, ttl).map(<[YtDlpResult]>::to_vec) };
+
+        match cached {
+            Some(results) => {
+                self.show_results(provider, results, false);
+                self.push_status(format!("Found {} results (cached)", self.search_results.len()));
+                self.searching = None;
+                self.refreshing_search = Some(key);
+            },
+            None => {
+                self.push_progress(format!("Searching {}: {}...", provider.name(), query));
+                self.searching = Some(key);
+                self.refreshing_search = None;
+            },
+        }
+        AudioPlayer::search_async(query, provider, self.event_tx.clone());
+    }
+
+    pub fn search_finished(&mut self, provider: Provider, query: String, listing: Listing) {
+        let key = search_key(provider, &query);
+        let results = listing.entries;
+        if let Err(e) = self.search_cache.insert(key.clone(), results.clone()) {
+            self.push_error(e);
+        }
+
+        if self.refreshing_search.as_ref() == Some(&key) {
+            // Swap the fresh list in under the user, keeping their place
+            self.refreshing_search = None;
+            let selected = self.search_results_state.selected();
+            self.all_results = results;
+            self.apply_result_view();
+            if let Some(i) = selected
+                && !self.search_results.is_empty() {
+                self.search_results_state.select(Some(i.min(self.search_results.len() - 1)));
+            }
+            self.push_status(format!("Found {} results", self.search_results.len()));
+        } else if self.searching.as_ref() == Some(&key) {
+            self.searching = None;
+            self.show_results(provider, results, false);
         } else {
-            self.current_tab -= 1;
+            // An earlier search the user has moved on from; the cache has it now
+            return;
+        }
+        self.report_skipped(listing.skipped);
+    }
+
+    pub fn search_failed(&mut self, provider: Provider, query: String, e: String) {
+        let key = Some(search_key(provider, &query));
+        if self.searching == key {
+            self.searching = None;
+            self.push_error(format!("Search Error: {}", e));
+            self.input_mode = self.input_mode.after_search(false);
+        } else if self.refreshing_search == key {
+            // The cached results are still up, just say the refresh didn't work
+            self.refreshing_search = None;
+            self.push_error(format!("Refresh failed ({}), showing cached results", e));
         }
     }
 
-    // Input Handling Helper Methods
-    pub fn move_cursor_left(&mut self) {
-        let cursor_moved_left = self.cursor_position.saturating_sub(1);
-        self.cursor_position = self.clamp_cursor(cursor_moved_left);
+    /// Whether the spinner should be going: a download or a search is under way
+    pub fn busy(&self) -> bool {
+        self.is_loading || self.searching.is_some()
     }
 
-    pub fn move_cursor_right(&mut self) {
-        let cursor_moved_right = self.cursor_position.saturating_add(1);
-        self.cursor_position = self.clamp_cursor(cursor_moved_right);
+    pub fn playlist_listed(&mut self, provider: Provider, listing: Listing) {
+        self.is_loading = false;
+        self.show_results(provider, listing.entries, true);
+        self.report_skipped(listing.skipped);
     }
 
-    pub fn enter_char(&mut self, new_char: char) {
-        self.search_input.insert(self.cursor_position, new_char);
-        self.move_cursor_right();
+    // Unreadable yt-dlp lines shouldn't make results vanish without a word
+    fn report_skipped(&mut self, skipped: usize) {
+        if skipped > 0 {
+            self.push_status(format!("{} unreadable entries skipped", skipped));
+        }
     }
 
-    pub fn delete_char(&mut self) {
-        let is_not_cursor_leftmost = self.cursor_position != 0;
-        if is_not_cursor_leftmost {
-            let current_index = self.cursor_position;
-            let from_left_to_current_index = current_index - 1;
+    /// Show search or playlist entries in the results list
+    pub fn show_results(&mut self, provider: Provider, results: Vec<YtDlpResult>, is_playlist: bool) {
+        self.all_results = results;
+        mark_cached(&mut self.all_results);
+        self.apply_result_view();
+        self.results_provider = provider;
+        self.results_are_playlist = is_playlist;
+        self.push_status(format!("Found {} results", self.search_results.len()));
+        if !self.search_results.is_empty() {
+            self.search_results_state.select(Some(0));
+        }
+        self.input_mode = self.input_mode.after_search(!self.search_results.is_empty());
+    }
 
-            self.search_input = self.search_input.chars().take(from_left_to_current_index).chain(self.search_input.chars().skip(current_index)).collect();
-            self.move_cursor_left();
+    // Rebuild the visible results from the full list with the current sort and filter
+    fn apply_result_view(&mut self) {
+        let hide_short = self.hide_short_results;
+        let mut results: Vec<YtDlpResult> = self
+            .all_results
+            .iter()
+            .filter(|r| !hide_short || r.duration.is_none_or(|d| d >= SHORT_RESULT_SECS))
+            .cloned()
+            .collect();
+        match self.results_sort {
+            ResultSort::Relevance => {},
+            // Longest first, unknown durations last
+            ResultSort::Duration => results.sort_by(|a, b| {
+                b.duration.unwrap_or(-1.0).total_cmp(&a.duration.unwrap_or(-1.0))
+            }),
+            ResultSort::Title => results.sort_by_key(|r| r.title.to_lowercase()),
         }
+        self.search_results = results;
+        self.search_results_state.select(if self.search_results.is_empty() { None } else { Some(0) });
     }
 
-    pub fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
-        new_cursor_pos.clamp(0, self.search_input.chars().count())
+    /// Pressing the active sort again goes back to yt-dlp's order
+    pub fn sort_results(&mut self, sort: ResultSort) {
+        self.results_sort = if self.results_sort == sort { ResultSort::Relevance } else { sort };
+        self.apply_result_view();
     }
 
-    pub fn reset_cursor(&mut self) {
-        self.cursor_position = 0;
+    pub fn toggle_short_results(&mut self) {
+        self.hide_short_results = !self.hide_short_results;
+        self.apply_result_view();
+        let hidden = self.all_results.len() - self.search_results.len();
+        self.push_status(format!("{} results, {} hidden", self.search_results.len(), hidden));
     }
 
-    // Search Result Navigation
-    pub fn next_search_result(&mut self) {
-        if self.search_results.is_empty() { return; }
-        let i = match self.search_results_state.selected() {
+    /// Play the selected result. Playlist entries become the play queue so playback continues through them.
+    pub fn play_selected_result(&mut self, stream: bool) {
+        let Some(selected) = self.search_results_state.selected() else { return };
+        if selected >= self.search_results.len() {
+            return;
+        }
+        if self.results_are_playlist {
+            self.play_queue = self.search_results.clone();
+            self.play_queue_entry(selected, stream);
+        } else {
+            self.play_queue = vec![self.search_results[selected].clone()];
+            self.play_queue_entry(0, stream);
+        }
+    }
+
+    /// Advance to the next queue entry (the next cached one when offline). Returns false at the end of the queue.
+    pub fn play_next_in_queue(&mut self) -> bool {
+        let Some(current) = self.queue_index else { return false };
+        let next = (current + 1..self.play_queue.len()).find(|&i| !is_offline() || self.is_available_offline(&self.play_queue[i].url));
+        match next {
             Some(i) => {
-                if i >= self.search_results.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+                self.play_queue_entry(i, false);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Index in play_queue of the first entry after the current one
+    fn upcoming_start(&self) -> usize {
+        self.queue_index.map_or(self.play_queue.len(), |i| (i + 1).min(self.play_queue.len()))
+    }
+
+    /// The next few queue entries, for the UP NEXT panel
+    pub fn up_next(&self) -> &[YtDlpResult] {
+        let start = self.upcoming_start();
+        &self.play_queue[start..(start + UP_NEXT_LEN).min(self.play_queue.len())]
+    }
+
+    /// Keep the UP NEXT selection on a row that exists after the queue moved on or shrank
+    pub fn clamp_up_next(&mut self) {
+        let len = self.up_next().len();
+        match self.up_next_state.selected() {
+            _ if len == 0 => self.up_next_state.select(None),
+            Some(i) if i >= len => self.up_next_state.select(Some(len - 1)),
+            None if self.queue_focused => self.up_next_state.select(Some(0)),
+            _ => {},
+        }
+    }
+
+    pub fn toggle_queue_focus(&mut self) {
+        self.queue_focused = !self.queue_focused;
+        self.clamp_up_next();
+    }
+
+    /// Jump the queue to the selected UP NEXT entry, skipping the ones before it
+    pub fn play_selected_upcoming(&mut self) {
+        let Some(selected) = self.up_next_state.selected().filter(|&i| i < self.up_next().len()) else { return };
+        let index = self.upcoming_start() + selected;
+        self.up_next_state.select(Some(0));
+        self.play_queue_entry(index, false);
+    }
+
+    pub fn remove_selected_upcoming(&mut self) {
+        let Some(selected) = self.up_next_state.selected().filter(|&i| i < self.up_next().len()) else { return };
+        let removed = self.play_queue.remove(self.upcoming_start() + selected);
+        self.push_status(format!("Removed from queue: {}", removed.title));
+        self.clamp_up_next();
+        if let Some(current) = self.queue_index {
+            self.prefetch_upcoming(current);
+        }
+    }
+
+    /// Open the `i` popup on the selected search result, the selected UP NEXT entry or the playing track
+    pub fn open_track_info(&mut self) {
+        let selected = match self.input_mode {
+            InputMode::SearchResults => self.search_results_state.selected().and_then(|i| self.search_results.get(i)),
+            InputMode::Normal if self.current_tab == Tab::Radio && self.queue_focused => {
+                self.up_next_state.selected().and_then(|i| self.up_next().get(i))
+            },
+            _ => None,
+        };
+        let (title, url, uploader, duration) = match (selected, &self.current_track) {
+            (Some(result), _) => (result.title.clone(), result.url.clone(), result.uploader.clone(), result.duration),
+            (None, Some(track)) => (
+                track.title.clone(),
+                track.url.clone(),
+                track.artist.clone(),
+                self.player.total_duration.map(|d| d.as_secs_f64()),
+            ),
+            (None, None) => {
+                self.push_status("Nothing selected or playing");
+                return;
+            },
+        };
+
+        let path = if self.playing_url() == Some(url.as_str()) {
+            self.current_path.as_ref().map(PathBuf::from)
+        } else if is_local_file(&url) {
+            Some(PathBuf::from(&url))
+        } else {
+            cache::find_cached(&url, audio_quality())
+        };
+        let metadata = path.as_deref().and_then(cache::read_metadata).unwrap_or_default();
+        let tags = path.as_deref().and_then(read_tags);
+        let plays: Vec<u64> = self.history.plays.iter().filter(|p| p.url == url).map(|p| p.played_at).collect();
+        let queued = self.play_queue.iter().position(|entry| entry.url == url);
+
+        let mut details = TrackDetails::new(&url);
+        details.add("Title", Some(title));
+        details.add("Artist", tags.as_ref().and_then(|t| t.artist.clone()).or(metadata.artist));
+        details.add("Uploader", uploader.or(metadata.uploader));
+        details.add("Album", tags.and_then(|t| t.album).or(metadata.album));
+        details.add("Duration", duration.or(metadata.duration).map(|d| format_time(Duration::from_secs_f64(d.max(0.0)))));
+        details.add("URL", Some(url.clone()));
+        details.add("File", path.as_ref().map(|p| p.display().to_string()));
+        details.add("Size", path.and_then(|p| std::fs::metadata(p).ok()).map(|m| format_bytes(m.len())));
+        details.add("Plays", Some(plays.len().to_string()));
+        details.add("Last played", plays.iter().max().map(|at| format_ago(now_secs(), *at)));
+        details.add("Favorite", Some(if self.favorites.is_favorite(&url) { "yes" } else { "no" }));
+        details.add("In queue", queued.map(|i| format!("{} of {}", i + 1, self.play_queue.len())));
+        self.track_info = Some(details);
+    }
+
+    pub fn is_cached(&self, url: &str) -> bool {
+        cache::find_cached(url, audio_quality()).is_some()
+    }
+
+    fn is_available_offline(&self, url: &str) -> bool {
+        is_local_file(url) || self.is_cached(url)
+    }
+
+    pub fn toggle_offline(&mut self) {
+        let offline = !is_offline();
+        set_offline(offline);
+        self.settings.offline = offline;
+        if offline {
+            self.prefetch.clear();
+        }
+        match self.save_session() {
+            Ok(()) if offline => self.push_status("Offline: only cached tracks will play"),
+            Ok(()) => self.push_status("Back online"),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    fn play_queue_entry(&mut self, index: usize, stream: bool) {
+        let Some(YtDlpResult { title, url, uploader, .. }) = self.play_queue.get(index).cloned() else { return };
+        self.queue_index = Some(index);
+
+        // Nothing to stream from offline, the cached file is all there is
+        let stream = stream && !is_offline();
+        let verb = if stream { "Streaming" } else { "Downloading" };
+        self.push_progress(format!("{}: {}...", verb, title));
+        self.is_loading = true;
+        self.player.begin_loading();
+        self.requested_track = Some(TrackInfo::new(title, url.clone()).with_artist(uploader));
+        self.awaiting_prefetch = None;
+
+        let tx = self.event_tx.clone();
+        let cancel = self.new_download_token();
+        let prefetched = self.prefetched.get(&url).filter(|path| Path::new(path).is_file()).cloned();
+        if is_local_file(&url) {
+            // Files from the MAP tab play straight from disk
+            let _ = tx.send(AppEvent::AudioLoaded(url));
+        } else if stream {
+            AudioPlayer::stream_async(url, tx, cancel);
+        } else if let Some(path) = prefetched {
+            let _ = tx.send(AppEvent::AudioLoaded(path));
+        } else if self.prefetch.is_downloading(&url) {
+            // Two yt-dlp processes writing the same cache file would clobber each other
+            self.push_progress("Waiting for prefetched download...");
+            self.awaiting_prefetch = Some(url);
+        } else {
+            self.prefetch.dequeue(&url);
+            AudioPlayer::load_source_async(url, tx, cancel);
+        }
+
+        self.prefetch_upcoming(index);
+    }
+
+    fn prefetch_upcoming(&mut self, index: usize) {
+        self.prefetch.clear();
+        if is_offline() {
+            return;
+        }
+        for YtDlpResult { url, .. } in self.play_queue.iter().skip(index + 1).take(PREFETCH_AHEAD) {
+            if !is_local_file(url) && !self.prefetched.get(url).is_some_and(|path| Path::new(path).is_file()) {
+                self.prefetch.enqueue(url.clone());
             }
+        }
+    }
+
+    pub fn prefetch_finished(&mut self, url: String, result: Result<String, String>) {
+        let awaited = self.awaiting_prefetch.as_ref() == Some(&url);
+        match result {
+            Ok(path) => {
+                self.prefetched.insert(url, path.clone());
+                self.cache_changed();
+                if awaited {
+                    self.awaiting_prefetch = None;
+                    let _ = self.event_tx.send(AppEvent::AudioLoaded(path));
+                }
+            },
+            Err(e) if awaited => {
+                // Give it a proper try (with retries and status updates) in the foreground
+                self.awaiting_prefetch = None;
+                self.push_progress(format!("Prefetch failed ({}), downloading...", e));
+                AudioPlayer::load_source_async(url, self.event_tx.clone(), Arc::clone(&self.download_cancel));
+            },
+            Err(_) => {},
+        }
+    }
+
+    pub fn current_chapters(&self) -> &[Chapter] {
+        self.current_track.as_ref().map(|t| t.chapters.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn current_chapter_index(&self) -> Option<usize> {
+        let position = self.player.get_current_time().as_secs_f64();
+        self.current_chapters().iter().rposition(|c| c.start_time <= position)
+    }
+
+    pub fn next_chapter(&mut self) {
+        let next = match self.current_chapter_index() {
+            Some(i) => i + 1,
             None => 0,
         };
-        self.search_results_state.select(Some(i));
+        if let Some(start) = self.current_chapters().get(next).map(|c| c.start_time) {
+            self.seek_to_chapter(start);
+        }
     }
 
-    pub fn previous_search_result(&mut self) {
-        if self.search_results.is_empty() { return; }
-        let i = match self.search_results_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.search_results.len() - 1
-                } else {
-                    i - 1
+    // Like a CD player: restart the current chapter unless we're right at its start
+    pub fn previous_chapter(&mut self) {
+        let Some(current) = self.current_chapter_index() else { return };
+        let position = self.player.get_current_time().as_secs_f64();
+        let chapters = self.current_chapters();
+        let target = if position - chapters[current].start_time > 3.0 || current == 0 {
+            chapters[current].start_time
+        } else {
+            chapters[current - 1].start_time
+        };
+        self.seek_to_chapter(target);
+    }
+
+    fn seek_to_chapter(&mut self, start: f64) {
+        if let Err(e) = self.player.seek(Duration::from_secs_f64(start.max(0.0))) {
+            self.push_error(e);
+        }
+    }
+
+    /// Jump past a SponsorBlock segment if the playhead just entered one
+    pub fn check_skip_segments(&mut self) {
+        if !self.settings.sponsorblock || *self.player.state() != PlaybackState::Playing {
+            return;
+        }
+        let Some(track) = &self.current_track else { return };
+        let position = self.player.get_current_time().as_secs_f64();
+        let Some(segment) = track.skip_segments.iter().find(|s| position >= s.start && position < s.end - 0.5).cloned() else {
+            return;
+        };
+
+        match self.player.seek(Duration::from_secs_f64(segment.end)) {
+            Ok(()) => self.push_status(format!(
+                "Skipped {} ({})",
+                segment.category,
+                format_time(Duration::from_secs_f64(segment.end - segment.start))
+            )),
+            Err(e) => {
+                // Can't seek (live stream), don't try again every frame
+                if let Some(track) = self.current_track.as_mut() {
+                    track.skip_segments.clear();
                 }
+                self.push_error(e);
+            },
+        }
+    }
+
+    /// Count the current track as played once it's been on for a while, and add up how long it's
+    /// actually been heard. That's written out whenever playback stops or pauses.
+    pub fn check_history(&mut self) {
+        let now = Instant::now();
+        let was_playing = match self.listen_checked.take() {
+            Some(last) => {
+                // A suspended laptop isn't listening
+                self.listened += (now - last).min(Duration::from_secs(1));
+                true
+            },
+            None => false,
+        };
+        if self.history_recorded && let Some(track) = &self.current_track {
+            let secs = self.listened.as_secs();
+            self.history.add_listened(&track.url, secs);
+            self.listened -= Duration::from_secs(secs);
+        }
+        if *self.player.state() != PlaybackState::Playing {
+            if was_playing && self.history_recorded {
+                self.history.save_async();
             }
-            None => 0,
+            return;
+        }
+        self.listen_checked = Some(now);
+        let Some(track) = &self.current_track else { return };
+        if self.history_recorded {
+            return;
+        }
+        let position = self.player.get_current_time();
+        let threshold = match self.player.total_duration {
+            Some(total) => HISTORY_THRESHOLD.min(total / 2),
+            None => HISTORY_THRESHOLD,
         };
-        self.search_results_state.select(Some(i));
+        if position >= threshold {
+            self.history_recorded = true;
+            let artist = self.now_playing.as_ref().and_then(|n| n.artist.clone());
+            let secs = self.listened.as_secs();
+            self.history.record(track.url.clone(), track.title.clone(), artist, secs);
+            self.listened -= Duration::from_secs(secs);
+        }
+    }
+
+    /// Play the entry selected in the STAT tab's history lists, or queue up everything by the selected artist
+    pub fn play_history_selection(&mut self) {
+        let tracks = match self.history.focus {
+            HistoryFocus::Artists => self.history.selected_artist_tracks(),
+            _ => self.history.selected().into_iter().collect(),
+        };
+        if tracks.is_empty() {
+            return;
+        }
+        self.play_queue = tracks
+            .into_iter()
+            .map(|(title, url)| YtDlpResult { title, url, webpage_url: None, duration: None, uploader: None, cached: false })
+            .collect();
+        self.play_queue_entry(0, false);
+    }
+
+    pub fn toggle_sponsorblock(&mut self) {
+        self.settings.sponsorblock = !self.settings.sponsorblock;
+        match self.save_session() {
+            Ok(()) => self.push_status(format!("SponsorBlock {}", if self.settings.sponsorblock { "on" } else { "off" })),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn toggle_remaining_time(&mut self) {
+        self.show_remaining = !self.show_remaining;
+        if let Err(e) = self.save_session() {
+            self.push_error(e);
+        }
+    }
+
+    pub fn pause_for_resume(&mut self) {
+        if *self.player.state() == PlaybackState::Playing {
+            self.player.toggle_pause();
+        }
+        self.graph_config.pause = true;
+    }
+
+    // Settings changed straight from key handlers (volume, tab, scope)
+    fn knobs(&self) -> (f32, Tab, f64, u32, [bool; 4], u32) {
+        let graph = &self.graph_config;
+        let switches = [graph.scatter, graph.marker_type == Marker::Braille, graph.references, graph.show_ui];
+        (self.player.volume, self.current_tab, graph.scale, graph.samples, switches, graph.persistence)
+    }
+
+    /// Save once the volume/tab/scope settings stop changing for a moment. Called every frame.
+    pub fn autosave_settings(&mut self) {
+        let knobs = self.knobs();
+        if knobs != self.saved_knobs {
+            self.saved_knobs = knobs;
+            self.knobs_changed_at = Some(Instant::now());
+        }
+        if self.knobs_changed_at.is_some_and(|at| at.elapsed() >= SETTINGS_DEBOUNCE) {
+            self.knobs_changed_at = None;
+            if let Err(e) = self.save_session() {
+                self.push_error(e);
+            }
+        }
+    }
+
+    /// Persist volume, tab and the current track position so the next launch can pick up from here
+    pub fn save_session(&self) -> Result<(), String> {
+        let last_track = match (&self.current_track, &self.current_path) {
+            (Some(track), Some(path)) => Some(LastTrack {
+                title: track.title.clone(),
+                url: track.url.clone(),
+                path: path.clone(),
+                position_secs: self.player.get_current_time().as_secs_f64(),
+            }),
+            _ => None,
+        };
+
+        let mut settings = self.settings.clone();
+        settings.volume = self.player.volume;
+        settings.current_tab = self.current_tab.index();
+        settings.last_track = last_track;
+        settings.scope_scale = self.graph_config.scale;
+        settings.scope_samples = self.graph_config.samples;
+        settings.scope_scatter = self.graph_config.scatter;
+        settings.scope_persistence = self.graph_config.persistence;
+        settings.scope_braille = self.graph_config.marker_type == Marker::Braille;
+        settings.scope_references = self.graph_config.references;
+        settings.scope_labels = self.graph_config.show_ui;
+        settings.show_remaining = self.show_remaining;
+        settings.search_history = self.search_history.queries.clone();
+        settings.save()
+    }
+
+    pub fn play_selected_station(&mut self) {
+        let Some(station) = self.radio_state.selected().and_then(|i| self.radio_stations.get(i)).cloned() else { return };
+        if is_offline() {
+            self.push_status("Offline: radio needs a connection");
+            return;
+        }
+        self.push_progress(format!("Tuning in: {}...", station.name));
+        self.is_loading = true;
+        self.player.begin_loading();
+        self.queue_index = None;
+        self.awaiting_prefetch = None;
+        self.requested_track = Some(TrackInfo::new(station.name, station.url.clone()));
+        AudioPlayer::radio_async(station.url, self.event_tx.clone(), self.new_download_token());
+    }
+
+    /// The station whose stream is playing, if any
+    pub fn playing_station(&self) -> Option<usize> {
+        let url = self.playing_url()?;
+        self.radio_stations.iter().position(|s| s.url == url)
+    }
+
+    /// Start typing a new station (None) or changing the selected one
+    pub fn start_station_input(&mut self, edit: bool) {
+        let editing = if edit { self.radio_state.selected().filter(|&i| i < self.radio_stations.len()) } else { None };
+        if edit && editing.is_none() {
+            return;
+        }
+        self.search_input.set(editing.map(|i| self.radio_stations[i].name.clone()).unwrap_or_default());
+        self.station_draft = Some((editing, String::new()));
+        self.input_mode = InputMode::StationName;
+    }
+
+    /// Enter in the station inputs: take the name and ask for the URL, or save the station
+    pub fn submit_station_input(&mut self) {
+        let input = self.search_input.as_str().trim().to_string();
+        let Some((editing, name)) = self.station_draft.take() else { return };
+        if input.is_empty() {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        if matches!(self.input_mode, InputMode::StationName) {
+            self.search_input.set(editing.map(|i| self.radio_stations[i].url.clone()).unwrap_or_default());
+            self.station_draft = Some((editing, input));
+            self.input_mode = InputMode::StationUrl;
+            return;
+        }
+
+        self.input_mode = InputMode::Normal;
+        if !input.starts_with("http://") && !input.starts_with("https://") {
+            self.push_error("Station URL must start with http:// or https://");
+            return;
+        }
+        let color = editing.and_then(|i| self.radio_stations[i].color.clone());
+        let station = Station { name, url: input, color };
+        let index = match editing {
+            Some(i) => {
+                self.radio_stations[i] = station;
+                i
+            },
+            None => {
+                self.radio_stations.push(station);
+                self.radio_stations.len() - 1
+            },
+        };
+        self.radio_state.select(Some(index));
+        match stations::save(&self.radio_stations) {
+            Ok(()) => self.push_status(format!("Saved station: {}", self.radio_stations[index].name)),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// Give the selected station the next color, or take it away after the last one
+    pub fn cycle_station_color(&mut self) {
+        let Some(i) = self.radio_state.selected().filter(|&i| i < self.radio_stations.len()) else { return };
+        let station = &mut self.radio_stations[i];
+        station.color = next_named_color(station.color.as_deref().and_then(color_by_name)).and_then(color_name).map(str::to_string);
+        let message = match &station.color {
+            Some(color) => format!("{}: {}", station.name, color),
+            None => format!("{}: no color", station.name),
+        };
+        self.update_scope_palette();
+        match stations::save(&self.radio_stations) {
+            Ok(()) => self.push_status(message),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn cancel_station_input(&mut self) {
+        self.station_draft = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn delete_selected_station(&mut self) {
+        let Some(i) = self.radio_state.selected().filter(|&i| i < self.radio_stations.len()) else { return };
+        let station = self.radio_stations.remove(i);
+        let len = self.radio_stations.len();
+        self.radio_state.select(if len == 0 { None } else { Some(i.min(len - 1)) });
+        match stations::save(&self.radio_stations) {
+            Ok(()) => self.push_status(format!("Deleted station: {}", station.name)),
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.switch_tab(self.current_tab.next());
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.switch_tab(self.current_tab.previous());
+    }
+
+    /// Go to `tab`, dropping into the file browser on MAP and out of it anywhere else
+    pub fn switch_tab(&mut self, tab: Tab) {
+        self.current_tab = tab;
+        if tab == Tab::Map {
+            self.browse_files();
+        } else {
+            self.input_mode = self.input_mode.off_file_browser();
+        }
+    }
+
+    /// Focus the MAP tab's listing, reading the music directory the first time
+    pub fn browse_files(&mut self) {
+        self.input_mode = InputMode::FileBrowser;
+        if self.file_browser.entries.is_empty() && !self.file_browser.loading {
+            let dir = self.file_browser.cwd.clone();
+            self.file_browser.open(dir, self.event_tx.clone());
+        }
+    }
+
+    pub fn directory_listed(&mut self, dir: PathBuf, result: Result<Vec<DirEntry>, String>) {
+        match result {
+            Ok(entries) => self.file_browser.listed(dir, entries),
+            Err(e) if dir == self.file_browser.cwd => {
+                self.file_browser.listed(dir, Vec::new());
+                self.push_error(e);
+            },
+            Err(_) => {},
+        }
+    }
+
+    /// Enter on the MAP tab: open the directory, or play the file and the audio after it in the same directory
+    pub fn file_browser_enter(&mut self) {
+        let Some(entry) = self.file_browser.selected().cloned() else { return };
+        if entry.is_dir {
+            self.file_browser.open(entry.path, self.event_tx.clone());
+            return;
+        }
+        if !entry.is_audio() {
+            self.push_error(format!("Not an audio file: {}", entry.name));
+            return;
+        }
+        let tracks: Vec<&DirEntry> = self.file_browser.entries.iter().filter(|e| e.is_audio()).collect();
+        let index = tracks.iter().position(|e| e.path == entry.path).unwrap_or(0);
+        self.play_queue = tracks.into_iter().map(|e| local_result(&e.path)).collect();
+        self.play_queue_entry(index, false);
+    }
+
+    /// `a` on the MAP tab: queue the selected file, or every audio file under the selected directory
+    pub fn queue_selected_file(&mut self) {
+        let Some(path) = self.file_browser.selected().map(|e| e.path.clone()) else { return };
+        self.push_progress(format!("Collecting audio in {}...", path.display()));
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _ = tx.send(AppEvent::FilesCollected(files::collect_audio(&path)));
+        });
+    }
+
+    /// Add collected files to the end of the queue, starting playback if nothing is on
+    pub fn files_collected(&mut self, paths: Vec<PathBuf>) {
+        if paths.is_empty() {
+            self.push_error("No audio files there");
+            return;
+        }
+        let count = paths.len();
+        let idle = !self.is_loading && !matches!(self.player.state(), PlaybackState::Playing | PlaybackState::Paused);
+        let start = self.play_queue.len();
+        self.play_queue.extend(paths.iter().map(|path| local_result(path)));
+        self.push_status(format!("Queued {} track{}", count, if count == 1 { "" } else { "s" }));
+        if idle {
+            self.play_queue_entry(start, false);
+        }
+    }
+
+    /// Clicks seek on the progress gauge and pick list rows (twice to play), the wheel moves list selections
+    pub fn handle_mouse(&mut self, event: MouseEvent) {
+        let Some(hit) = self.hit_regions.hit(event.column, event.row) else { return };
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.click(hit),
+            MouseEventKind::ScrollDown => self.scroll(hit.region, true),
+            MouseEventKind::ScrollUp => self.scroll(hit.region, false),
+            _ => {}
+        }
+    }
+
+    fn click(&mut self, hit: Hit) {
+        if hit.region == Region::ProgressLabel {
+            self.toggle_remaining_time();
+            return;
+        }
+        if hit.region == Region::Progress {
+            // Nothing to seek in before a track is loaded, or in a live stream
+            let Some(total) = self.player.total_duration else { return };
+            if let Err(e) = self.player.seek(total.mul_f64(hit.fraction)) {
+                self.push_error(e);
+            }
+            return;
+        }
+        let Some(row) = hit.row else { return };
+        if !self.select_row(hit.region, row) {
+            return;
+        }
+        if self.hit_regions.is_double_click(hit.region, row, Instant::now()) {
+            self.activate(hit.region);
+        }
+    }
+
+    // Select `row` of the list, focusing it the way its keys would. False when there's no such row.
+    fn select_row(&mut self, region: Region, row: usize) -> bool {
+        match region {
+            Region::Progress | Region::ProgressLabel => false,
+            Region::Stations => {
+                let valid = row < self.radio_stations.len();
+                if valid {
+                    self.radio_state.select(Some(row));
+                }
+                valid
+            },
+            Region::UpNext => {
+                let valid = row < self.up_next().len();
+                if valid {
+                    self.queue_focused = true;
+                    self.up_next_state.select(Some(row));
+                }
+                valid
+            },
+            Region::Results => {
+                let valid = row < self.search_results.len();
+                if valid {
+                    self.search_results_state.select(Some(row));
+                    self.input_mode = InputMode::SearchResults;
+                }
+                valid
+            },
+            Region::Podcasts => self.podcasts.select(row, self.favorites.items.len()),
+            Region::LibraryHits => {
+                let valid = row < self.library_hits.len();
+                if valid {
+                    self.library_state.select(Some(row));
+                }
+                valid
+            },
+            Region::History(focus) => self.history.select(focus, row),
+            Region::Files => {
+                self.input_mode = InputMode::FileBrowser;
+                self.file_browser.select(row)
+            },
+        }
+    }
+
+    // What Enter does on the selected row
+    fn activate(&mut self, region: Region) {
+        match region {
+            Region::Progress | Region::ProgressLabel => {},
+            Region::Stations => self.play_selected_station(),
+            Region::UpNext => self.play_selected_upcoming(),
+            Region::Results => {
+                self.play_selected_result(false);
+                self.input_mode = InputMode::Normal;
+            },
+            Region::Podcasts => self.podcast_enter(),
+            Region::LibraryHits => {
+                self.play_library_hit();
+                self.input_mode = InputMode::Normal;
+            },
+            Region::History(_) => self.play_history_selection(),
+            Region::Files => self.file_browser_enter(),
+        }
+    }
+
+    fn scroll(&mut self, region: Region, forward: bool) {
+        if let Region::History(focus) = region {
+            self.history.focus = focus;
+        }
+        self.move_selection(region, if forward { ListMove::Next } else { ListMove::Previous });
+    }
+
+    /// The list Up/Down, PageUp/PageDown and Home/End move, given the tab and mode
+    pub fn focused_list(&self) -> Option<Region> {
+        match self.input_mode {
+            InputMode::SearchResults => Some(Region::Results),
+            InputMode::LibrarySearch => Some(Region::LibraryHits),
+            InputMode::FileBrowser | InputMode::FileFilter => Some(Region::Files),
+            InputMode::Normal => Some(match self.current_tab {
+                Tab::Stat => Region::History(self.history.focus),
+                Tab::Inv => Region::Podcasts,
+                Tab::Radio if self.queue_focused => Region::UpNext,
+                // Up and Down pick stations from any other tab
+                _ => Region::Stations,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Move the selection in `region`'s list. A page is as many rows as it had on screen last frame.
+    pub fn move_selection(&mut self, region: Region, movement: ListMove) {
+        let page = self.hit_regions.rows(region);
+        match region {
+            Region::Progress | Region::ProgressLabel => {},
+            Region::Stations => {
+                if let Some(i) = movement.apply(self.radio_state.selected(), self.radio_stations.len(), page) {
+                    self.radio_state.select(Some(i));
+                }
+            },
+            Region::UpNext => {
+                if let Some(i) = movement.apply(self.up_next_state.selected(), self.up_next().len(), page) {
+                    self.up_next_state.select(Some(i));
+                }
+            },
+            Region::Results => {
+                if let Some(i) = movement.apply(self.search_results_state.selected(), self.search_results.len(), page) {
+                    self.search_results_state.select(Some(i));
+                }
+            },
+            Region::Podcasts => self.podcasts.move_selection(movement, page, self.favorites.items.len()),
+            Region::LibraryHits => {
+                if let Some(i) = movement.apply(self.library_state.selected(), self.library_hits.len(), page) {
+                    self.library_state.select(Some(i));
+                }
+            },
+            Region::History(_) => self.history.move_selection(movement, page),
+            Region::Files => self.file_browser.move_selection(movement, page),
+        }
+    }
+}
+
+// The scope's grid lines stay in the background, unless there are no colors to do it with
+fn axis_color(theme: &Theme) -> Color {
+    if theme.mono { Color::Reset } else { Color::DarkGray }
+}
+
+// Queue entries for files on disk carry the path where a URL would be
+fn is_local_file(url: &str) -> bool {
+    !url.contains("://") && Path::new(url).is_file()
+}
+
+// Note which of `results` have been downloaded, so drawing them never has to look
+fn mark_cached(results: &mut [YtDlpResult]) {
+    for result in results {
+        result.cached = cache::find_cached(&result.url, audio_quality()).is_some();
+    }
+}
+
+fn local_result(path: &Path) -> YtDlpResult {
+    let title = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().to_string();
+    YtDlpResult { title, url: path.to_string_lossy().to_string(), webpage_url: None, duration: None, uploader: None, cached: false }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_searches_leave_open_prompts_alone() {
+        assert_eq!(InputMode::Normal.after_search(true), InputMode::SearchResults);
+        assert_eq!(InputMode::SearchResults.after_search(false), InputMode::Normal);
+        assert_eq!(InputMode::Confirm.after_search(true), InputMode::Confirm);
+        assert_eq!(InputMode::FeedUrl.after_search(false), InputMode::FeedUrl);
+    }
+
+    #[test]
+    fn leaving_map_ends_browsing_and_filtering() {
+        assert_eq!(InputMode::FileBrowser.off_file_browser(), InputMode::Normal);
+        assert_eq!(InputMode::FileFilter.off_file_browser(), InputMode::Normal);
+        assert_eq!(InputMode::Confirm.off_file_browser(), InputMode::Confirm);
     }
 }
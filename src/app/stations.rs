@@ -0,0 +1,62 @@
+use std::fs;
+use serde_derive::{Deserialize, Serialize};
+use super::paths::data_path;
+
+// In the data directory; written with the defaults on first run
+pub const STATIONS_FILE: &str = "sound_cows_stations.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Station {
+    pub name: String,
+    pub url: String, // Plain HTTP audio stream (Icecast/Shoutcast)
+    // Name of one of ui::theme::NAMED_COLORS; files from before stations had colors load as None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+fn station(name: &str, url: &str) -> Station {
+    Station { name: name.to_string(), url: url.to_string(), color: None }
+}
+
+// A few long-running stations that serve plain MP3 streams
+fn default_stations() -> Vec<Station> {
+    vec![
+        station("SomaFM Groove Salad", "https://ice1.somafm.com/groovesalad-128-mp3"),
+        station("SomaFM Secret Agent", "https://ice1.somafm.com/secretagent-128-mp3"),
+        station("SomaFM Left Coast 70s", "https://ice1.somafm.com/seventies-128-mp3"),
+        station("SomaFM DEF CON Radio", "https://ice1.somafm.com/defcon-128-mp3"),
+        station("Radio Paradise Main Mix", "https://stream.radioparadise.com/mp3-128"),
+        station("KEXP 90.3 Seattle", "https://kexp-mp3-128.streamguys1.com/kexp128.mp3"),
+    ]
+}
+
+/// The saved station list, or the defaults (saved right away) if there's none yet
+pub fn load() -> Vec<Station> {
+    match fs::read_to_string(data_path(STATIONS_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|_| default_stations()),
+        Err(_) => {
+            let stations = default_stations();
+            let _ = save(&stations);
+            stations
+        },
+    }
+}
+
+pub fn save(stations: &[Station]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(stations).map_err(|e| format!("Stations error: {}", e))?;
+    fs::write(data_path(STATIONS_FILE), json).map_err(|e| format!("Failed to save stations: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_stations_saved_without_colors() {
+        let stations: Vec<Station> = serde_json::from_str(r#"[{"name": "KEXP", "url": "https://kexp.example/128.mp3"}]"#).unwrap();
+        assert_eq!(stations[0].color, None);
+        let colored = Station { color: Some("cyan".to_string()), ..stations[0].clone() };
+        let saved = serde_json::to_string(&colored).unwrap();
+        assert_eq!(serde_json::from_str::<Station>(&saved).unwrap().color.as_deref(), Some("cyan"));
+    }
+}
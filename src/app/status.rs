@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use super::history::now_secs;
+
+const INFO_TTL: Duration = Duration::from_secs(4);
+const ERROR_TTL: Duration = Duration::from_secs(10);
+// With more messages waiting, the one showing gives way after this long
+const QUEUED_TTL: Duration = Duration::from_millis(1500);
+pub const LOG_LEN: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusLevel {
+    Info,
+    Progress, // Replaced by the next progress message instead of queueing behind it
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub text: String,
+    pub level: StatusLevel,
+    pub shown_at: Option<Instant>, // None while it waits its turn
+    pub ttl: Duration,
+}
+
+// A message as it went into the log
+#[derive(Debug, Clone)]
+pub struct LoggedMessage {
+    pub text: String,
+    pub level: StatusLevel,
+    pub logged_at: u64, // Unix seconds
+}
+
+/// The footer's status line: messages show one at a time until they expire,
+/// and the last LOG_LEN of them are kept for the help overlay
+#[derive(Default)]
+pub struct Status {
+    queue: VecDeque<StatusMessage>,
+    pub log: VecDeque<LoggedMessage>,
+}
+
+impl Status {
+    pub fn push(&mut self, text: String, level: StatusLevel, now: Instant) {
+        // A download's progress is one line in the log, not hundreds
+        match self.log.back_mut() {
+            Some(last) if level == StatusLevel::Progress && last.level == StatusLevel::Progress => last.text = text.clone(),
+            _ => self.log.push_back(LoggedMessage { text: text.clone(), level, logged_at: now_secs() }),
+        }
+        if self.log.len() > LOG_LEN {
+            self.log.pop_front();
+        }
+
+        if level == StatusLevel::Progress
+            && let Some(last) = self.queue.back_mut()
+            && last.level == StatusLevel::Progress {
+            // Keep it up for as long as updates keep coming
+            last.text = text;
+            last.shown_at = last.shown_at.map(|_| now);
+            return;
+        }
+        let ttl = if level == StatusLevel::Error { ERROR_TTL } else { INFO_TTL };
+        self.queue.push_back(StatusMessage { text, level, shown_at: None, ttl });
+        self.expire(now);
+    }
+
+    /// Drop whatever has been up long enough and start the clock on the next one
+    pub fn expire(&mut self, now: Instant) {
+        loop {
+            let waiting = self.queue.len() > 1;
+            let Some(front) = self.queue.front_mut() else { break };
+            let Some(shown_at) = front.shown_at else {
+                front.shown_at = Some(now);
+                break;
+            };
+            let shown = now.duration_since(shown_at);
+            if shown >= front.ttl || (waiting && shown >= QUEUED_TTL) {
+                self.queue.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn current(&self) -> Option<&StatusMessage> {
+        self.queue.front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(status: &Status) -> Option<&str> {
+        status.current().map(|m| m.text.as_str())
+    }
+
+    #[test]
+    fn messages_expire() {
+        let start = Instant::now();
+        let mut status = Status::default();
+        status.push("Found 5 results".to_string(), StatusLevel::Info, start);
+        assert_eq!(text(&status), Some("Found 5 results"));
+        status.expire(start + INFO_TTL - Duration::from_millis(1));
+        assert_eq!(text(&status), Some("Found 5 results"));
+        status.expire(start + INFO_TTL);
+        assert_eq!(text(&status), None);
+    }
+
+    #[test]
+    fn errors_stay_up_longer() {
+        let start = Instant::now();
+        let mut status = Status::default();
+        status.push("Search Error".to_string(), StatusLevel::Error, start);
+        status.expire(start + INFO_TTL);
+        assert_eq!(text(&status), Some("Search Error"));
+        status.expire(start + ERROR_TTL);
+        assert_eq!(text(&status), None);
+    }
+
+    #[test]
+    fn rapid_messages_queue() {
+        let start = Instant::now();
+        let mut status = Status::default();
+        status.push("Added to favorites".to_string(), StatusLevel::Info, start);
+        status.push("Playing: Song".to_string(), StatusLevel::Info, start);
+        assert_eq!(text(&status), Some("Added to favorites"));
+        status.expire(start + QUEUED_TTL);
+        assert_eq!(text(&status), Some("Playing: Song"));
+        // The last one gets its full time
+        status.expire(start + QUEUED_TTL * 2);
+        assert_eq!(text(&status), Some("Playing: Song"));
+        assert_eq!(status.log.len(), 2);
+    }
+
+    #[test]
+    fn progress_replaces_progress() {
+        let start = Instant::now();
+        let mut status = Status::default();
+        status.push("Downloading: 1 MB".to_string(), StatusLevel::Progress, start);
+        status.push("Downloading: 2 MB".to_string(), StatusLevel::Progress, start + Duration::from_secs(3));
+        assert_eq!(text(&status), Some("Downloading: 2 MB"));
+        // Timed from the latest update
+        status.expire(start + Duration::from_secs(5));
+        assert_eq!(text(&status), Some("Downloading: 2 MB"));
+        assert_eq!(status.log.len(), 1);
+    }
+
+    #[test]
+    fn log_keeps_the_last_fifty() {
+        let mut status = Status::default();
+        for i in 0..60 {
+            status.push(i.to_string(), StatusLevel::Info, Instant::now());
+        }
+        assert_eq!(status.log.len(), LOG_LEN);
+        assert_eq!(status.log.front().unwrap().text, "10");
+    }
+}
@@ -0,0 +1,79 @@
+/// The screens along the header, in the order they're shown
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Tab {
+    Stat,  // Settings and listening history
+    Inv,   // Podcasts and favorites
+    Data,  // Search
+    Map,   // Local files
+    #[default]
+    Radio, // Stations, the scope and now playing
+}
+
+impl Tab {
+    pub const ALL: [Tab; 5] = [Tab::Stat, Tab::Inv, Tab::Data, Tab::Map, Tab::Radio];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::Stat => "STAT",
+            Tab::Inv => "INV",
+            Tab::Data => "DATA",
+            Tab::Map => "MAP",
+            Tab::Radio => "RADIO",
+        }
+    }
+
+    /// Position in ALL, which is also how the settings file remembers it. The variants are
+    /// declared in the same order, so this is just the discriminant.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The tab at `index`, RADIO for anything out of range (an old or hand-edited settings file)
+    pub fn from_index(index: usize) -> Tab {
+        Tab::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// The tab a number key jumps to, '1' being the first
+    pub fn from_digit(c: char) -> Option<Tab> {
+        let n = c.to_digit(10)? as usize;
+        n.checked_sub(1).and_then(|i| Tab::ALL.get(i)).copied()
+    }
+
+    pub fn next(&self) -> Tab {
+        Tab::ALL[(self.index() + 1) % Tab::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Tab {
+        Tab::ALL[(self.index() + Tab::ALL.len() - 1) % Tab::ALL.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_round_trip() {
+        for (i, tab) in Tab::ALL.iter().enumerate() {
+            assert_eq!(tab.index(), i);
+            assert_eq!(Tab::from_index(i), *tab);
+        }
+        assert_eq!(Tab::from_index(9), Tab::Radio);
+    }
+
+    #[test]
+    fn next_and_previous_wrap() {
+        assert_eq!(Tab::Radio.next(), Tab::Stat);
+        assert_eq!(Tab::Stat.previous(), Tab::Radio);
+        assert_eq!(Tab::Inv.next(), Tab::Data);
+    }
+
+    #[test]
+    fn number_keys_count_from_one() {
+        assert_eq!(Tab::from_digit('1'), Some(Tab::Stat));
+        assert_eq!(Tab::from_digit('5'), Some(Tab::Radio));
+        assert_eq!(Tab::from_digit('0'), None);
+        assert_eq!(Tab::from_digit('6'), None);
+        assert_eq!(Tab::from_digit('x'), None);
+    }
+}
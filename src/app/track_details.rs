@@ -0,0 +1,34 @@
+/// What the `i` popup shows about one track, gathered when it's opened
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackDetails {
+    pub url: String,                       // What `y` copies
+    pub rows: Vec<(&'static str, String)>, // (label, value) in the order shown
+}
+
+impl TrackDetails {
+    pub fn new(url: &str) -> Self {
+        TrackDetails { url: url.to_string(), rows: Vec::new() }
+    }
+
+    /// Add a row, unless there's nothing to say for it
+    pub fn add(&mut self, label: &'static str, value: Option<impl Into<String>>) {
+        if let Some(value) = value.map(Into::into).filter(|v| !v.trim().is_empty()) {
+            self.rows.push((label, value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_values_leave_no_row() {
+        let mut details = TrackDetails::new("https://example.com/a");
+        details.add("Title", Some("A"));
+        details.add("Album", None::<String>);
+        details.add("Artist", Some(" "));
+        details.add("URL", Some(details.url.clone()));
+        assert_eq!(details.rows, [("Title", "A".to_string()), ("URL", "https://example.com/a".to_string())]);
+    }
+}
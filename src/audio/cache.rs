@@ -0,0 +1,289 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use super::http::AUDIO_EXTENSIONS;
+use super::stream::{AudioQuality, TrackMetadata};
+
+// Downloaded tracks live here, one file per source URL
+pub const CACHE_DIR: &str = "cache";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheUsage {
+    pub bytes: u64,
+    pub files: usize,
+}
+
+// Leftovers younger than this might belong to another running instance
+const ORPHAN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+// What yt-dlp leaves behind when a download or conversion dies halfway
+const TEMP_EXTENSIONS: &[&str] = &["part", "ytdl", "webm", "m4a", "opus", "temp"];
+
+// Downloads being written right now, so exit can clean up after any that are still going
+static IN_FLIGHT: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Registers a download target until dropped
+pub struct InFlightDownload(PathBuf);
+
+impl Drop for InFlightDownload {
+    fn drop(&mut self) {
+        // Just this registration: the same path can be downloading twice at once
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+        if let Some(index) = in_flight.iter().position(|p| *p == self.0) {
+            in_flight.swap_remove(index);
+        }
+    }
+}
+
+pub fn track_download(path: &Path) -> InFlightDownload {
+    IN_FLIGHT.lock().unwrap().push(path.to_path_buf());
+    InFlightDownload(path.to_path_buf())
+}
+
+pub fn downloads_in_flight() -> usize {
+    IN_FLIGHT.lock().unwrap().len()
+}
+
+/// Delete whatever the unfinished downloads have written so far, except `keep` (the file the sink has open)
+pub fn remove_in_flight(keep: &[&Path]) {
+    let paths = IN_FLIGHT.lock().unwrap().clone();
+    for path in paths.iter().filter(|p| !keep.contains(&p.as_path())) {
+        remove_failed_download(path);
+    }
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+// FNV-1a, so cache file names stay stable across builds
+fn hash_url(url: &str) -> u64 {
+    url.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Where the download for `url` at `quality` is (or will be) stored. yt-dlp's downloads are
+/// always .mp3; a direct download swaps in its own extension, see `existing`.
+pub fn cache_path_for(url: &str, quality: AudioQuality) -> PathBuf {
+    // Quality is part of the key so switching it never serves an old low-bitrate file
+    let key = format!("{}#{}", url, quality.name());
+    Path::new(CACHE_DIR).join(format!("{:016x}.mp3", hash_url(&key)))
+}
+
+/// A cached download of `url` at any quality, preferring `quality`
+pub fn find_cached(url: &str, quality: AudioQuality) -> Option<PathBuf> {
+    std::iter::once(quality)
+        .chain(AudioQuality::ALL)
+        .map(|q| cache_path_for(url, q))
+        .find_map(|path| existing(&path))
+}
+
+/// The cached file at `path`, under whichever audio extension it was saved with
+pub fn existing(path: &Path) -> Option<PathBuf> {
+    AUDIO_EXTENSIONS.iter().map(|ext| path.with_extension(ext)).find(|path| path.is_file())
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| AUDIO_EXTENSIONS.iter().any(|a| ext == *a))
+}
+
+// Whether `path` is named like `cache_path_for` names files, `<16 hex digits>.<ext>`
+fn is_cache_file(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.len() == 16 && stem.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// yt-dlp metadata (chapters etc.) stored next to a cached file
+pub fn metadata_path_for(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
+
+pub fn read_metadata(path: &Path) -> Option<TrackMetadata> {
+    let contents = fs::read_to_string(metadata_path_for(path)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn write_metadata(path: &Path, metadata: &TrackMetadata) {
+    if let Ok(json) = serde_json::to_string(metadata) {
+        let _ = fs::write(metadata_path_for(path), json);
+    }
+}
+
+// Delete a cached file together with its metadata sidecar
+fn remove_entry(path: &Path) -> bool {
+    let removed = fs::remove_file(path).is_ok();
+    let _ = fs::remove_file(metadata_path_for(path));
+    removed
+}
+
+/// Mark a cached file as recently used so eviction keeps it around
+pub fn touch(path: &Path) {
+    if let Ok(file) = File::options().append(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+fn entries() -> Vec<CacheEntry> {
+    let Ok(dir) = fs::read_dir(CACHE_DIR) else { return Vec::new() };
+    dir.flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            // Sidecars are tiny and go away with their audio file
+            if !metadata.is_file() || entry.path().extension().is_some_and(|ext| ext == "json") {
+                return None;
+            }
+            Some(CacheEntry {
+                path: entry.path(),
+                size: metadata.len(),
+                last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            })
+        })
+        .collect()
+}
+
+pub fn usage() -> CacheUsage {
+    entries().iter().fold(CacheUsage::default(), |usage, entry| CacheUsage {
+        bytes: usage.bytes + entry.size,
+        files: usage.files + 1,
+    })
+}
+
+/// Delete least recently used files until the cache fits in `max_bytes`. Returns how many were removed.
+pub fn enforce_limit(max_bytes: u64, keep: &[&Path]) -> usize {
+    let mut entries = entries();
+    let mut total: u64 = entries.iter().map(|e| e.size).sum();
+    entries.sort_by_key(|e| e.last_used);
+
+    let mut removed = 0;
+    for entry in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if keep.iter().any(|k| *k == entry.path) {
+            continue;
+        }
+        if remove_entry(&entry.path) {
+            total = total.saturating_sub(entry.size);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Remove everything except the files in `keep`. Returns how many were removed.
+pub fn clear(keep: &[&Path]) -> usize {
+    entries()
+        .into_iter()
+        .filter(|e| !keep.iter().any(|k| *k == e.path))
+        .filter(|e| remove_entry(&e.path))
+        .count()
+}
+
+/// Clean up whatever a failed download left behind (the output file, `.part` files, pre-conversion files)
+pub fn remove_failed_download(path: &Path) {
+    let Some(stem) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else { return };
+    for entry in entries() {
+        let is_leftover = entry
+            .path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(&format!("{}.", stem)));
+        if is_leftover {
+            let _ = fs::remove_file(&entry.path);
+        }
+    }
+}
+
+// A direct download can end in .m4a like a yt-dlp intermediate does. It has a sidecar
+// and, unlike an intermediate, no converted .mp3 next to it.
+fn is_direct_download(path: &Path) -> bool {
+    metadata_path_for(path).exists() && !path.with_extension("mp3").exists()
+}
+
+/// Remove metadata sidecars whose audio file is gone, and empty audio files. Returns (files, bytes) removed.
+pub fn remove_orphan_sidecars() -> (usize, u64) {
+    remove_orphan_sidecars_in(Path::new(CACHE_DIR))
+}
+
+fn remove_orphan_sidecars_in(dir: &Path) -> (usize, u64) {
+    let Ok(entries) = fs::read_dir(dir) else { return (0, 0) };
+    let mut removed = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Other files share the directory (the search cache), leave them be
+        if !is_cache_file(&path) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else { continue };
+        let orphan = match path.extension() {
+            Some(ext) if ext == "json" => existing(&path).is_none(),
+            _ => is_audio(&path) && metadata.len() == 0,
+        };
+        if orphan && fs::remove_file(&path).is_ok() {
+            removed.0 += 1;
+            removed.1 += metadata.len();
+        }
+    }
+    removed
+}
+
+/// Remove `.part` files and conversion intermediates that earlier runs left behind and nobody touched for a day
+pub fn remove_orphans() {
+    let now = SystemTime::now();
+    for entry in entries() {
+        let is_temp = entry
+            .path
+            .extension()
+            .is_some_and(|ext| TEMP_EXTENSIONS.iter().any(|t| ext == *t))
+            && !is_direct_download(&entry.path);
+        let is_old = now.duration_since(entry.last_used).is_ok_and(|age| age > ORPHAN_AGE);
+        if is_temp && is_old {
+            let _ = fs::remove_file(&entry.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn a_second_download_of_a_path_stays_registered() {
+        let path = Path::new("cache/in-flight-twice.mp3");
+        let registered = || IN_FLIGHT.lock().unwrap().iter().filter(|p| *p == path).count();
+        let first = track_download(path);
+        let second = track_download(path);
+        drop(first);
+        assert_eq!(registered(), 1);
+        drop(second);
+        assert_eq!(registered(), 0);
+    }
+
+    #[test]
+    fn removes_sidecars_without_audio_and_empty_files() {
+        let dir = TempDir::new();
+        let files = [
+            ("00000000000000aa.mp3", &b"audio"[..]),
+            ("00000000000000aa.json", b"{}"),
+            ("00000000000000bb.json", b"{\"id\":null}"),
+            ("00000000000000cc.mp3", b""),
+            ("00000000000000dd.ogg", b"audio"),
+            ("00000000000000dd.json", b"{}"),
+            ("searches.json", b"[]"),
+            ("notes.mp3", b""),
+        ];
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+
+        let removed = remove_orphan_sidecars_in(dir.path());
+        let left: Vec<bool> = files.iter().map(|(name, _)| dir.join(name).exists()).collect();
+
+        assert_eq!(removed, (2, 11));
+        assert_eq!(left, [true, true, false, false, true, true, true, true]);
+    }
+}
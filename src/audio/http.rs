@@ -0,0 +1,457 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// A read that gets nothing for this long means the connection is dead, time to reconnect
+const STALL_TIMEOUT: Duration = Duration::from_secs(10);
+// Tries at picking a dropped stream back up where it left off, a little apart
+const MAX_RECONNECTS: usize = 3;
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+// The reader gives up waiting when the filler hasn't said anything for this long, reconnects included
+const GIVE_UP_TIMEOUT: Duration = Duration::from_secs(60);
+const CHUNK_SIZE: usize = 64 * 1024;
+// Bytes of a stream held in memory. Played audio stays around for seeking back until new data needs the room.
+const BUFFER_SIZE: usize = 8 * 1024 * 1024;
+
+// Files we can decode ourselves, no need for yt-dlp. Direct downloads are cached under these.
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "ogg", "oga", "m4a", "aac", "flac", "wav"];
+// Sites yt-dlp has to handle; never worth a HEAD request
+const EXTRACTOR_HOSTS: &[&str] = &["youtube.com", "youtu.be", "soundcloud.com", "bandcamp.com"];
+// Anything bigger is almost certainly not a single track
+const MAX_DIRECT_DOWNLOAD: u64 = 1024 * 1024 * 1024;
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Picks the stream back up at a byte offset after the connection dropped
+type Reconnect = Box<dyn FnMut(u64) -> io::Result<Box<dyn Read + Send>> + Send>;
+
+// The downloaded part of the stream in a fixed ring: stream offset `o` lives at `ring[o % ring.len()]`
+struct BufferState {
+    ring: Box<[u8]>,
+    start: u64, // Stream offset of the oldest byte still kept
+    len: usize,
+    position: u64, // Where the reader is; bytes from here on are never dropped to make room
+
+    finished: bool,
+    error: Option<String>,
+    closed: bool, // The reader is gone
+}
+
+impl BufferState {
+    fn new(capacity: usize) -> Self {
+        BufferState { ring: vec![0; capacity].into_boxed_slice(), start: 0, len: 0, position: 0, finished: false, error: None, closed: false }
+    }
+
+    fn end(&self) -> u64 {
+        self.start + self.len as u64
+    }
+
+    // Append as much of `data` as fits, dropping played bytes for room. Returns how much went in.
+    fn push(&mut self, data: &[u8]) -> usize {
+        let capacity = self.ring.len();
+        let played = self.position.saturating_sub(self.start).min(self.len as u64) as usize;
+        let n = data.len().min(capacity - self.len + played);
+        let dropped = (self.len + n).saturating_sub(capacity);
+        self.start += dropped as u64;
+        self.len -= dropped;
+        for (i, byte) in data[..n].iter().enumerate() {
+            self.ring[((self.end() + i as u64) % capacity as u64) as usize] = *byte;
+        }
+        self.len += n;
+        n
+    }
+
+    // Copy from the reader's position into `buf`, returns how many bytes
+    fn copy_out(&self, buf: &mut [u8]) -> usize {
+        let capacity = self.ring.len() as u64;
+        let n = buf.len().min((self.end() - self.position) as usize);
+        for (i, byte) in buf[..n].iter_mut().enumerate() {
+            *byte = self.ring[((self.position + i as u64) % capacity) as usize];
+        }
+        n
+    }
+}
+
+struct Shared {
+    state: Mutex<BufferState>,
+    data_ready: Condvar,
+    space_free: Condvar,
+}
+
+/// `Read + Seek` over an HTTP body that is still downloading in the background.
+/// Reads past the downloaded part block until more data arrives; a dropped connection is reconnected.
+pub struct StreamReader {
+    shared: Arc<Shared>,
+    content_length: Option<u64>,
+}
+
+/// Start downloading `url` in the background and return a reader that can be decoded right away
+pub fn open_stream(url: &str) -> Result<StreamReader, String> {
+    let agent = ureq::AgentBuilder::new().timeout_connect(STALL_TIMEOUT).timeout_read(STALL_TIMEOUT).build();
+    let response = agent
+        .get(url)
+        .call()
+        .map_err(|e| format!("Stream request failed: {}", e))?;
+    let content_length = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok());
+
+    // Files resume where they stopped; live radio just starts over at "now"
+    let url = url.to_string();
+    let reconnect = move |offset: u64| -> io::Result<Box<dyn Read + Send>> {
+        let request = agent.get(&url);
+        let response = match content_length {
+            Some(_) => request.set("Range", &format!("bytes={}-", offset)).call(),
+            None => request.call(),
+        }
+        .map_err(io::Error::other)?;
+        if content_length.is_some() && response.status() != 206 {
+            return Err(io::Error::other("Server can't resume the stream"));
+        }
+        Ok(Box::new(response.into_reader()))
+    };
+    Ok(StreamReader::spawn(Box::new(response.into_reader()), content_length, BUFFER_SIZE, Box::new(reconnect)))
+}
+
+impl StreamReader {
+    fn spawn(body: Box<dyn Read + Send>, content_length: Option<u64>, capacity: usize, reconnect: Reconnect) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(BufferState::new(capacity)),
+            data_ready: Condvar::new(),
+            space_free: Condvar::new(),
+        });
+        let filler = Arc::clone(&shared);
+        thread::spawn(move || fill(&filler, body, content_length, reconnect));
+        StreamReader { shared, content_length }
+    }
+}
+
+// Runs on the download thread until the body ends, fails for good, or the reader is dropped
+fn fill(shared: &Shared, mut body: Box<dyn Read + Send>, content_length: Option<u64>, mut reconnect: Reconnect) {
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    loop {
+        let result = body.read(&mut chunk);
+        let mut state = shared.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        let complete = content_length.is_none_or(|len| state.end() >= len);
+        match result {
+            Ok(0) if complete => state.finished = true,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Ok(n) if n > 0 => {
+                let mut data = &chunk[..n];
+                while !data.is_empty() {
+                    let taken = state.push(data);
+                    data = &data[taken..];
+                    shared.data_ready.notify_all();
+                    if !data.is_empty() {
+                        state = shared.space_free.wait(state).unwrap();
+                        if state.closed {
+                            return;
+                        }
+                    }
+                }
+                continue;
+            },
+            // Stalled, dropped, or cut short
+            Ok(_) | Err(_) => {
+                let offset = state.end();
+                drop(state);
+                let resumed = (0..MAX_RECONNECTS).find_map(|attempt| {
+                    if attempt > 0 {
+                        thread::sleep(RECONNECT_DELAY);
+                    }
+                    reconnect(offset).ok()
+                });
+                state = shared.state.lock().unwrap();
+                match resumed {
+                    Some(next) => {
+                        body = next;
+                        continue;
+                    },
+                    None => {
+                        let reason = result.err().map_or("connection closed early".to_string(), |e| e.to_string());
+                        state.error = Some(reason);
+                    },
+                }
+            },
+        }
+        drop(state);
+        shared.data_ready.notify_all();
+        return;
+    }
+}
+
+impl Drop for StreamReader {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.space_free.notify_all();
+    }
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.position < state.start {
+            return Err(io::Error::other("Stream data already discarded"));
+        }
+        loop {
+            let available = state.end();
+            if state.position < available {
+                let n = state.copy_out(buf);
+                state.position += n as u64;
+                self.shared.space_free.notify_all();
+                return Ok(n);
+            }
+            if let Some(e) = &state.error {
+                return Err(io::Error::other(format!("Stream error: {}", e)));
+            }
+            if state.finished {
+                return Ok(0);
+            }
+
+            let (next, timeout) = self.shared.data_ready.wait_timeout(state, GIVE_UP_TIMEOUT).unwrap();
+            state = next;
+            if timeout.timed_out() && state.end() == available && !state.finished && state.error.is_none() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "Stream stalled"));
+            }
+        }
+    }
+}
+
+impl Seek for StreamReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => state.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => match self.content_length {
+                Some(len) => len.checked_add_signed(delta),
+                None => return Err(io::Error::new(io::ErrorKind::Unsupported, "Stream length unknown")),
+            },
+        };
+        let target = target.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek"))?;
+        state.position = target;
+        self.shared.space_free.notify_all();
+        Ok(target)
+    }
+}
+
+// The known audio extension `url`'s path ends in
+fn audio_extension(url: &str) -> Option<&'static str> {
+    // Ignore the query string and fragment, podcast enclosures often carry tracking parameters
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let (_, ext) = path.rsplit_once('.')?;
+    AUDIO_EXTENSIONS.iter().copied().find(|a| ext.eq_ignore_ascii_case(a))
+}
+
+fn has_audio_extension(url: &str) -> bool {
+    audio_extension(url).is_some()
+}
+
+fn mime_type(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase()
+}
+
+fn is_audio_content_type(content_type: &str) -> bool {
+    let mime = mime_type(content_type);
+    mime.starts_with("audio/") || mime == "application/ogg"
+}
+
+// The file extension for an audio content type, when it's one we know
+fn content_type_extension(content_type: &str) -> Option<&'static str> {
+    Some(match mime_type(content_type).as_str() {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/ogg" | "audio/vorbis" | "audio/opus" | "application/ogg" => "ogg",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+        "audio/aac" | "audio/aacp" => "aac",
+        "audio/flac" | "audio/x-flac" => "flac",
+        "audio/wav" | "audio/wave" | "audio/x-wav" | "audio/vnd.wave" => "wav",
+        _ => return None,
+    })
+}
+
+/// Whether `url` is a plain audio file we can download without yt-dlp:
+/// a known audio extension, or (for unknown sites) a HEAD response with an audio content type
+pub fn is_direct_audio_url(url: &str) -> bool {
+    if has_audio_extension(url) {
+        return true;
+    }
+    if EXTRACTOR_HOSTS.iter().any(|host| url.contains(host)) {
+        return false;
+    }
+    ureq::head(url)
+        .timeout(HEAD_TIMEOUT)
+        .call()
+        .ok()
+        .and_then(|response| response.header("Content-Type").map(is_audio_content_type))
+        .unwrap_or(false)
+}
+
+/// Download an audio file straight into `path`, with its extension swapped for what the server
+/// says the file is (redirects are followed by ureq). Returns where it ended up.
+/// `on_progress(downloaded, total)` is called as chunks arrive.
+pub fn download_direct(
+    url: &str,
+    path: &Path,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<PathBuf, String> {
+    let response = ureq::get(url).call().map_err(|e| format!("Download failed: {}", e))?;
+
+    let content_type = response.header("Content-Type").unwrap_or("").to_string();
+    // Servers often send octet-stream for audio files, trust the extension then
+    let looks_like_audio = is_audio_content_type(&content_type)
+        || (content_type.starts_with("application/octet-stream") && has_audio_extension(url));
+    if !looks_like_audio {
+        return Err(format!("Not an audio file ({})", if content_type.is_empty() { "no content type" } else { &content_type }));
+    }
+    let total = response.header("Content-Length").and_then(|len| len.parse::<u64>().ok());
+    match total {
+        Some(0) => return Err("Server returned an empty file".to_string()),
+        Some(len) if len > MAX_DIRECT_DOWNLOAD => return Err(format!("File too large ({} MB)", len / 1024 / 1024)),
+        _ => {},
+    }
+    let path = path.with_extension(content_type_extension(&content_type).or_else(|| audio_extension(url)).unwrap_or("mp3"));
+
+    // Write next to the final name and rename at the end so a half-written file is never "cached"
+    let partial = path.with_extension("part");
+    let result = (|| {
+        let mut file = File::create(&partial).map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+        let mut body = response.into_reader();
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut downloaded = 0u64;
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Err("Download cancelled".to_string());
+            }
+            let n = match body.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(format!("Download failed: {}", e)),
+            };
+            file.write_all(&chunk[..n]).map_err(|e| format!("Failed to write download: {}", e))?;
+            downloaded += n as u64;
+            if downloaded > MAX_DIRECT_DOWNLOAD {
+                return Err("File too large".to_string());
+            }
+            on_progress(downloaded, total);
+        }
+
+        if downloaded == 0 || total.is_some_and(|len| len != downloaded) {
+            return Err(format!("Download incomplete ({} of {} bytes)", downloaded, total.unwrap_or(0)));
+        }
+        fs::rename(&partial, &path).map_err(|e| format!("Failed to save download: {}", e))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result.map(|()| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hands out `data` in small reads, then fails like a dead connection
+    struct Dropping {
+        data: io::Cursor<Vec<u8>>,
+    }
+
+    impl Read for Dropping {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(7);
+            let n = self.data.read(&mut buf[..len])?;
+            if n == 0 {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "read timed out"));
+            }
+            Ok(n)
+        }
+    }
+
+    fn bytes(range: std::ops::Range<u32>) -> Vec<u8> {
+        range.map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn audio_urls_are_told_by_their_extension() {
+        assert!(has_audio_extension("https://example.com/episode.mp3"));
+        assert!(has_audio_extension("https://cdn.example.com/a/b/Show.M4A?utm_source=feed#t=10"));
+        assert!(has_audio_extension("http://example.com/take.flac"));
+        assert!(!has_audio_extension("https://example.com/watch?v=abc.mp3"));
+        assert!(!has_audio_extension("https://example.com/page.html"));
+        assert!(!has_audio_extension("https://example.com/mp3"));
+        assert_eq!(audio_extension("https://example.com/x.OGG?dl=1"), Some("ogg"));
+    }
+
+    #[test]
+    fn audio_content_types_name_an_extension() {
+        assert!(is_audio_content_type("audio/mpeg"));
+        assert!(is_audio_content_type("Audio/Ogg; codecs=opus"));
+        assert!(is_audio_content_type("application/ogg"));
+        assert!(!is_audio_content_type("text/html; charset=utf-8"));
+        assert!(!is_audio_content_type("application/octet-stream"));
+        assert!(!is_audio_content_type(""));
+
+        assert_eq!(content_type_extension("audio/mpeg"), Some("mp3"));
+        assert_eq!(content_type_extension("audio/x-m4a"), Some("m4a"));
+        assert_eq!(content_type_extension("audio/ogg; codecs=vorbis"), Some("ogg"));
+        assert_eq!(content_type_extension("audio/x-flac"), Some("flac"));
+        assert_eq!(content_type_extension("audio/x-unknown"), None);
+    }
+
+    #[test]
+    fn keeps_only_a_ring_of_recent_bytes() {
+        let body = io::Cursor::new(bytes(0..1000));
+        let mut reader = StreamReader::spawn(Box::new(body), None, 64, Box::new(|_| Err(io::Error::other("no"))));
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, bytes(0..1000));
+
+        // The last 64 bytes are still there to seek back into, anything older is gone
+        reader.seek(SeekFrom::Start(1000 - 64)).unwrap();
+        let mut tail = [0u8; 64];
+        reader.read_exact(&mut tail).unwrap();
+        assert_eq!(tail.to_vec(), bytes(936..1000));
+        reader.seek(SeekFrom::Start(1000 - 65)).unwrap();
+        assert!(reader.read(&mut tail).is_err());
+    }
+
+    #[test]
+    fn picks_a_dropped_stream_back_up_where_it_stopped() {
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&offsets);
+        let reconnect = move |offset: u64| -> io::Result<Box<dyn Read + Send>> {
+            seen.lock().unwrap().push(offset);
+            Ok(Box::new(io::Cursor::new(bytes(offset as u32..100))))
+        };
+        let first = Dropping { data: io::Cursor::new(bytes(0..40)) };
+        let mut reader = StreamReader::spawn(Box::new(first), Some(100), 256, Box::new(reconnect));
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, bytes(0..100));
+        assert_eq!(*offsets.lock().unwrap(), [40]);
+    }
+
+    #[test]
+    fn gives_up_after_a_few_reconnects() {
+        let attempts = Arc::new(Mutex::new(0));
+        let counted = Arc::clone(&attempts);
+        let reconnect = move |_| -> io::Result<Box<dyn Read + Send>> {
+            *counted.lock().unwrap() += 1;
+            Err(io::Error::other("refused"))
+        };
+        let first = Dropping { data: io::Cursor::new(bytes(0..10)) };
+        let mut reader = StreamReader::spawn(Box::new(first), None, 256, Box::new(reconnect));
+        let mut all = Vec::new();
+        assert!(reader.read_to_end(&mut all).is_err());
+        assert_eq!(all, bytes(0..10));
+        assert_eq!(*attempts.lock().unwrap(), MAX_RECONNECTS);
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rodio::cpal::{self, FromSample, SizedSample};
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+use super::wav::WavWriter;
+use crate::scope::Matrix;
+
+// How much input the scope can look back over, in seconds
+const RING_SECS: usize = 2;
+
+/// The newest input samples, per channel, and what's waiting to go into a recording.
+/// Filled by the input stream's thread, read by the scope like a playing file.
+pub struct LiveInput {
+    samples: Vec<VecDeque<f64>>,
+    capacity: usize,
+    pending: Option<Vec<f64>>, // Interleaved, Some while recording
+}
+
+impl LiveInput {
+    pub fn new(channels: usize, capacity: usize) -> Self {
+        LiveInput { samples: vec![VecDeque::with_capacity(capacity); channels.max(1)], capacity, pending: None }
+    }
+
+    /// Take in interleaved samples; a partial frame at the end is dropped
+    pub fn push(&mut self, interleaved: &[f64]) {
+        let channels = self.samples.len();
+        for frame in interleaved.chunks_exact(channels) {
+            for (ring, sample) in self.samples.iter_mut().zip(frame) {
+                if ring.len() == self.capacity {
+                    ring.pop_front();
+                }
+                ring.push_back(*sample);
+            }
+            if let Some(pending) = &mut self.pending {
+                pending.extend_from_slice(frame);
+            }
+        }
+    }
+
+    /// The newest `len` samples of each channel into `out`, silence in front while there aren't that many yet
+    pub fn fill_window(&self, len: usize, out: &mut Matrix<f64>) {
+        out.resize_with(self.samples.len(), Vec::new);
+        for (ring, out) in self.samples.iter().zip(out.iter_mut()) {
+            let have = ring.len().min(len);
+            out.clear();
+            out.resize(len - have, 0.0);
+            out.extend(ring.iter().skip(ring.len() - have));
+        }
+    }
+}
+
+struct Recording {
+    writer: WavWriter,
+    path: PathBuf,
+    started: Instant,
+}
+
+/// The default input device, open and feeding a LiveInput. Dropping it closes the device.
+pub struct Mic {
+    _stream: cpal::Stream,
+    pub input: Arc<Mutex<LiveInput>>,
+    pub sample_rate: u32,
+    pub channels: usize,
+    recording: Option<Recording>,
+}
+
+impl Mic {
+    /// Open the default input device. `on_error` hears about the device failing later on, from its thread.
+    pub fn open(on_error: impl Fn(String) + Send + 'static) -> Result<Mic, String> {
+        let device = cpal::default_host().default_input_device().ok_or("No microphone found")?;
+        let config = device.default_input_config().map_err(|e| format!("Microphone unavailable: {}", e))?;
+        let sample_rate = config.sample_rate().0;
+        let channels = config.channels() as usize;
+        let input = Arc::new(Mutex::new(LiveInput::new(channels, sample_rate as usize * RING_SECS)));
+
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build::<f32>(&device, &config.into(), input.clone(), on_error),
+            cpal::SampleFormat::I16 => build::<i16>(&device, &config.into(), input.clone(), on_error),
+            cpal::SampleFormat::U16 => build::<u16>(&device, &config.into(), input.clone(), on_error),
+            other => return Err(format!("Microphone sample format {} isn't supported", other)),
+        }?;
+        stream.play().map_err(|e| format!("Microphone didn't start: {}", e))?;
+        Ok(Mic { _stream: stream, input, sample_rate, channels, recording: None })
+    }
+
+    /// Start writing what comes in to a new WAV file at `path`
+    pub fn start_recording(&mut self, path: &Path) -> Result<(), String> {
+        let writer = WavWriter::create(path, self.channels as u16, self.sample_rate)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        if let Ok(mut input) = self.input.lock() {
+            input.pending = Some(Vec::new());
+        }
+        self.recording = Some(Recording { writer, path: path.to_path_buf(), started: Instant::now() });
+        Ok(())
+    }
+
+    /// Write out what's come in since the last call. Called every frame while recording.
+    pub fn flush(&mut self) -> Result<(), String> {
+        let Some(recording) = &mut self.recording else { return Ok(()) };
+        let pending = match self.input.lock() {
+            Ok(mut input) => input.pending.as_mut().map(std::mem::take).unwrap_or_default(),
+            Err(_) => return Ok(()),
+        };
+        recording.writer.write(&pending).map_err(|e| format!("Recording failed: {}", e))
+    }
+
+    /// Finish the file; the path of what was recorded, if anything was
+    pub fn stop_recording(&mut self) -> Result<Option<PathBuf>, String> {
+        self.flush()?;
+        if let Ok(mut input) = self.input.lock() {
+            input.pending = None;
+        }
+        let Some(recording) = self.recording.take() else { return Ok(None) };
+        recording.writer.finish().map_err(|e| format!("Recording failed: {}", e))?;
+        Ok(Some(recording.path))
+    }
+
+    pub fn recording_for(&self) -> Option<Duration> {
+        self.recording.as_ref().map(|r| r.started.elapsed())
+    }
+}
+
+fn build<T>(device: &cpal::Device, config: &cpal::StreamConfig, input: Arc<Mutex<LiveInput>>, on_error: impl Fn(String) + Send + 'static) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f64: FromSample<T>,
+{
+    let mut converted = Vec::new();
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                converted.clear();
+                converted.extend(data.iter().map(|s| s.to_sample::<f64>()));
+                if let Ok(mut input) = input.lock() {
+                    input.push(&converted);
+                }
+            },
+            move |e| on_error(format!("Microphone: {}", e)),
+            None,
+        )
+        .map_err(|e| format!("Microphone unavailable: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_holds_the_newest_samples() {
+        let mut input = LiveInput::new(2, 3);
+        let mut window = Vec::new();
+        input.push(&[0.1, -0.1, 0.2, -0.2]);
+        input.fill_window(3, &mut window);
+        assert_eq!(window, [vec![0.0, 0.1, 0.2], vec![0.0, -0.1, -0.2]]);
+        input.pending = Some(Vec::new());
+        input.push(&[0.3, -0.3, 0.4, -0.4, 0.5]);
+        input.fill_window(2, &mut window);
+        assert_eq!(window, [vec![0.3, 0.4], vec![-0.3, -0.4]]);
+        input.fill_window(4, &mut window);
+        assert_eq!(window[0], [0.0, 0.2, 0.3, 0.4]);
+        assert_eq!(input.pending, Some(vec![0.3, -0.3, 0.4, -0.4]));
+    }
+}
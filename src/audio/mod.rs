@@ -1,2 +1,8 @@
+pub mod cache;
+pub mod http;
+pub mod mic;
 pub mod player;
+pub mod sponsorblock;
 pub mod stream;
+pub mod tags;
+pub mod wav;
@@ -1,13 +1,43 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::BufReader;
 use std::time::{Duration, Instant};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::thread;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
-use rodio::{Decoder, OutputStream, Sink, Source};
+use rodio::{Decoder, OutputStream, Sample, Sink, Source};
+use rodio::cpal::FromSample;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
 use crate::scope::Matrix;
 use crate::app::state::AppEvent;
-use super::stream::{download_audio, search_audio};
+use super::cache;
+use super::mic::LiveInput;
+use super::sponsorblock;
+use super::tags::{self, read_tags, TrackTags};
+use super::http::{self, open_stream, StreamReader};
+use super::stream::{audio_quality, download_with_retry, extract_stream_url, fetch_metadata, is_offline, list_playlist, search_audio, Provider, TrackMetadata};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaybackState {
+    Stopped,
+    Loading,
+    Playing,
+    Paused,
+    Errored(String),
+}
+
+impl PlaybackState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlaybackState::Stopped => "STOPPED",
+            PlaybackState::Loading => "LOADING",
+            PlaybackState::Playing => "PLAYING",
+            PlaybackState::Paused => "PAUSED",
+            PlaybackState::Errored(_) => "ERROR",
+        }
+    }
+}
 
 pub struct AudioPlayer {
     // We keep these alive
@@ -20,18 +50,106 @@ pub struct AudioPlayer {
     pub sample_rate: u32,
     pub channels: usize,
     pub is_streaming_mode: bool, // New flag for optimization
+    pub is_remote_stream: bool, // Playing straight from HTTP, can't seek
+    pub live_input: Option<Arc<Mutex<LiveInput>>>, // The mic, when the scope shows it instead of playback
 
     // Playback Timing State
     pub start_time: Option<Instant>,
     pub elapsed_when_paused: Duration,
     pub total_duration: Option<Duration>,
-
-    // Errors
-    pub error_message: Option<String>,
+    pub duration_estimated: bool, // total_duration is guessed from the file size
 
     // State
-    pub is_paused: bool,
+    state: PlaybackState,
     pub volume: f32,
+    pub now_playing: Option<TrackTags>, // From the file's tags, None for streams
+    pub device_name: Option<String>, // The output device rodio opened, for the STAT tab
+}
+
+// Reuse the cached download for `url` if there is one, otherwise download it into the cache.
+// Blocks on the network (the HEAD probe, the download), so it's only ever run on a worker thread.
+fn fetch_cached(url: &str, cancel: &AtomicBool, tx: Option<&Sender<AppEvent>>) -> Result<PathBuf, String> {
+    let quality = audio_quality();
+    if is_offline() {
+        // Any quality will do when there's no way to download a better one
+        return cache::find_cached(url, quality).ok_or_else(|| "Offline and not in the cache".to_string());
+    }
+    let path = cache::cache_path_for(url, quality);
+    if let Some(path) = cache::existing(&path) {
+        cache::touch(&path);
+        if cache::read_metadata(&path).is_none() {
+            refresh_metadata(url, &path, tx);
+        }
+        return Ok(path);
+    }
+
+    fs::create_dir_all(cache::CACHE_DIR).map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    let _in_flight = cache::track_download(&path);
+
+    let on_progress = |downloaded, total| {
+        if let Some(tx) = tx {
+            send_unless_cancelled(tx, cancel, AppEvent::DownloadProgress(downloaded, total));
+        }
+    };
+    if http::is_direct_audio_url(url) {
+        let path = http::download_direct(url, &path, cancel, on_progress)?;
+        // Nothing for yt-dlp to add; an empty sidecar stops the cache-hit path from asking it
+        cache::write_metadata(&path, &TrackMetadata::default());
+        return Ok(path);
+    }
+
+    let on_retry = |attempt, max| {
+        if let Some(tx) = tx {
+            send_unless_cancelled(tx, cancel, AppEvent::DownloadRetry(attempt, max));
+        }
+    };
+    match download_with_retry(url, &path, quality, cancel, on_retry, on_progress) {
+        Ok(_) => {
+            store_metadata(url, &path);
+            Ok(path)
+        },
+        Err(e) => {
+            cache::remove_failed_download(&path);
+            Err(e.to_string())
+        }
+    }
+}
+
+// Chapters, tags etc. are nice to have; a failed lookup or tag write never blocks playback
+fn store_metadata(url: &str, path: &Path) {
+    if let Some(metadata) = lookup_metadata(url) {
+        cache::write_metadata(path, &metadata);
+        let _ = tags::write_tags(path, &metadata);
+    }
+}
+
+// A cached file whose sidecar went missing plays at once and gets its chapters and skips once they're
+// looked up. Its tags are left alone, since the file may already be playing.
+fn refresh_metadata(url: &str, path: &Path, tx: Option<&Sender<AppEvent>>) {
+    let (url, path, tx) = (url.to_string(), path.to_path_buf(), tx.cloned());
+    thread::spawn(move || {
+        if let Some(metadata) = lookup_metadata(&url) {
+            cache::write_metadata(&path, &metadata);
+            if let Some(tx) = tx {
+                let _ = tx.send(AppEvent::MetadataReady(path.to_string_lossy().to_string()));
+            }
+        }
+    });
+}
+
+fn lookup_metadata(url: &str) -> Option<TrackMetadata> {
+    let mut metadata = fetch_metadata(url).ok()?;
+    if let Some(id) = metadata.youtube_id() {
+        metadata.skip_segments = sponsorblock::fetch_segments(id).unwrap_or_default();
+    }
+    Some(metadata)
+}
+
+// Whatever a download or stream finds after the user moved on would replace what they picked since
+fn send_unless_cancelled(tx: &Sender<AppEvent>, cancel: &AtomicBool, event: AppEvent) {
+    if !cancel.load(Ordering::Relaxed) {
+        let _ = tx.send(event);
+    }
 }
 
 impl AudioPlayer {
@@ -44,12 +162,16 @@ impl AudioPlayer {
             sample_rate: 44100,
             channels: 2,
             is_streaming_mode: false,
+            is_remote_stream: false,
+            live_input: None,
             start_time: None,
             elapsed_when_paused: Duration::from_secs(0),
             total_duration: None,
-            error_message: None,
-            is_paused: false,
+            duration_estimated: false,
+            state: PlaybackState::Stopped,
             volume: 1.0,
+            now_playing: None,
+            device_name: None,
         };
 
         player.init();
@@ -64,70 +186,124 @@ impl AudioPlayer {
                         self._stream = Some(stream);
                         self._stream_handle = Some(stream_handle);
                         self.sink = Some(s);
+                        // try_default opens the host's default device, so that's the one to name
+                        self.device_name = rodio::cpal::default_host().default_output_device().and_then(|d| d.name().ok());
                     },
-                    Err(e) => self.error_message = Some(format!("Sink error: {}", e)),
+                    Err(e) => self.state = PlaybackState::Errored(format!("Sink error: {}", e)),
                 }
             },
-            Err(e) => self.error_message = Some(format!("Audio init error: {}", e)),
+            Err(e) => self.state = PlaybackState::Errored(format!("Audio init error: {}", e)),
         }
     }
 
-    // Synchronous load (legacy / local)
-    #[allow(dead_code)]
-    pub fn load_source(&mut self, path_or_url: &str) {
-        if self.sink.is_none() {
-            return;
-        }
+    /// Download (or find in the cache) `url` and report it as AudioLoaded or AudioError.
+    /// Once `cancel` is set (another track was picked), the outcome is dropped.
+    pub fn load_source_async(url: String, tx: Sender<AppEvent>, cancel: Arc<AtomicBool>) {
+        thread::spawn(move || {
+            let event = match fetch_cached(&url, &cancel, Some(&tx)) {
+                Ok(path) => AppEvent::AudioLoaded(path.to_string_lossy().to_string()),
+                Err(e) => AppEvent::AudioError(e),
+            };
+            send_unless_cancelled(&tx, &cancel, event);
+        });
+    }
 
-        self.error_message = None;
+    // Download `url` into the cache ahead of time (run by the prefetch workers)
+    pub fn prefetch(url: &str, tx: &Sender<AppEvent>, cancel: &AtomicBool) {
+        let event = match fetch_cached(url, cancel, None) {
+            Ok(path) => AppEvent::PrefetchComplete(url.to_string(), path.to_string_lossy().to_string()),
+            Err(e) => AppEvent::PrefetchFailed(url.to_string(), e),
+        };
+        let _ = tx.send(event);
+    }
 
-        let path = if path_or_url.starts_with("http") {
-            let temp_path = Path::new("stream_cache.mp3");
-            match download_audio(path_or_url, temp_path) {
-                Ok(_) => temp_path,
+    // Expand a playlist URL into individual entries, reporting progress as they stream in
+    pub fn list_playlist_async(url: String, tx: Sender<AppEvent>) {
+        thread::spawn(move || {
+            let progress_tx = tx.clone();
+            match list_playlist(&url, |count| {
+                let _ = progress_tx.send(AppEvent::PlaylistProgress(count));
+            }) {
+                Ok(entries) => {
+                    let _ = tx.send(AppEvent::PlaylistListed(Provider::from_url(&url), entries));
+                },
                 Err(e) => {
-                    self.error_message = Some(e);
-                    return;
+                    let _ = tx.send(AppEvent::PlaylistError(e.to_string()));
                 }
             }
-        } else {
-            Path::new(path_or_url)
-        };
-
-        self.play_file(path);
+        });
     }
 
-    // Async load wrapper
-    pub fn load_source_async(url: String, tx: Sender<AppEvent>) {
+    // Play straight from the extracted media URL, falling back to a normal download if that fails
+    pub fn stream_async(url: String, tx: Sender<AppEvent>, cancel: Arc<AtomicBool>) {
         thread::spawn(move || {
-            let temp_path = Path::new("stream_cache.mp3");
-            match download_audio(&url, temp_path) {
-                Ok(_) => {
-                    let _ = tx.send(AppEvent::AudioLoaded(temp_path.to_string_lossy().to_string()));
-                },
+            let stream = extract_stream_url(&url).map_err(|e| e.to_string()).and_then(|(media_url, duration)| {
+                let reader = open_stream(&media_url)?;
+                let decoder = Decoder::new(reader).map_err(|e| format!("Can't decode stream: {}", e))?;
+                Ok((decoder, duration.map(Duration::from_secs_f64)))
+            });
+
+            match stream {
+                Ok((decoder, duration)) => send_unless_cancelled(&tx, &cancel, AppEvent::StreamReady(Box::new(decoder), duration)),
                 Err(e) => {
-                    let _ = tx.send(AppEvent::AudioError(e));
+                    send_unless_cancelled(&tx, &cancel, AppEvent::StreamFallback(e));
+                    let event = match fetch_cached(&url, &cancel, Some(&tx)) {
+                        Ok(path) => AppEvent::AudioLoaded(path.to_string_lossy().to_string()),
+                        Err(e) => AppEvent::AudioError(e),
+                    };
+                    send_unless_cancelled(&tx, &cancel, event);
                 }
             }
         });
     }
 
-    pub fn search_async(query: String, tx: Sender<AppEvent>) {
+    /// Play an internet radio stream. It never ends, so there's nothing to download instead.
+    /// Once `cancel` is set (another station or track was picked), the outcome is dropped.
+    pub fn radio_async(url: String, tx: Sender<AppEvent>, cancel: Arc<AtomicBool>) {
         thread::spawn(move || {
-            match search_audio(&query) {
+            let stream = open_stream(&url)
+                .and_then(|reader| Decoder::new(reader).map_err(|e| format!("Can't decode stream: {}", e)));
+            send_unless_cancelled(&tx, &cancel, match stream {
+                Ok(decoder) => AppEvent::StreamReady(Box::new(decoder), None),
+                Err(e) => AppEvent::AudioError(e),
+            });
+        });
+    }
+
+    pub fn search_async(query: String, provider: Provider, tx: Sender<AppEvent>) {
+        thread::spawn(move || {
+            match search_audio(&query, provider) {
                 Ok(results) => {
-                    let _ = tx.send(AppEvent::SearchFinished(results));
+                    let _ = tx.send(AppEvent::SearchFinished(provider, query, results));
                 },
                 Err(e) => {
-                    let _ = tx.send(AppEvent::SearchError(e));
+                    let _ = tx.send(AppEvent::SearchError(provider, query, e.to_string()));
                 }
             }
         });
     }
 
+    /// Start playing a source that is still being downloaded. No visualization data and no seeking.
+    pub fn play_stream(&mut self, source: Decoder<StreamReader>, duration: Option<Duration>) {
+        if let Some(sink) = &self.sink {
+            sink.stop();
+            self.sample_rate = source.sample_rate();
+            self.channels = source.channels() as usize;
+            self.is_streaming_mode = true;
+            self.is_remote_stream = true;
+            self.now_playing = None;
+            self.audio_data = vec![Vec::new(); self.channels];
+            self.total_duration = duration;
+            self.duration_estimated = false;
+            self.start_sink(source);
+        }
+    }
+
     pub fn play_file(&mut self, path: &Path) {
         if let Some(sink) = &self.sink {
             sink.stop();
+            self.is_remote_stream = false;
+            self.now_playing = read_tags(path);
 
             match File::open(path) {
                 Ok(file) => {
@@ -168,20 +344,12 @@ impl AudioPlayer {
                                  // Duration = size / 16000.
                                  let approx_seconds = file_size / 16000;
                                  self.total_duration = Some(Duration::from_secs(approx_seconds));
+                                 self.duration_estimated = true;
 
                                  // We need to consume the `source` we created? No, we can use it.
                                  // But we need a clone or reopen for Sink?
                                  // Rodio Sink takes ownership of Source.
-                                 if let Some(handle) = &self._stream_handle {
-                                     if let Ok(new_sink) = Sink::try_new(handle) {
-                                         new_sink.set_volume(self.volume);
-                                         new_sink.append(source); // Use the source directly! No collecting!
-                                         self.sink = Some(new_sink);
-                                         self.start_time = Some(Instant::now());
-                                         self.elapsed_when_paused = Duration::from_secs(0);
-                                         self.is_paused = false;
-                                     }
-                                 }
+                                 self.start_sink(source); // Use the source directly! No collecting!
                              } else {
                                  // --- FULL LOAD MODE (Visualizer Active) ---
                                  self.is_streaming_mode = false;
@@ -189,21 +357,12 @@ impl AudioPlayer {
                                  let samples: Vec<f32> = source.convert_samples().collect(); // Expensive step!
                                  let total_samples = samples.len() / self.channels;
                                  self.total_duration = Some(Duration::from_secs_f64(total_samples as f64 / self.sample_rate as f64));
+                                 self.duration_estimated = false;
 
                                  // We consumed source, so reopen for sink
-                                 if let Ok(file_play) = File::open(path) {
-                                     if let Ok(source_play) = Decoder::new(BufReader::new(file_play)) {
-                                         if let Some(handle) = &self._stream_handle {
-                                             if let Ok(new_sink) = Sink::try_new(handle) {
-                                                 new_sink.set_volume(self.volume);
-                                                 new_sink.append(source_play);
-                                                 self.sink = Some(new_sink);
-                                                 self.start_time = Some(Instant::now());
-                                                 self.elapsed_when_paused = Duration::from_secs(0);
-                                                 self.is_paused = false;
-                                             }
-                                         }
-                                     }
+                                 if let Ok(file_play) = File::open(path)
+                                     && let Ok(source_play) = Decoder::new(BufReader::new(file_play)) {
+                                     self.start_sink(source_play);
                                  }
 
                                  self.audio_data = vec![Vec::new(); self.channels];
@@ -212,74 +371,178 @@ impl AudioPlayer {
                                  }
                              }
                         },
-                        Err(e) => self.error_message = Some(format!("Format error: {}", e)),
+                        Err(e) => self.state = PlaybackState::Errored(format!("Format error: {}", e)),
                     }
                 },
                 Err(_) => {
-                     self.error_message = Some(format!("File not found: {}", path.display()));
+                     self.state = PlaybackState::Errored(format!("File not found: {}", path.display()));
                 }
             }
         }
     }
 
+    /// Play a file starting at `offset` instead of the beginning (used to resume sessions)
+    pub fn play_file_from(&mut self, path: &Path, offset: Duration) -> Result<(), String> {
+        self.play_file(path);
+        if offset > Duration::ZERO {
+            self.seek(offset)?;
+        }
+        Ok(())
+    }
+
+    /// Jump to an absolute position in the current track, keeping the playback clock in sync
+    pub fn seek(&mut self, position: Duration) -> Result<(), String> {
+        if !matches!(self.state, PlaybackState::Playing | PlaybackState::Paused) {
+            return Ok(());
+        }
+        if self.is_remote_stream {
+            return Err("Seeking isn't supported while streaming".to_string());
+        }
+
+        let position = match self.total_duration {
+            Some(total) => position.min(total),
+            None => position,
+        };
+
+        if let Some(sink) = &self.sink {
+            sink.try_seek(position).map_err(|e| format!("Seek error: {}", e))?;
+            self.elapsed_when_paused = position;
+            self.start_time = if self.state == PlaybackState::Paused { None } else { Some(Instant::now()) };
+        }
+        Ok(())
+    }
+
+    pub fn state(&self) -> &PlaybackState {
+        &self.state
+    }
+
+    /// Mark that a track is being fetched. Whatever is already playing keeps playing until it arrives.
+    pub fn begin_loading(&mut self) {
+        if !matches!(self.state, PlaybackState::Playing | PlaybackState::Paused) {
+            self.state = PlaybackState::Loading;
+        }
+    }
+
+    pub fn load_failed(&mut self, error: String) {
+        if self.state == PlaybackState::Loading {
+            self.state = PlaybackState::Errored(error);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(sink) = &self.sink {
+            sink.stop();
+        }
+        self.state = PlaybackState::Stopped;
+        self.start_time = None;
+        self.elapsed_when_paused = Duration::from_secs(0);
+    }
+
+    /// Notice when the sink has run dry so the clock stops at the end of the track.
+    /// Returns true on the frame the track finished.
+    pub fn update_state(&mut self) -> bool {
+        if self.state != PlaybackState::Playing {
+            return false;
+        }
+        if let Some(sink) = &self.sink
+            && sink.empty() {
+            self.elapsed_when_paused = self.get_current_time();
+            if let Some(total) = self.total_duration {
+                self.elapsed_when_paused = self.elapsed_when_paused.min(total);
+            }
+            self.start_time = None;
+            self.state = PlaybackState::Stopped;
+            return true;
+        }
+        false
+    }
+
+    // Swap in a fresh sink playing `source` and reset the playback clock
+    fn start_sink<S>(&mut self, source: S)
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        if let Some(handle) = &self._stream_handle
+            && let Ok(new_sink) = Sink::try_new(handle) {
+            new_sink.set_volume(self.volume);
+            new_sink.append(source);
+            self.sink = Some(new_sink);
+            self.start_time = Some(Instant::now());
+            self.elapsed_when_paused = Duration::from_secs(0);
+            self.state = PlaybackState::Playing;
+        }
+    }
+
     /// Helper to get the current playback position
     pub fn get_current_time(&self) -> Duration {
-        if self.is_paused {
-            self.elapsed_when_paused
-        } else {
-            if let Some(start) = self.start_time {
-                self.elapsed_when_paused + start.elapsed()
-            } else {
-                Duration::from_secs(0)
-            }
+        match (&self.state, self.start_time) {
+            (PlaybackState::Playing, Some(start)) => self.elapsed_when_paused + start.elapsed(),
+            _ => self.elapsed_when_paused,
         }
     }
 
+    /// Whether the scope has anything moving to show
+    pub fn scope_live(&self) -> bool {
+        self.live_input.is_some() || self.state == PlaybackState::Playing
+    }
+
     pub fn get_window(&self, window_size: usize) -> Matrix<f64> {
-        // If paused or streaming (no data), return a flat line
-        if self.is_paused || self.is_streaming_mode {
-            return vec![vec![0.0; window_size]; self.channels];
-        }
+        let mut window = Vec::new();
+        self.fill_window(window_size, &mut window);
+        window
+    }
 
-        let elapsed_seconds = self.get_current_time().as_secs_f64();
-        let start_sample = (elapsed_seconds * self.sample_rate as f64) as usize;
+    /// What get_window gives, written into `out` so the scope can reuse its buffers every frame
+    pub fn fill_window(&self, window_size: usize, out: &mut Matrix<f64>) {
+        if let Some(input) = &self.live_input
+            && let Ok(input) = input.lock() {
+            return input.fill_window(window_size, out);
+        }
 
-        // Safety check if audio_data is empty (should cover streaming mode, but double check)
-        if self.audio_data.is_empty() || self.audio_data[0].is_empty() {
-             return vec![vec![0.0; window_size]; self.channels];
+        out.resize_with(self.channels, Vec::new);
+        for channel in out.iter_mut() {
+            channel.clear();
         }
 
-        let mut window = vec![Vec::new(); self.channels];
-        for ch in 0..self.channels {
-            if start_sample < self.audio_data[ch].len() {
-                let end = std::cmp::min(start_sample + window_size, self.audio_data[ch].len());
-                window[ch] = self.audio_data[ch][start_sample..end].to_vec();
-                if window[ch].len() < window_size {
-                     window[ch].resize(window_size, 0.0);
+        // Only a playing, fully loaded file has samples to show; anything else is a flat line
+        if self.state == PlaybackState::Playing && !self.is_streaming_mode {
+            let elapsed_seconds = self.get_current_time().as_secs_f64();
+            let start_sample = (elapsed_seconds * self.sample_rate as f64) as usize;
+            for (out, channel) in out.iter_mut().zip(&self.audio_data) {
+                if start_sample < channel.len() {
+                    let end = std::cmp::min(start_sample + window_size, channel.len());
+                    out.extend_from_slice(&channel[start_sample..end]);
                 }
-            } else {
-                window[ch] = vec![0.0; window_size];
             }
         }
-        window
+
+        for channel in out.iter_mut() {
+            channel.resize(window_size, 0.0);
+        }
     }
 
     pub fn toggle_pause(&mut self) {
         if let Some(sink) = &self.sink {
-            if self.is_paused {
-                // RESUME
-                sink.play();
-                self.is_paused = false;
-                self.start_time = Some(Instant::now());
-            } else {
-                // PAUSE
-                sink.pause();
-                self.is_paused = true;
-                // Capture elapsed time up to this moment
-                if let Some(start) = self.start_time {
-                    self.elapsed_when_paused += start.elapsed();
-                }
-                self.start_time = None;
+            match self.state {
+                PlaybackState::Paused => {
+                    // RESUME
+                    sink.play();
+                    self.state = PlaybackState::Playing;
+                    self.start_time = Some(Instant::now());
+                },
+                PlaybackState::Playing => {
+                    // PAUSE
+                    sink.pause();
+                    self.state = PlaybackState::Paused;
+                    // Capture elapsed time up to this moment
+                    if let Some(start) = self.start_time {
+                        self.elapsed_when_paused += start.elapsed();
+                    }
+                    self.start_time = None;
+                },
+                _ => {}
             }
         }
     }
@@ -299,3 +562,30 @@ impl AudioPlayer {
         self.set_volume(self.volume - 0.1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn a_superseded_station_reports_nothing() {
+        // Nothing listens on the discard port, so tuning in fails at once
+        let (tx, rx) = mpsc::channel();
+        AudioPlayer::radio_async("http://127.0.0.1:9/".to_string(), tx.clone(), Arc::new(AtomicBool::new(false)));
+        AudioPlayer::radio_async("http://127.0.0.1:9/".to_string(), tx, Arc::new(AtomicBool::new(true)));
+        let events: Vec<AppEvent> = rx.iter().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], AppEvent::AudioError(e) if e.starts_with("Stream request failed")));
+    }
+
+    #[test]
+    fn a_superseded_download_reports_nothing() {
+        let (tx, rx) = mpsc::channel();
+        AudioPlayer::load_source_async("http://127.0.0.1:9/kept.mp3".to_string(), tx.clone(), Arc::new(AtomicBool::new(false)));
+        AudioPlayer::load_source_async("http://127.0.0.1:9/dropped.mp3".to_string(), tx, Arc::new(AtomicBool::new(true)));
+        let events: Vec<AppEvent> = rx.iter().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], AppEvent::AudioError(e) if e.starts_with("Download failed")));
+    }
+}
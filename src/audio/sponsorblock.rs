@@ -0,0 +1,42 @@
+use serde_derive::{Deserialize, Serialize};
+use super::stream::is_offline;
+
+const API_URL: &str = "https://sponsor.ajay.app/api/skipSegments";
+// Segments worth skipping in music uploads
+const CATEGORIES: &str = r#"["sponsor","intro","outro"]"#;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkipSegment {
+    pub category: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Deserialize)]
+struct ApiSegment {
+    category: String,
+    segment: [f64; 2],
+}
+
+/// Look up SponsorBlock skip ranges for a YouTube video id. No submissions is not an error.
+pub fn fetch_segments(video_id: &str) -> Result<Vec<SkipSegment>, String> {
+    if is_offline() {
+        return Ok(Vec::new());
+    }
+    let response = ureq::get(API_URL)
+        .query("videoID", video_id)
+        .query("categories", CATEGORIES)
+        .call();
+
+    let segments: Vec<ApiSegment> = match response {
+        Ok(response) => serde_json::from_reader(response.into_reader()).map_err(|e| format!("Bad SponsorBlock response: {}", e))?,
+        Err(ureq::Error::Status(404, _)) => return Ok(Vec::new()),
+        Err(e) => return Err(format!("SponsorBlock request failed: {}", e)),
+    };
+
+    Ok(segments
+        .into_iter()
+        .filter(|s| s.segment[1] > s.segment[0])
+        .map(|s| SkipSegment { category: s.category, start: s.segment[0], end: s.segment[1] })
+        .collect())
+}
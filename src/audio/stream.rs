@@ -1,66 +1,1037 @@
-use std::process::Command;
-use std::path::Path;
-use serde_derive::Deserialize; // We need serde for JSON parsing
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Condvar, Mutex, OnceLock, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde_derive::{Deserialize, Serialize}; // We need serde for JSON parsing
+use super::sponsorblock::SkipSegment;
 
-#[derive(Deserialize, Debug)]
+#[cfg(windows)]
+const YT_DLP_NAMES: &[&str] = &["yt-dlp.exe", "yt-dlp"];
+#[cfg(not(windows))]
+const YT_DLP_NAMES: &[&str] = &["yt-dlp"];
+
+// Full yt-dlp stderr goes here, the UI only gets a one-line summary
+pub const LOG_PATH: &str = "sound_cows.log";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum YtDlpError {
+    VideoUnavailable,
+    GeoBlocked,
+    NetworkError,
+    BinaryMissing(String), // Where we looked
+    UnsupportedUrl,
+    AgeRestricted,
+    CookiesUnreadable(String), // The configured path
+    Offline,
+    Cancelled,
+    Other(String),
+}
+
+impl YtDlpError {
+    /// Classify a failed run from its stderr, logging the full text for debugging
+    pub fn from_stderr(context: &str, stderr: &str) -> YtDlpError {
+        log_stderr(context, stderr);
+        YtDlpError::classify(stderr)
+    }
+
+    fn classify(stderr: &str) -> YtDlpError {
+        let lower = stderr.to_lowercase();
+        let has = |patterns: &[&str]| patterns.iter().any(|p| lower.contains(p));
+
+        if has(&["video unavailable", "private video", "has been removed", "http error 404", "does not exist"]) {
+            YtDlpError::VideoUnavailable
+        } else if has(&["not available in your country", "blocked it in your country", "geo restrict", "geo-restrict"]) {
+            YtDlpError::GeoBlocked
+        } else if has(&["confirm your age", "age-restricted", "inappropriate for some users"]) {
+            YtDlpError::AgeRestricted
+        } else if has(&["unsupported url", "is not a valid url"]) {
+            YtDlpError::UnsupportedUrl
+        } else if has(&[
+            "timed out",
+            "connection",
+            "temporary failure",
+            "network is unreachable",
+            "http error 5",
+            "unable to download",
+        ]) {
+            YtDlpError::NetworkError
+        } else {
+            // Keep the most useful single line: yt-dlp's own ERROR: line, or the last thing it said
+            let line = stderr
+                .lines()
+                .find_map(|l| l.trim().strip_prefix("ERROR:"))
+                .or_else(|| stderr.lines().rev().find(|l| !l.trim().is_empty()))
+                .unwrap_or("yt-dlp failed")
+                .trim();
+            YtDlpError::Other(line.to_string())
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            YtDlpError::VideoUnavailable => "Video unavailable".to_string(),
+            YtDlpError::GeoBlocked => "Blocked in your region".to_string(),
+            YtDlpError::NetworkError => "Network error".to_string(),
+            YtDlpError::BinaryMissing(tried) => format!("yt-dlp not found (tried {})", tried),
+            YtDlpError::UnsupportedUrl => "Unsupported URL".to_string(),
+            YtDlpError::AgeRestricted => "Age-restricted, sign-in required".to_string(),
+            YtDlpError::CookiesUnreadable(path) => format!("Can't read cookies file {}", path),
+            YtDlpError::Offline => "Offline".to_string(),
+            YtDlpError::Cancelled => "Download cancelled".to_string(),
+            YtDlpError::Other(line) => line.clone(),
+        }
+    }
+
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            YtDlpError::VideoUnavailable => Some("try another result"),
+            YtDlpError::GeoBlocked => Some("try another upload"),
+            YtDlpError::NetworkError => Some("check your connection"),
+            YtDlpError::BinaryMissing(_) => Some("install yt-dlp or set YT_DLP_PATH"),
+            YtDlpError::UnsupportedUrl => Some("check the link"),
+            YtDlpError::AgeRestricted => Some("set a cookies file with --cookies"),
+            YtDlpError::CookiesUnreadable(_) => Some("check the --cookies path"),
+            YtDlpError::Offline => Some("press O to go online"),
+            YtDlpError::Cancelled | YtDlpError::Other(_) => None,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        *self == YtDlpError::NetworkError
+    }
+}
+
+impl std::fmt::Display for YtDlpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.suggestion() {
+            Some(suggestion) => write!(f, "{} ({})", self.message(), suggestion),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+fn log_stderr(context: &str, stderr: &str) {
+    if let Ok(mut log) = OpenOptions::new().create(true).append(true).open(LOG_PATH) {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let _ = writeln!(log, "[{}] {}\n{}", timestamp, context, stderr.trim_end());
+    }
+}
+
+// What went wrong starting `command`; a missing binary is named so the message says where it looked
+fn spawn_error(command: &Command, e: std::io::Error) -> YtDlpError {
+    let binary = command.get_program().to_string_lossy();
+    if e.kind() == std::io::ErrorKind::NotFound {
+        YtDlpError::BinaryMissing(binary.into_owned())
+    } else {
+        YtDlpError::Other(format!("Failed to execute {}: {}", binary, e))
+    }
+}
+
+fn wait_error(e: std::io::Error) -> YtDlpError {
+    YtDlpError::Other(format!("Lost track of yt-dlp: {}", e))
+}
+
+// Path from the settings file, set once at startup
+static CONFIGURED_YT_DLP: OnceLock<String> = OnceLock::new();
+
+pub fn set_configured_yt_dlp(path: String) {
+    let _ = CONFIGURED_YT_DLP.set(path);
+}
+
+/// Find the yt-dlp binary: $YT_DLP_PATH, then the configured path, then $PATH, then the working directory
+pub fn find_yt_dlp() -> Result<PathBuf, YtDlpError> {
+    let mut tried = Vec::new();
+
+    if let Ok(path) = env::var("YT_DLP_PATH") {
+        if Path::new(&path).is_file() {
+            return Ok(PathBuf::from(path));
+        }
+        tried.push(format!("$YT_DLP_PATH ({})", path));
+    }
+
+    if let Some(path) = CONFIGURED_YT_DLP.get() {
+        if Path::new(path).is_file() {
+            return Ok(PathBuf::from(path));
+        }
+        tried.push(format!("config ({})", path));
+    }
+
+    if let Some(paths) = env::var_os("PATH") {
+        for dir in env::split_paths(&paths) {
+            for name in YT_DLP_NAMES {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return Ok(candidate);
+                }
+            }
+        }
+    }
+    tried.push("$PATH".to_string());
+
+    for name in YT_DLP_NAMES {
+        let candidate = Path::new(".").join(name);
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+        tried.push(format!("./{}", name));
+    }
+
+    Err(YtDlpError::BinaryMissing(tried.join(", ")))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CookieSource {
+    File(String),
+    Browser(String), // Passed straight to --cookies-from-browser
+}
+
+impl CookieSource {
+    pub fn describe(&self) -> String {
+        match self {
+            CookieSource::File(path) => format!("file {}", path),
+            CookieSource::Browser(browser) => format!("from browser {}", browser),
+        }
+    }
+}
+
+// Cookies for age-restricted/members-only content, set once at startup
+static COOKIES: OnceLock<CookieSource> = OnceLock::new();
+
+pub fn set_cookies(source: CookieSource) {
+    let _ = COOKIES.set(source);
+}
+
+// Offline mode: nothing that touches the network may run
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// A yt-dlp command with the configured cookies already applied
+fn yt_dlp_command() -> Result<Command, YtDlpError> {
+    if is_offline() {
+        return Err(YtDlpError::Offline);
+    }
+    let mut command = Command::new(find_yt_dlp()?);
+    match COOKIES.get() {
+        Some(CookieSource::File(path)) => {
+            // yt-dlp's own error for this is a Python traceback, catch it up front
+            if File::open(path).is_err() {
+                return Err(YtDlpError::CookiesUnreadable(path.clone()));
+            }
+            command.arg("--cookies").arg(path);
+        },
+        Some(CookieSource::Browser(browser)) => {
+            command.arg("--cookies-from-browser").arg(browser);
+        },
+        None => {},
+    }
+    Ok(command)
+}
+
+pub fn yt_dlp_version() -> Result<String, String> {
+    let binary = find_yt_dlp().map_err(|e| e.to_string())?;
+    let output = Command::new(&binary)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", binary.display(), e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!("yt-dlp --version failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Provider {
+    #[default]
+    YouTube,
+    SoundCloud,
+    Bandcamp,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] = [Provider::YouTube, Provider::SoundCloud, Provider::Bandcamp];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::YouTube => "YouTube",
+            Provider::SoundCloud => "SoundCloud",
+            Provider::Bandcamp => "Bandcamp",
+        }
+    }
+
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Provider::YouTube => "YT",
+            Provider::SoundCloud => "SC",
+            Provider::Bandcamp => "BC",
+        }
+    }
+
+    pub fn next(&self) -> Provider {
+        let i = Provider::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Provider::ALL[(i + 1) % Provider::ALL.len()]
+    }
+
+    // yt-dlp search extractor prefix; Bandcamp has none, only direct URLs work there
+    fn search_prefix(&self) -> Option<&'static str> {
+        match self {
+            Provider::YouTube => Some("ytsearch5"),
+            Provider::SoundCloud => Some("scsearch5"),
+            Provider::Bandcamp => None,
+        }
+    }
+
+    /// Guess which site a URL belongs to, for tagging results
+    pub fn from_url(url: &str) -> Provider {
+        if url.contains("soundcloud.com") {
+            Provider::SoundCloud
+        } else if url.contains("bandcamp.com") {
+            Provider::Bandcamp
+        } else {
+            Provider::YouTube
+        }
+    }
+
+    /// Split an `@yt` / `@sc` / `@bc` prefix off a query, if present
+    pub fn from_query(query: &str) -> (Option<Provider>, &str) {
+        let prefixes = [("@yt", Provider::YouTube), ("@sc", Provider::SoundCloud), ("@bc", Provider::Bandcamp)];
+        for (prefix, provider) in prefixes {
+            if let Some(rest) = query.strip_prefix(prefix)
+                && (rest.is_empty() || rest.starts_with(' ')) {
+                return (Some(provider), rest.trim_start());
+            }
+        }
+        (None, query)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+// One search result or playlist entry
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct YtDlpResult {
     pub title: String,
-    pub url: String, // Or webpage_url
+    pub url: String, // Replaced by webpage_url when yt-dlp gives one
     pub webpage_url: Option<String>,
+    pub duration: Option<f64>,
+    pub uploader: Option<String>,
+    #[serde(skip)]
+    pub cached: bool, // Downloaded already; the app fills this in, see App::cache_changed
+}
+
+// The parts of a full `--dump-json` we keep next to the cached file
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TrackMetadata {
+    pub id: Option<String>,
+    pub extractor_key: Option<String>, // "Youtube", "Soundcloud", ...
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub uploader: Option<String>, // Channel name, the best we get for most uploads
+    pub album: Option<String>,
+    pub duration: Option<f64>,
+    pub thumbnail: Option<String>, // Cover or video thumbnail URL
+    #[serde(deserialize_with = "null_as_empty")]
+    pub chapters: Vec<Chapter>,
+    pub skip_segments: Vec<SkipSegment>, // Filled in from SponsorBlock, not by yt-dlp
+}
+
+impl TrackMetadata {
+    pub fn youtube_id(&self) -> Option<&str> {
+        match self.extractor_key.as_deref() {
+            Some("Youtube") => self.id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+// yt-dlp writes `"chapters": null` for videos without chapters
+fn null_as_empty<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<Chapter>, D::Error> {
+    use serde::Deserialize;
+    Ok(Option::<Vec<Chapter>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Fetch full metadata for a single track (chapters etc.) without downloading it
+pub fn fetch_metadata(url: &str) -> Result<TrackMetadata, YtDlpError> {
+    let mut command = yt_dlp_command()?;
+    command
+        .arg("--dump-json")
+        .arg("--no-playlist")
+        .arg("--skip-download")
+        .arg("--no-warnings")
+        .arg(url);
+    let output = command.output().map_err(|e| spawn_error(&command, e))?;
+
+    if !output.status.success() {
+        return Err(YtDlpError::from_stderr(&format!("metadata {}", url), &String::from_utf8_lossy(&output.stderr)));
+    }
+
+    serde_json::from_slice(&output.stdout).map_err(|e| YtDlpError::Other(format!("Bad metadata: {}", e)))
+}
+
+// A `--flat-playlist` line as yt-dlp actually writes it: any field can be missing or null
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawEntry {
+    title: Option<String>,
+    url: Option<String>,
+    webpage_url: Option<String>,
+    id: Option<String>,
+    ie_key: Option<String>,
+    extractor_key: Option<String>,
+    duration: Option<f64>,
+    uploader: Option<String>,
+    channel: Option<String>,
+}
+
+// Entries that came back from one search or playlist listing
+#[derive(Debug, Default)]
+pub struct Listing {
+    pub entries: Vec<YtDlpResult>,
+    pub skipped: usize, // Lines that weren't usable entries
+}
+
+impl Listing {
+    fn push_line(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        match parse_entry(line) {
+            Some(entry) => self.entries.push(entry),
+            None => self.skipped += 1,
+        }
+    }
+}
+
+// Best page URL for an entry: webpage_url, then url, then one built from the id
+fn entry_url(raw: &RawEntry) -> Option<String> {
+    let is_http = |u: &&String| u.starts_with("http://") || u.starts_with("https://");
+    if let Some(url) = raw.webpage_url.as_ref().filter(is_http).or(raw.url.as_ref().filter(is_http)) {
+        return Some(url.clone());
+    }
+
+    // Older yt-dlp versions put the bare video id in `url`
+    let id = raw.id.as_ref().or(raw.url.as_ref())?;
+    match raw.ie_key.as_deref().or(raw.extractor_key.as_deref()) {
+        Some("Youtube") => Some(format!("https://www.youtube.com/watch?v={}", id)),
+        _ => None,
+    }
+}
+
+fn parse_entry(line: &str) -> Option<YtDlpResult> {
+    let raw = serde_json::from_str::<RawEntry>(line).ok()?;
+    let url = entry_url(&raw)?;
+    Some(YtDlpResult {
+        title: raw.title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| "(untitled)".to_string()),
+        webpage_url: Some(url.clone()),
+        url,
+        duration: raw.duration,
+        uploader: raw.uploader.or(raw.channel),
+        cached: false,
+    })
+}
+
+/// Whether a URL points at a whole playlist/set/album rather than a single track
+pub fn is_playlist_url(url: &str) -> bool {
+    url.contains("list=") || url.contains("/playlist") || url.contains("/sets/") || url.contains("/album/")
+}
+
+/// Enumerate a playlist's entries without downloading them, calling `on_entry` with the running count
+pub fn list_playlist(url: &str, mut on_entry: impl FnMut(usize)) -> Result<Listing, YtDlpError> {
+    let mut command = yt_dlp_command()?;
+    command
+        .arg("--flat-playlist")
+        .arg("--dump-json")
+        .arg("--no-warnings")
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(|e| spawn_error(&command, e))?;
+
+    let mut listing = Listing::default();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let before = listing.entries.len();
+            listing.push_line(&line);
+            if listing.entries.len() > before {
+                on_entry(listing.entries.len());
+            }
+        }
+    }
+
+    let output = child.wait_with_output().map_err(wait_error)?;
+    if output.status.success() || !listing.entries.is_empty() {
+        Ok(listing)
+    } else {
+        Err(YtDlpError::from_stderr(&format!("playlist {}", url), &String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioQuality {
+    Low,
+    Medium,
+    High,
+    Best,
+}
+
+impl AudioQuality {
+    pub const ALL: [AudioQuality; 4] = [AudioQuality::Low, AudioQuality::Medium, AudioQuality::High, AudioQuality::Best];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioQuality::Low => "LOW",
+            AudioQuality::Medium => "MEDIUM",
+            AudioQuality::High => "HIGH",
+            AudioQuality::Best => "BEST",
+        }
+    }
+
+    pub fn next(&self) -> AudioQuality {
+        let i = AudioQuality::ALL.iter().position(|q| q == self).unwrap_or(0);
+        AudioQuality::ALL[(i + 1) % AudioQuality::ALL.len()]
+    }
+
+    // Source format to pick before converting to mp3
+    fn format_selector(&self) -> &'static str {
+        match self {
+            AudioQuality::Low => "worstaudio[abr>=48]/worstaudio/bestaudio",
+            AudioQuality::Medium => "bestaudio[abr<=128]/bestaudio",
+            AudioQuality::High => "bestaudio[abr<=192]/bestaudio",
+            AudioQuality::Best => "bestaudio",
+        }
+    }
+
+    // Value for --audio-quality (mp3 bitrate, or 0 for the best VBR)
+    fn audio_quality_arg(&self) -> &'static str {
+        match self {
+            AudioQuality::Low => "96K",
+            AudioQuality::Medium => "128K",
+            AudioQuality::High => "192K",
+            AudioQuality::Best => "0",
+        }
+    }
+}
+
+// Quality for new downloads, changeable at runtime from the STAT tab
+static QUALITY: RwLock<AudioQuality> = RwLock::new(AudioQuality::High);
+
+pub fn set_audio_quality(quality: AudioQuality) {
+    *QUALITY.write().unwrap() = quality;
+}
+
+pub fn audio_quality() -> AudioQuality {
+    *QUALITY.read().unwrap()
+}
+
+// Formats rodio can decode progressively (no webm/opus support)
+const STREAM_FORMAT: &str = "bestaudio[ext=m4a]/bestaudio[ext=mp3]/bestaudio[ext=ogg]";
+
+/// Ask yt-dlp for the direct media URL (and duration, if known) without downloading anything
+pub fn extract_stream_url(url: &str) -> Result<(String, Option<f64>), YtDlpError> {
+    let mut command = yt_dlp_command()?;
+    command
+        .arg("-f")
+        .arg(STREAM_FORMAT)
+        .arg("--no-warnings")
+        .arg("--print")
+        .arg("duration")
+        .arg("--print")
+        .arg("urls")
+        .arg(url);
+    let output = command.output().map_err(|e| spawn_error(&command, e))?;
+
+    if !output.status.success() {
+        return Err(YtDlpError::from_stderr(&format!("stream {}", url), &String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let duration = lines.next().and_then(|d| d.trim().parse::<f64>().ok());
+    match lines.next() {
+        Some(media_url) if !media_url.trim().is_empty() => Ok((media_url.trim().to_string(), duration)),
+        _ => Err(YtDlpError::Other("yt-dlp returned no stream URL".to_string())),
+    }
+}
+
+// Waits before each retry of a failed download
+const RETRY_DELAYS: [Duration; 3] = [Duration::from_secs(1), Duration::from_secs(4), Duration::from_secs(10)];
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Marks our --progress-template lines on stdout; byte counts follow, "NA" when yt-dlp doesn't know
+const PROGRESS_PREFIX: &str = "sound_cows-progress";
+
+// (downloaded, total) from one of our progress lines. Fragmented downloads only have an estimated total.
+fn parse_progress(line: &str) -> Option<(u64, Option<u64>)> {
+    let mut fields = line.strip_prefix(PROGRESS_PREFIX)?.split_whitespace();
+    let bytes = |field: Option<&str>| field.and_then(|f| f.parse::<f64>().ok()).filter(|b| *b >= 0.0).map(|b| b as u64);
+    let downloaded = bytes(fields.next())?;
+    Some((downloaded, bytes(fields.next()).filter(|total| *total > 0)))
 }
 
-pub fn download_audio(url: &str, output_path: &Path) -> Result<(), String> {
-    let output = Command::new("./yt-dlp")
+/// `on_progress(downloaded, total)` is called as yt-dlp reports progress
+pub fn download_audio(
+    url: &str,
+    output_path: &Path,
+    quality: AudioQuality,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<(), YtDlpError> {
+    let mut command = yt_dlp_command()?;
+    command
+        .arg("-f")
+        .arg(quality.format_selector())
         .arg("-x") // Extract audio
         .arg("--audio-format")
         .arg("mp3")
+        .arg("--audio-quality")
+        .arg(quality.audio_quality_arg())
         .arg("-o")
         .arg(output_path)
         .arg("--force-overwrites") // Overwrite if exists
+        .arg("--newline")
+        .arg("--progress-template")
+        .arg(format!("download:{} %(progress.downloaded_bytes)s %(progress.total_bytes,progress.total_bytes_estimate)s", PROGRESS_PREFIX))
         .arg(url)
-        .output();
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(|e| spawn_error(&command, e))?;
 
-    match output {
-        Ok(o) => {
-            if o.status.success() {
-                Ok(())
-            } else {
-                Err(format!("yt-dlp error: {}", String::from_utf8_lossy(&o.stderr)))
+    // Drain stderr on the side so a chatty yt-dlp can't fill the pipe and hang
+    let stderr = child.stderr.take();
+    let stderr_reader = thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text);
+        }
+        text
+    });
+    // Progress lines come back over a channel so `on_progress` stays on this thread
+    let (progress_tx, progress_rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            for progress in BufReader::new(stdout).lines().map_while(Result::ok).filter_map(|line| parse_progress(&line)) {
+                let _ = progress_tx.send(progress);
             }
-        },
-        Err(e) => Err(format!("Failed to execute yt-dlp: {}", e)),
+        });
+    }
+
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(YtDlpError::Cancelled);
+        }
+        for (downloaded, total) in progress_rx.try_iter() {
+            on_progress(downloaded, total);
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                let stderr = stderr_reader.join().unwrap_or_default();
+                return if status.success() {
+                    Ok(())
+                } else {
+                    Err(YtDlpError::from_stderr(&format!("download {}", url), &stderr))
+                };
+            },
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(wait_error(e)),
+        }
+    }
+}
+
+/// `download_audio` with exponential backoff. `on_retry(n, max)` is called before the n-th retry.
+pub fn download_with_retry(
+    url: &str,
+    output_path: &Path,
+    quality: AudioQuality,
+    cancel: &AtomicBool,
+    on_retry: impl FnMut(usize, usize),
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<(), YtDlpError> {
+    retry(&RETRY_DELAYS, cancel, on_retry, || download_audio(url, output_path, quality, cancel, &mut on_progress))
+}
+
+// Runs `attempt` until it succeeds, fails for good, or every delay in `delays` has been waited out
+fn retry(
+    delays: &[Duration],
+    cancel: &AtomicBool,
+    mut on_retry: impl FnMut(usize, usize),
+    mut attempt: impl FnMut() -> Result<(), YtDlpError>,
+) -> Result<(), YtDlpError> {
+    let mut retries = 0;
+    loop {
+        match attempt() {
+            Ok(()) => return Ok(()),
+            Err(e) if retries < delays.len() && e.is_retryable() => {
+                retries += 1;
+                on_retry(retries, delays.len());
+                if !sleep_unless_cancelled(delays[retries - 1], cancel) {
+                    return Err(YtDlpError::Cancelled);
+                }
+            },
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Returns false if cancelled while waiting
+fn sleep_unless_cancelled(duration: Duration, cancel: &AtomicBool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
     }
+    !cancel.load(Ordering::Relaxed)
+}
+
+// At most this many yt-dlp processes downloading ahead at once
+const MAX_PREFETCH_WORKERS: usize = 2;
+
+#[derive(Default)]
+struct PrefetchQueue {
+    pending: VecDeque<String>,
+    in_progress: Vec<String>,
 }
 
-pub fn search_audio(query: &str) -> Result<Vec<(String, String)>, String> {
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueueStats {
+    pub pending: usize,
+    pub in_progress: usize,
+}
+
+/// Small worker pool that downloads upcoming tracks in the background.
+/// `job` does the actual work (and reporting) for one URL.
+pub struct DownloadManager {
+    queue: Arc<(Mutex<PrefetchQueue>, Condvar)>,
+    cancel: Arc<AtomicBool>, // Set on shutdown, stops the running jobs
+}
+
+impl DownloadManager {
+    pub fn new(job: impl Fn(&str, &AtomicBool) + Send + Sync + 'static) -> Self {
+        let queue = Arc::new((Mutex::new(PrefetchQueue::default()), Condvar::new()));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let job = Arc::new(job);
+
+        for _ in 0..MAX_PREFETCH_WORKERS {
+            let queue = Arc::clone(&queue);
+            let job = Arc::clone(&job);
+            let cancel = Arc::clone(&cancel);
+            thread::spawn(move || {
+                let (lock, available) = &*queue;
+                loop {
+                    let url = {
+                        let mut state = lock.lock().unwrap();
+                        while state.pending.is_empty() {
+                            state = available.wait(state).unwrap();
+                        }
+                        let url = state.pending.pop_front().unwrap();
+                        state.in_progress.push(url.clone());
+                        url
+                    };
+
+                    job(&url, &cancel);
+
+                    lock.lock().unwrap().in_progress.retain(|u| *u != url);
+                }
+            });
+        }
+
+        DownloadManager { queue, cancel }
+    }
+
+    /// Drop the queue and stop the downloads in progress
+    pub fn shutdown(&self) {
+        self.clear();
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Queue `url` unless it's already queued or downloading
+    pub fn enqueue(&self, url: String) {
+        let (lock, available) = &*self.queue;
+        let mut state = lock.lock().unwrap();
+        if state.pending.contains(&url) || state.in_progress.contains(&url) {
+            return;
+        }
+        state.pending.push_back(url);
+        available.notify_one();
+    }
+
+    /// Take `url` off the queue if no worker picked it up yet. Returns true if it was queued.
+    pub fn dequeue(&self, url: &str) -> bool {
+        let mut state = self.queue.0.lock().unwrap();
+        let before = state.pending.len();
+        state.pending.retain(|u| u != url);
+        state.pending.len() != before
+    }
+
+    /// Drop everything that hasn't started yet
+    pub fn clear(&self) {
+        self.queue.0.lock().unwrap().pending.clear();
+    }
+
+    pub fn is_downloading(&self, url: &str) -> bool {
+        self.queue.0.lock().unwrap().in_progress.iter().any(|u| u == url)
+    }
+
+    /// Distinct downloads queued or running, counting `url` as one more unless it's among them
+    pub fn outstanding_with(&self, url: &str) -> usize {
+        let state = self.queue.0.lock().unwrap();
+        let mut urls: Vec<&str> = state.pending.iter().chain(&state.in_progress).map(String::as_str).collect();
+        urls.push(url);
+        urls.sort_unstable();
+        urls.dedup();
+        urls.len()
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        let state = self.queue.0.lock().unwrap();
+        QueueStats { pending: state.pending.len(), in_progress: state.in_progress.len() }
+    }
+}
+
+/// Parse yt-dlp's one-JSON-object-per-line output
+pub fn parse_listing(stdout: &str) -> Listing {
+    let mut listing = Listing::default();
+    for line in stdout.lines() {
+        listing.push_line(line);
+    }
+    listing
+}
+
+pub fn search_audio(query: &str, provider: Provider) -> Result<Listing, YtDlpError> {
     // ytsearch5:query means "search youtube for query and get 5 results"
-    let search_query = format!("ytsearch5:{}", query);
+    let prefix = provider.search_prefix().ok_or_else(|| {
+        YtDlpError::Other(format!("{} has no search, paste a track or album URL instead", provider.name()))
+    })?;
+    let search_query = format!("{}:{}", prefix, query);
 
-    let output = Command::new("./yt-dlp")
+    let mut command = yt_dlp_command()?;
+    command
         .arg("--flat-playlist") // Don't download, just list
         .arg("--dump-json")     // Output as JSON
         .arg("--no-warnings")
-        .arg(&search_query)
-        .output();
+        .arg(&search_query);
+    let output = command.output();
 
     match output {
         Ok(o) => {
             if o.status.success() {
-                let stdout = String::from_utf8_lossy(&o.stdout);
-                let mut results = Vec::new();
-
-                // yt-dlp outputs one JSON object per line
-                for line in stdout.lines() {
-                    if let Ok(entry) = serde_json::from_str::<YtDlpResult>(line) {
-                        let url = entry.webpage_url.unwrap_or(entry.url);
-                        results.push((entry.title, url));
-                    }
-                }
-                Ok(results)
+                Ok(parse_listing(&String::from_utf8_lossy(&o.stdout)))
             } else {
-                Err(format!("yt-dlp search error: {}", String::from_utf8_lossy(&o.stderr)))
+                Err(YtDlpError::from_stderr(&format!("search {}", search_query), &String::from_utf8_lossy(&o.stderr)))
             }
         },
-        Err(e) => Err(format!("Failed to execute yt-dlp search: {}", e)),
+        Err(e) => Err(spawn_error(&command, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stderr_is_classified_by_what_yt_dlp_said() {
+        let cases = [
+            ("ERROR: [youtube] abc: Video unavailable. This video has been removed", YtDlpError::VideoUnavailable),
+            ("ERROR: [youtube] abc: Private video. Sign in if you've been granted access", YtDlpError::VideoUnavailable),
+            ("ERROR: unable to download webpage: HTTP Error 404: Not Found", YtDlpError::VideoUnavailable),
+            ("ERROR: This video is not available in your country", YtDlpError::GeoBlocked),
+            ("ERROR: This video is geo-restricted", YtDlpError::GeoBlocked),
+            ("ERROR: Sign in to confirm your age. This video may be inappropriate for some users.", YtDlpError::AgeRestricted),
+            ("ERROR: Unsupported URL: https://example.com/", YtDlpError::UnsupportedUrl),
+            ("ERROR: 'foo' is not a valid URL", YtDlpError::UnsupportedUrl),
+            ("ERROR: Unable to download webpage: <urlopen error timed out>", YtDlpError::NetworkError),
+            ("ERROR: [Errno -3] Temporary failure in name resolution", YtDlpError::NetworkError),
+            ("ERROR: HTTP Error 503: Service Unavailable", YtDlpError::NetworkError),
+        ];
+        for (stderr, expected) in cases {
+            assert_eq!(YtDlpError::classify(stderr), expected, "{}", stderr);
+        }
+    }
+
+    #[test]
+    fn unrecognised_stderr_keeps_its_most_useful_line() {
+        let cases = [
+            ("WARNING: something\nERROR: Requested format is not available\n", "Requested format is not available"),
+            ("Traceback (most recent call last):\n  ...\nKeyError: 'id'\n\n", "KeyError: 'id'"),
+            ("", "yt-dlp failed"),
+        ];
+        for (stderr, line) in cases {
+            assert_eq!(YtDlpError::classify(stderr), YtDlpError::Other(line.to_string()), "{:?}", stderr);
+        }
+    }
+
+    #[test]
+    fn a_missing_binary_is_named() {
+        let command = Command::new("/nowhere/yt-dlp");
+        let missing = spawn_error(&command, std::io::ErrorKind::NotFound.into());
+        assert_eq!(missing, YtDlpError::BinaryMissing("/nowhere/yt-dlp".to_string()));
+        assert_eq!(missing.message(), "yt-dlp not found (tried /nowhere/yt-dlp)");
+        let denied = spawn_error(&command, std::io::ErrorKind::PermissionDenied.into());
+        assert!(matches!(denied, YtDlpError::Other(message) if message.starts_with("Failed to execute /nowhere/yt-dlp")));
+    }
+
+    #[test]
+    fn the_awaited_download_counts_once() {
+        // Jobs hold their worker until shutdown, so the queue stays put while it's looked at
+        let manager = DownloadManager::new(|_, cancel| {
+            while !cancel.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+        for url in ["a", "b", "c"] {
+            manager.enqueue(url.to_string());
+        }
+        while !(manager.is_downloading("a") && manager.is_downloading("b")) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(manager.outstanding_with("a"), 3);
+        assert_eq!(manager.outstanding_with("c"), 3);
+        assert_eq!(manager.outstanding_with("d"), 4);
+        manager.shutdown();
+    }
+
+    // An attempt that fails with each of `failures` in turn, then succeeds. Counts its calls.
+    fn failing(failures: Vec<YtDlpError>, calls: &mut usize) -> impl FnMut() -> Result<(), YtDlpError> + '_ {
+        let mut failures = failures.into_iter();
+        move || {
+            *calls += 1;
+            failures.next().map_or(Ok(()), Err)
+        }
+    }
+
+    #[test]
+    fn retries_transient_failures_until_one_succeeds() {
+        let delays = [Duration::ZERO; 3];
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        let result = retry(&delays, &AtomicBool::new(false), |n, max| retries.push((n, max)), failing(vec![YtDlpError::NetworkError; 2], &mut calls));
+        assert_eq!(result, Ok(()));
+        assert_eq!(calls, 3);
+        assert_eq!(retries, [(1, 3), (2, 3)]);
+
+        // Out of retries, the last failure is what comes back
+        let mut calls = 0;
+        let result = retry(&delays, &AtomicBool::new(false), |_, _| {}, failing(vec![YtDlpError::NetworkError; 4], &mut calls));
+        assert_eq!(result, Err(YtDlpError::NetworkError));
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn permanent_failures_are_not_retried() {
+        let mut calls = 0;
+        let mut retried = false;
+        let result = retry(&[Duration::ZERO; 3], &AtomicBool::new(false), |_, _| retried = true, failing(vec![YtDlpError::VideoUnavailable], &mut calls));
+        assert_eq!(result, Err(YtDlpError::VideoUnavailable));
+        assert_eq!((calls, retried), (1, false));
+    }
+
+    #[test]
+    fn cancelling_during_the_backoff_stops_retrying() {
+        let cancel = AtomicBool::new(false);
+        let mut calls = 0;
+        let started = Instant::now();
+        let result = retry(&[Duration::from_secs(60); 3], &cancel, |_, _| cancel.store(true, Ordering::Relaxed), failing(vec![YtDlpError::NetworkError; 3], &mut calls));
+        assert_eq!(result, Err(YtDlpError::Cancelled));
+        assert_eq!(calls, 1);
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn reads_our_progress_lines() {
+        assert_eq!(parse_progress("sound_cows-progress 1024 4096"), Some((1024, Some(4096))));
+        // Estimated totals are floats, and the very first line can be missing one
+        assert_eq!(parse_progress("sound_cows-progress 2048 10240.5"), Some((2048, Some(10240))));
+        assert_eq!(parse_progress("sound_cows-progress 0 NA"), Some((0, None)));
+        assert_eq!(parse_progress("[download] Destination: x.webm"), None);
+        assert_eq!(parse_progress("sound_cows-progress NA NA"), None);
+    }
+
+    // Trimmed `--flat-playlist --dump-json` lines in the shapes yt-dlp produces (extra fields dropped)
+    const YTSEARCH: &str = r#"{"_type": "url", "ie_key": "Youtube", "id": "dQw4w9WgXcQ", "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ", "title": "Rick Astley - Never Gonna Give You Up (Official Music Video)", "description": null, "duration": 213.0, "channel_id": "UCuAXFkgsw1L7xaCfnd5JJOw", "channel": "Rick Astley", "uploader": null, "view_count": 1600000000, "live_status": null}
+{"_type": "url", "ie_key": "Youtube", "id": "yPYZpwSpKmA", "url": "https://www.youtube.com/watch?v=yPYZpwSpKmA", "title": "Rick Astley - Together Forever (Official Video)", "duration": 205.0, "channel": "Rick Astley", "uploader": "Rick Astley"}
+{"_type": "url", "ie_key": "Youtube", "id": "jfKfPfyJRdk", "url": "https://www.youtube.com/watch?v=jfKfPfyJRdk", "title": "lofi hip hop radio - beats to relax/study to", "duration": null, "channel": "Lofi Girl", "live_status": "is_live"}
+"#;
+
+    const SCSEARCH: &str = r#"{"_type": "url", "ie_key": "Soundcloud", "id": "1234567890", "url": "https://api.soundcloud.com/tracks/1234567890", "webpage_url": "https://soundcloud.com/some-artist/some-track", "title": "Some Track", "uploader": "Some Artist", "duration": 184.32}
+{"_type": "url", "ie_key": "Soundcloud", "url": "https://soundcloud.com/other-artist/untitled-demo", "title": "", "uploader": "Other Artist"}
+"#;
+
+    const PLAYLIST: &str = r#"{"_type": "url", "ie_key": "Youtube", "id": "dQw4w9WgXcQ", "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ", "title": "Never Gonna Give You Up", "duration": 213, "channel": "Rick Astley", "playlist_index": 1}
+
+{"_type": "url", "ie_key": "Youtube", "id": "kffacxfA7G4", "url": "kffacxfA7G4", "title": "[Deleted video]", "duration": null, "channel": null, "playlist_index": 2}
+WARNING: [youtube:tab] YouTube said: INFO - 1 unavailable video is hidden
+{"_type": "url", "ie_key": "Generic", "title": "Nowhere to be found", "playlist_index": 3}
+{"_type": "url", "ie_key": "Youtube", "id": "Zi_XLOBDo_Y", "title": null, "playlist_index": 4}
+"#;
+
+    #[test]
+    fn ytsearch_output_parses_every_entry() {
+        let listing = parse_listing(YTSEARCH);
+        assert_eq!(listing.skipped, 0);
+        assert_eq!(listing.entries.len(), 3);
+
+        let first = &listing.entries[0];
+        assert_eq!(first.url, "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(first.duration, Some(213.0));
+        // uploader is null here, channel fills in
+        assert_eq!(first.uploader.as_deref(), Some("Rick Astley"));
+
+        // Live streams have no duration but are still results
+        assert_eq!(listing.entries[2].duration, None);
+    }
+
+    #[test]
+    fn scsearch_prefers_webpage_url_and_names_untitled_tracks() {
+        let listing = parse_listing(SCSEARCH);
+        assert_eq!(listing.skipped, 0);
+        assert_eq!(listing.entries[0].url, "https://soundcloud.com/some-artist/some-track");
+        assert_eq!(listing.entries[1].url, "https://soundcloud.com/other-artist/untitled-demo");
+        assert_eq!(listing.entries[1].title, "(untitled)");
+    }
+
+    #[test]
+    fn playlist_dump_builds_urls_from_ids_and_counts_bad_lines() {
+        let listing = parse_listing(PLAYLIST);
+        let urls: Vec<&str> = listing.entries.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "https://www.youtube.com/watch?v=kffacxfA7G4",
+                "https://www.youtube.com/watch?v=Zi_XLOBDo_Y",
+            ]
+        );
+        assert_eq!(listing.entries[2].title, "(untitled)");
+        // The stray warning and the entry with no way to reach it; blank lines don't count
+        assert_eq!(listing.skipped, 2);
+    }
+
+    #[test]
+    fn integer_durations_are_accepted() {
+        let entry = parse_entry(PLAYLIST.lines().next().unwrap()).unwrap();
+        assert_eq!(entry.duration, Some(213.0));
     }
 }
@@ -0,0 +1,58 @@
+use std::path::Path;
+use id3::{Tag, TagLike, Version};
+use super::stream::TrackMetadata;
+
+// What the player shows for a local file, read from its ID3 tag
+#[derive(Debug, Clone, Default)]
+pub struct TrackTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+}
+
+impl TrackTags {
+    /// "Artist - Title (Album)", leaving out whatever isn't known
+    pub fn display(&self) -> Option<String> {
+        let name = match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            (Some(artist), None) => artist.clone(),
+            (None, None) => return None,
+        };
+        match &self.album {
+            Some(album) => Some(format!("{} ({})", name, album)),
+            None => Some(name),
+        }
+    }
+}
+
+/// Write title/artist/album from yt-dlp's metadata into the file's ID3v2 tag
+pub fn write_tags(path: &Path, metadata: &TrackMetadata) -> Result<(), String> {
+    let mut tag = Tag::read_from_path(path).unwrap_or_default();
+    if let Some(title) = &metadata.title {
+        tag.set_title(title.as_str());
+    }
+    if let Some(artist) = metadata.artist.as_ref().or(metadata.uploader.as_ref()) {
+        tag.set_artist(artist.as_str());
+    }
+    if let Some(album) = &metadata.album {
+        tag.set_album(album.as_str());
+    }
+    tag.write_to_path(path, Version::Id3v24).map_err(|e| format!("Failed to tag {}: {}", path.display(), e))
+}
+
+/// The first picture embedded in the file's ID3 tag (usually the front cover), as encoded image bytes
+pub fn read_picture(path: &Path) -> Option<Vec<u8>> {
+    let tag = Tag::read_from_path(path).ok()?;
+    tag.pictures().next().map(|picture| picture.data.clone())
+}
+
+pub fn read_tags(path: &Path) -> Option<TrackTags> {
+    let tag = Tag::read_from_path(path).ok()?;
+    let tags = TrackTags {
+        title: tag.title().map(str::to_string),
+        artist: tag.artist().map(str::to_string),
+        album: tag.album().map(str::to_string),
+    };
+    tags.display().is_some().then_some(tags)
+}
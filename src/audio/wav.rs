@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::scope::Matrix;
+
+const BITS: u16 = 16;
+
+/// `channels` (one Vec of -1.0..1.0 samples each, all as long) as a 16-bit PCM WAV file
+pub fn encode(channels: &Matrix<f64>, sample_rate: u32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write(&mut bytes, channels, sample_rate).expect("writing to a Vec doesn't fail");
+    bytes
+}
+
+pub fn write(out: &mut impl Write, channels: &Matrix<f64>, sample_rate: u32) -> io::Result<()> {
+    let frames = channels.iter().map(Vec::len).min().unwrap_or(0);
+    write_header(out, channels.len().max(1) as u16, sample_rate, frames as u32)?;
+    for frame in 0..frames {
+        for channel in channels {
+            write_sample(out, channel[frame])?;
+        }
+    }
+    Ok(())
+}
+
+fn write_header(out: &mut impl Write, channels: u16, sample_rate: u32, frames: u32) -> io::Result<()> {
+    let block_align = channels * BITS / 8;
+    let data_len = frames * block_align as u32;
+
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_len).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&channels.to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&BITS.to_le_bytes())?;
+
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())
+}
+
+fn write_sample(out: &mut impl Write, sample: f64) -> io::Result<()> {
+    let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16;
+    out.write_all(&sample.to_le_bytes())
+}
+
+/// A WAV file written as the samples come in, for recordings of unknown length.
+/// The header's sizes are filled in by `finish`.
+pub struct WavWriter {
+    out: BufWriter<File>,
+    channels: u16,
+    sample_rate: u32,
+    samples: u32,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        write_header(&mut out, channels, sample_rate, 0)?;
+        Ok(WavWriter { out, channels, sample_rate, samples: 0 })
+    }
+
+    /// Append interleaved samples, -1.0..1.0
+    pub fn write(&mut self, interleaved: &[f64]) -> io::Result<()> {
+        for sample in interleaved {
+            write_sample(&mut self.out, *sample)?;
+        }
+        self.samples += interleaved.len() as u32;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.seek(SeekFrom::Start(0))?;
+        write_header(&mut self.out, self.channels, self.sample_rate, self.samples / self.channels.max(1) as u32)?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use rodio::Source;
+    use std::io::Cursor;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn header_describes_the_samples() {
+        let bytes = encode(&vec![vec![0.0; 3], vec![0.0; 3]], 48_000);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16_at(&bytes, 20), 1);
+        assert_eq!(u16_at(&bytes, 22), 2);
+        assert_eq!(u32_at(&bytes, 24), 48_000);
+        assert_eq!(u32_at(&bytes, 28), 48_000 * 4);
+        assert_eq!(u16_at(&bytes, 32), 4);
+        assert_eq!(u16_at(&bytes, 34), 16);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(&bytes, 40), 12);
+        assert_eq!(bytes.len(), 44 + 12);
+    }
+
+    #[test]
+    fn decodes_back_to_the_same_samples() {
+        let left = vec![0.0, 0.5, -0.5, 1.0];
+        let right = vec![0.25, -0.25, 0.75, -1.0];
+        let bytes = encode(&vec![left.clone(), right.clone()], 22_050);
+
+        let decoder = rodio::Decoder::new_wav(Cursor::new(bytes)).unwrap();
+        assert_eq!((decoder.channels(), decoder.sample_rate()), (2, 22_050));
+        let decoded: Vec<f64> = decoder.map(|s: i16| s as f64 / i16::MAX as f64).collect();
+        let expected: Vec<f64> = left.iter().zip(&right).flat_map(|(l, r)| [*l, *r]).collect();
+        assert_eq!(decoded.len(), expected.len());
+        assert!(decoded.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-4), "{:?}", decoded);
+    }
+
+    #[test]
+    fn writer_fills_in_the_sizes_when_finished() {
+        let dir = TempDir::new();
+        let path = dir.join("test.wav");
+        let mut writer = WavWriter::create(&path, 2, 8_000).unwrap();
+        writer.write(&[0.5, -0.5]).unwrap();
+        writer.write(&[0.25, -0.25, 0.0, 0.0]).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), encode(&vec![vec![0.5, 0.25, 0.0], vec![-0.5, -0.25, 0.0]], 8_000));
+    }
+}
@@ -0,0 +1,4 @@
+//! The scope behind sound_cows' RADIO tab: oscilloscope, spectrum and vectorscope display modes,
+//! the processing in front of them, and `ScopeWidget` to draw one in any ratatui app.
+//! `examples/embed.rs` shows it on its own.
+pub mod scope;
@@ -1,4 +1,5 @@
 use crossterm::{
+    clipboard::CopyToClipboard,
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -7,18 +8,53 @@ use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
-use std::{error::Error, io, path::Path};
+use std::{error::Error, io::{self, IsTerminal, Write}, path::Path, time::{Duration, Instant}};
 
 mod app;
 mod audio;
-mod scope;
+mod format;
+#[cfg(test)]
+mod testing;
 mod ui;
 
-use app::state::{App, InputMode, AppEvent};
-use scope::display::{update_value_f, update_value_i, DisplayMode};
-use audio::player::AudioPlayer;
+use sound_cows::scope;
+use app::cli::{self, CliArgs};
+use app::{favorites::Favorites, history::History, maintenance, nav::ListMove, paths, podcasts::Podcasts, settings::Settings};
+use app::tab::Tab;
+use app::state::{App, ConfirmAction, InputMode, AppEvent, ResultSort, TrackInfo};
+use scope::channels::channel_key;
+use scope::display::{update_value_f, update_value_i};
+use audio::player::{AudioPlayer, PlaybackState};
+use format::format_bytes;
+use audio::stream::{is_offline, is_playlist_url, Provider};
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let cli = match CliArgs::parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if cli.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Err(e) = setup_data_dir(&cli) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if cli.check {
+        match check_library() {
+            Ok(summary) => println!("{}", summary),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -27,8 +63,9 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run it
-    let app = App::new();
-    let res = run_app(&mut terminal, app);
+    let mut app = App::new(&cli);
+    let res = run_app(&mut terminal, &mut app, cli.frame_interval());
+    app.shutdown();
 
     // Restore terminal
     disable_raw_mode()?;
@@ -46,51 +83,250 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<(), Box<dyn Error>>
+// Pick the data directory, offering to move files left in the working directory by older versions.
+// Only a typed "y" moves them; piped input isn't asked at all, and a "no" isn't asked again.
+fn setup_data_dir(cli: &CliArgs) -> Result<(), String> {
+    let dir = paths::init_data_dir(cli.data_dir.as_deref())?;
+    let legacy = paths::legacy_files();
+    let mut settings = Settings::load();
+    if legacy.is_empty() || settings.legacy_files_declined || !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    println!("Found {} in the current directory.", legacy.join(", "));
+    print!("Move to {}? [y/N] ", dir.display());
+    io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).map_err(|e| e.to_string())?;
+    if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        paths::migrate_legacy(&legacy)?;
+    } else {
+        settings.legacy_files_declined = true;
+        settings.save()?;
+        println!("Left them where they are; you won't be asked again.");
+    }
+    Ok(())
+}
+
+// --check: the STAT tab's library check, without the TUI
+fn check_library() -> Result<String, String> {
+    let mut podcasts = Podcasts::load();
+    let mut favorites = Favorites::load();
+    let mut history = History::load();
+    let report = maintenance::check_library(&mut podcasts.feeds, &mut favorites.items, &mut history.plays);
+    if report.library_changed() {
+        podcasts.save()?;
+        favorites.save()?;
+        history.save()?;
+    }
+    Ok(report.describe())
+}
+
+// How long to wait for input when nothing is animating, and how often to redraw anyway
+// (the clock, progress and expiring status messages)
+const IDLE_POLL: Duration = Duration::from_millis(50);
+const IDLE_REDRAW: Duration = Duration::from_secs(1);
+// One animation step; marquees and spinners move at the same speed whatever the frame rate
+const TICK: Duration = Duration::from_millis(16);
+
+/// Draws only when something happened, a frame of animation is due while playing or loading,
+/// or once a second otherwise, so an idle player sits at next to no CPU
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, frame_interval: Duration) -> Result<(), Box<dyn Error>>
 where <B as Backend>::Error: 'static {
+    let started = Instant::now();
+    let mut last_draw: Option<Instant> = None;
+    let mut changed = true;
     loop {
-        terminal.draw(|f| ui::layout::draw(f, &mut app)).map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Draw error: {}", e)))?;
+        // Keep going through the queue when a track runs out
+        if app.player.update_state() && !app.is_loading {
+            app.play_next_in_queue();
+        }
+        app.check_skip_segments();
+        app.check_history();
+        app.autosave_settings();
+        app.flush_mic();
+        app.update_pitch();
+        app.update_library_search();
+        app.status.expire(Instant::now());
 
-        // Check for async events non-blockingly
-        if let Ok(event) = app.event_rx.try_recv() {
+        // Everything the background threads have sent since last time
+        while let Ok(event) = app.event_rx.try_recv() {
+            changed = true;
             match event {
                 AppEvent::AudioLoaded(path) => {
                     app.is_loading = false;
-                    app.player.play_file(Path::new(&path));
-                    app.loading_status = Some("Playing URL".to_string());
-                    app.current_tab = 4; // Switch to Radio
+                    app.current_track = app.requested_track.take();
+                    if let Some(track) = app.current_track.as_mut() {
+                        track.load_metadata(Path::new(&path));
+                    }
+                    app.current_path = Some(path.clone());
+                    app.history_recorded = app.pending_resume.is_some();
+                    app.enforce_cache_limit(Path::new(&path));
+                    if let Some(track) = &app.current_track
+                        && app.podcasts.mark_played(&track.url)
+                        && let Err(e) = app.podcasts.save() {
+                        app.push_error(e);
+                    }
+
+                    if let Some(offset) = app.pending_resume.take() {
+                        let resumed = app.player.play_file_from(Path::new(&path), offset);
+                        app.pause_for_resume();
+                        match resumed {
+                            Ok(()) => app.push_status("Resumed last track (Space to play)"),
+                            Err(e) => app.push_error(e),
+                        }
+                    } else {
+                        app.player.play_file(Path::new(&path));
+                        app.graph_config.pause = false;
+                        match app.player.state() {
+                            PlaybackState::Errored(e) => app.push_error(e.clone()),
+                            _ => app.push_status("Playing URL"),
+                        }
+                    }
+                    app.track_started();
+                    app.download_finished();
+
+                    if let Err(e) = app.save_session() {
+                        app.push_error(e);
+                    }
                 },
                 AppEvent::AudioError(e) => {
                     app.is_loading = false;
-                    app.loading_status = Some(format!("Error: {}", e));
+                    app.download = None;
+                    app.player.load_failed(e.clone());
+                    app.requested_track = None;
+                    app.pending_resume = None;
+                    app.push_error(format!("Error: {}", e));
                 },
-                AppEvent::SearchFinished(results) => {
+                AppEvent::SearchFinished(provider, query, listing) => app.search_finished(provider, query, listing),
+                AppEvent::PlaylistListed(provider, listing) => app.playlist_listed(provider, listing),
+                AppEvent::PrefetchComplete(url, path) => app.prefetch_finished(url, Ok(path)),
+                AppEvent::PrefetchFailed(url, e) => app.prefetch_finished(url, Err(e)),
+                AppEvent::FeedRefreshed(url, result) => app.feed_refreshed(url, result),
+                AppEvent::DirectoryListed(dir, result) => app.directory_listed(dir, result),
+                AppEvent::FilesCollected(paths) => app.files_collected(paths),
+                AppEvent::ArtworkReady(url, artwork) => app.artwork_ready(url, artwork),
+                AppEvent::MetadataReady(path) => app.metadata_ready(path),
+                AppEvent::MicError(e) => app.mic_failed(e),
+                AppEvent::StreamReady(decoder, duration) => {
                     app.is_loading = false;
-                    app.search_results = results;
-                    app.loading_status = Some(format!("Found {} results", app.search_results.len()));
-                    if !app.search_results.is_empty() {
-                        app.search_results_state.select(Some(0));
-                        app.input_mode = InputMode::SearchResults;
-                    } else {
-                        app.input_mode = InputMode::Normal;
-                    }
+                    app.current_track = app.requested_track.take();
+                    app.current_path = None;
+                    app.history_recorded = false;
+                    app.player.play_stream(*decoder, duration);
+                    app.track_started();
+                    app.graph_config.pause = false;
+                    app.push_status("Streaming (no seeking)");
+                    app.switch_tab(Tab::Radio);
+                },
+                AppEvent::StreamFallback(e) => {
+                    app.push_progress(format!("Stream unavailable ({}), downloading instead...", e.trim()));
+                },
+                AppEvent::DownloadRetry(attempt, max) => {
+                    app.push_progress(format!("Download failed, retrying ({}/{})...", attempt, max));
                 },
-                AppEvent::SearchError(e) => {
+                AppEvent::DownloadProgress(downloaded, total) => {
+                    app.download_progressed(downloaded, total);
+                    app.push_progress(match total {
+                        Some(total) => format!("Downloading: {} / {}", format_bytes(downloaded), format_bytes(total)),
+                        None => format!("Downloading: {}", format_bytes(downloaded)),
+                    });
+                },
+                AppEvent::PlaylistProgress(count) => {
+                    app.push_progress(format!("Listing playlist: {} entries...", count));
+                },
+                AppEvent::SearchError(provider, query, e) => app.search_failed(provider, query, e),
+                AppEvent::PlaylistError(e) => {
                     app.is_loading = false;
-                    app.loading_status = Some(format!("Search Error: {}", e));
-                    app.input_mode = InputMode::Normal;
+                    app.push_error(format!("Playlist Error: {}", e));
+                    app.input_mode = app.input_mode.after_search(false);
+                },
+                AppEvent::YtDlpChecked(result) => {
+                    if let Err(e) = &result {
+                        app.push_error(e.clone());
+                    }
+                    app.yt_dlp_status = Some(result);
                 }
             }
         }
 
-        if event::poll(std::time::Duration::from_millis(16))? {
-            let event = event::read().map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Event error: {}", e)))?;
+        changed |= app.end_download_flash();
 
-            if app.current_tab == 4 {
-                app.oscilloscope.handle(event.clone());
+        // The scope and spinner need frames; anything else only changes on events or the clock
+        let animating = app.busy() || app.mic.is_some() || matches!(app.player.state(), PlaybackState::Playing);
+        let since_draw = last_draw.map_or(Duration::MAX, |at| at.elapsed());
+        if changed || since_draw >= IDLE_REDRAW || (animating && since_draw >= frame_interval) {
+            app.tick = (started.elapsed().as_millis() / TICK.as_millis()) as u64;
+            terminal.draw(|f| ui::layout::draw(f, app)).map_err(|e| io::Error::other(format!("Draw error: {}", e)))?;
+            last_draw = Some(Instant::now());
+            changed = false;
+        }
+
+        let timeout = if animating {
+            frame_interval.saturating_sub(last_draw.map_or(Duration::ZERO, |at| at.elapsed()))
+        } else {
+            IDLE_POLL
+        };
+        if event::poll(timeout)? {
+            let event = event::read().map_err(|e| io::Error::other(format!("Event error: {}", e)))?;
+            changed = true;
+
+            // The help overlay swallows everything until it's closed
+            if app.show_help {
+                if let Event::Key(key) = event {
+                    match key.code {
+                        KeyCode::Char('?' | 'q') | KeyCode::Esc => app.show_help = false,
+                        KeyCode::Tab => {
+                            app.help_log = !app.help_log;
+                            app.help_scroll = 0;
+                        }
+                        KeyCode::Down if app.help_log => app.help_scroll = app.help_scroll.saturating_add(1),
+                        KeyCode::Up if app.help_log => app.help_scroll = app.help_scroll.saturating_sub(1),
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            // So does the track info popup
+            if let Some(details) = &app.track_info {
+                if let Event::Key(key) = event {
+                    match key.code {
+                        KeyCode::Char('y' | 'Y') => {
+                            // OSC 52: the terminal puts it on the system clipboard, where it supports that
+                            let copied = execute!(io::stdout(), CopyToClipboard::to_clipboard_from(details.url.as_str()));
+                            match copied {
+                                Ok(()) => app.push_status("Copied URL to the clipboard"),
+                                Err(e) => app.push_error(format!("Couldn't copy: {}", e)),
+                            }
+                        },
+                        KeyCode::Char('i' | 'q') | KeyCode::Esc | KeyCode::Enter => app.track_info = None,
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            // Station keys on the RADIO tab win over the scope's own bindings, and typing isn't for the scope at all
+            let station_key = matches!(&event, Event::Key(k) if matches!(k.code, KeyCode::Char('a' | 'e' | 'd') | KeyCode::Enter));
+            if app.current_tab == Tab::Radio && matches!(app.input_mode, InputMode::Normal) && !station_key {
+                app.handle_scope_event(event.clone());
+            }
+
+            // Clicks only make sense where there's no prompt or question in the way
+            if let Event::Mouse(mouse) = event
+                && matches!(app.input_mode, InputMode::Normal | InputMode::SearchResults | InputMode::LibrarySearch | InputMode::FileBrowser | InputMode::FileFilter) {
+                app.handle_mouse(mouse);
             }
 
             if let Event::Key(key) = event {
+                // List movement is the same in every list
+                if let Some(region) = app.focused_list()
+                    && let Some(movement) = ListMove::for_key(&key, region, app.current_tab) {
+                    app.move_selection(region, movement);
+                    continue;
+                }
+
                 // Global Scope Controls
                 let magnitude = match key.modifiers {
                     KeyModifiers::SHIFT => 10.0,
@@ -102,112 +338,332 @@ where <B as Backend>::Error: 'static {
                 match app.input_mode {
                     InputMode::Normal => {
                         match key.code {
-                            KeyCode::Char('/') if app.current_tab == 2 => {
+                            KeyCode::Char('?') => app.show_help = true,
+                            KeyCode::Char('i') => app.open_track_info(),
+                            KeyCode::Char('/') if app.current_tab == Tab::Data => {
+                                app.search_history.reset();
                                 app.input_mode = InputMode::Editing;
                             }
-                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Char('/') if app.current_tab == Tab::Inv => app.start_library_search(),
+                            KeyCode::Esc if app.is_loading && app.requested_track.is_some() => {
+                                app.cancel_download();
+                                app.push_progress("Cancelling download...");
+                            }
+                            // INV tab: podcasts
+                            KeyCode::Char('a') if app.current_tab == Tab::Inv => {
+                                app.feed_input.clear();
+                                app.input_mode = InputMode::FeedUrl;
+                            }
+                            KeyCode::Enter if app.current_tab == Tab::Inv => app.podcast_enter(),
+                            KeyCode::Esc if app.current_tab == Tab::Inv => app.podcasts.close(),
+                            KeyCode::Char('d') if app.current_tab == Tab::Inv && !app.podcasts.is_open() => app.confirm(ConfirmAction::RemoveFeed),
+                            KeyCode::Char('r') if app.current_tab == Tab::Inv => app.refresh_feeds(),
+                            KeyCode::Char('x') if app.current_tab == Tab::Inv => app.start_export(),
+                            KeyCode::Char('b') if app.current_tab == Tab::Inv => app.start_backup_input(InputMode::BackupPath),
+                            KeyCode::Char('l') if app.current_tab == Tab::Inv => app.start_backup_input(InputMode::RestorePath),
+
+                            KeyCode::Char('q') if app.is_loading || matches!(app.player.state(), PlaybackState::Playing) => {
+                                app.confirm(ConfirmAction::Quit);
+                            }
+                            KeyCode::Char('q') => {
+                                app.save_session()?;
+                                return Ok(());
+                            }
 
-                            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == 4 => {
+                            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == Tab::Radio => {
                                 update_value_f(&mut app.graph_config.scale, 0.01, magnitude, 0.0..10.0);
                             }
-                            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == 4 => {
+                            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == Tab::Radio => {
                                 update_value_f(&mut app.graph_config.scale, -0.01, magnitude, 0.0..10.0);
                             }
-                            KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == 4 => {
+                            KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == Tab::Radio => {
                                 update_value_i(&mut app.graph_config.samples, true, 25, magnitude, 0..app.graph_config.width * 2);
                             }
-                            KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == 4 => {
+                            KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) && app.current_tab == Tab::Radio => {
                                 update_value_i(&mut app.graph_config.samples, false, 25, magnitude, 0..app.graph_config.width * 2);
                             }
-                            KeyCode::Char('s') if app.current_tab == 4 => app.graph_config.scatter = !app.graph_config.scatter,
-                            KeyCode::Char(' ') if app.current_tab == 4 => {
+                            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) && app.current_tab == Tab::Radio => app.save_scope_snapshot(),
+                            KeyCode::Char('s') if app.current_tab == Tab::Radio => app.graph_config.scatter = !app.graph_config.scatter,
+                            KeyCode::Char('v') if app.current_tab == Tab::Radio => app.cycle_scope_view(),
+                            KeyCode::Char('w') if app.current_tab == Tab::Radio => app.cycle_persistence(),
+                            KeyCode::Char('m') if app.current_tab == Tab::Radio => app.toggle_mic(),
+                            KeyCode::Char('M') if app.current_tab == Tab::Radio => app.toggle_mid_side(),
+                            KeyCode::Char('D') if app.current_tab == Tab::Radio => app.toggle_dc_block(),
+                            KeyCode::Char('b') if app.current_tab == Tab::Radio => app.toggle_braille(),
+                            KeyCode::Char('g') if app.current_tab == Tab::Radio => app.toggle_references(),
+                            KeyCode::Char('U') if app.current_tab == Tab::Radio => app.toggle_scope_labels(),
+                            KeyCode::Char('[') if app.current_tab == Tab::Radio => app.step_scope_gain(0, false),
+                            KeyCode::Char(']') if app.current_tab == Tab::Radio => app.step_scope_gain(0, true),
+                            KeyCode::Char('{') if app.current_tab == Tab::Radio => app.step_scope_gain(1, false),
+                            KeyCode::Char('}') if app.current_tab == Tab::Radio => app.step_scope_gain(1, true),
+                            KeyCode::Char(c) if app.current_tab == Tab::Radio
+                                && channel_key(c).is_some_and(|(_, shifted)| shifted || key.modifiers.contains(KeyModifiers::ALT)) => app.channel_key_pressed(c),
+                            KeyCode::Char('R') if app.current_tab == Tab::Radio => app.toggle_mic_recording(),
+                            KeyCode::Char(' ') if app.current_tab == Tab::Radio => {
                                 app.graph_config.pause = !app.graph_config.pause;
                                 app.player.toggle_pause();
                             },
+                            KeyCode::Char('x') if app.current_tab == Tab::Radio => app.player.stop(),
+                            KeyCode::Char('r') if app.current_tab == Tab::Radio => app.toggle_remaining_time(),
+                            KeyCode::Char('u') if app.current_tab == Tab::Radio => app.toggle_queue_focus(),
+                            KeyCode::Enter if app.current_tab == Tab::Radio && app.queue_focused => app.play_selected_upcoming(),
+                            KeyCode::Char('d') if app.current_tab == Tab::Radio && app.queue_focused => app.remove_selected_upcoming(),
+                            KeyCode::Enter if app.current_tab == Tab::Radio => app.play_selected_station(),
+                            KeyCode::Char('a') if app.current_tab == Tab::Radio => app.start_station_input(false),
+                            KeyCode::Char('e') if app.current_tab == Tab::Radio => app.start_station_input(true),
+                            KeyCode::Char('c') if app.current_tab == Tab::Radio => app.cycle_station_color(),
+                            KeyCode::Char('d') if app.current_tab == Tab::Radio => app.confirm(ConfirmAction::DeleteStation),
+                            KeyCode::Enter if app.current_tab == Tab::Map => app.browse_files(),
+                            KeyCode::Char('c') if app.current_tab == Tab::Stat => app.confirm(ConfirmAction::ClearCache),
+                            KeyCode::Char('v') if app.current_tab == Tab::Stat => app.check_library(),
+                            KeyCode::Char('b') if app.current_tab == Tab::Stat => app.cycle_quality(),
+                            KeyCode::Char('k') if app.current_tab == Tab::Stat => app.toggle_sponsorblock(),
+                            KeyCode::Char('t') if app.current_tab == Tab::Stat => app.cycle_theme(),
+                            KeyCode::Char('m') if app.current_tab == Tab::Stat => app.toggle_monochrome(),
+                            KeyCode::Char('h') if app.current_tab == Tab::Stat => app.history.toggle_focus(),
+                            KeyCode::Enter if app.current_tab == Tab::Stat => app.play_history_selection(),
+                            KeyCode::Char('.') => app.next_chapter(),
+                            KeyCode::Char(',') => app.previous_chapter(),
+                            KeyCode::Char('o') => app.toggle_offline(),
+                            KeyCode::Char('f') => app.toggle_favorite_selected(),
                             KeyCode::Char('+') => app.player.volume_up(),
                             KeyCode::Char('-') => app.player.volume_down(),
 
-                            KeyCode::Down if !key.modifiers.contains(KeyModifiers::SHIFT) => app.next_station(),
-                            KeyCode::Up if !key.modifiers.contains(KeyModifiers::SHIFT) => app.previous_station(),
+                            KeyCode::Char(c @ '1'..='5') => {
+                                if let Some(tab) = Tab::from_digit(c) {
+                                    app.switch_tab(tab);
+                                }
+                            },
                             KeyCode::Left if !key.modifiers.contains(KeyModifiers::SHIFT) => app.previous_tab(),
                             KeyCode::Right if !key.modifiers.contains(KeyModifiers::SHIFT) => app.next_tab(),
                             KeyCode::Tab => app.next_tab(),
                             _ => {}
                         }
                     },
+                    InputMode::Confirm => {
+                        match key.code {
+                            KeyCode::Char('y' | 'Y') | KeyCode::Enter => {
+                                app.input_mode = InputMode::Normal;
+                                match app.pending_confirm.take() {
+                                    Some(ConfirmAction::Quit) => {
+                                        app.save_session()?;
+                                        return Ok(());
+                                    }
+                                    Some(action) => app.run_confirmed(action),
+                                    None => {}
+                                }
+                            }
+                            KeyCode::Char('n' | 'N') | KeyCode::Esc => {
+                                app.input_mode = InputMode::Normal;
+                                app.pending_confirm = None;
+                            }
+                            _ => {}
+                        }
+                    },
                     InputMode::Editing => {
                         match key.code {
                             KeyCode::Enter => {
-                                let query = app.search_input.clone();
+                                let query = app.search_input.as_str().to_string();
+                                app.search_history.record(&query);
+                                if let Err(e) = app.save_session() {
+                                    app.push_error(e);
+                                }
 
-                                if query.starts_with("http://") || query.starts_with("https://") {
+                                let is_url = query.starts_with("http://") || query.starts_with("https://");
+
+                                if is_offline() && (!is_url || is_playlist_url(&query)) {
+                                    // Only single cached URLs can work without a network
+                                    app.push_status("Offline: search and playlist listing are disabled (O to go online)");
+
+                                } else if is_playlist_url(&query) && is_url {
+                                    // Playlist URL - list the entries so they can be picked one by one
+                                    app.push_progress("Listing playlist...");
+                                    app.is_loading = true;
+
+                                    let tx = app.event_tx.clone();
+                                    AudioPlayer::list_playlist_async(query, tx);
+
+                                    app.search_input.clear();
+
+                                } else if is_url {
                                     // Direct URL handling - Async
-                                    app.loading_status = Some(format!("Downloading URL: {}...", query));
+                                    app.push_progress(format!("Downloading URL: {}...", query));
                                     app.is_loading = true;
+                                    app.player.begin_loading();
 
                                     // Need to pass the sender to the static function.
                                     // app.player.load_source_async needs to be static or we clone sender
+                                    app.requested_track = Some(TrackInfo::new(query.clone(), query.clone()));
                                     let tx = app.event_tx.clone();
-                                    AudioPlayer::load_source_async(query, tx);
+                                    AudioPlayer::load_source_async(query, tx, app.new_download_token());
 
                                     app.search_input.clear();
-                                    app.reset_cursor();
                                     app.input_mode = InputMode::Normal;
 
                                 } else {
                                     // Search Query handling - Async
-                                    app.loading_status = Some(format!("Searching: {}...", query));
-                                    app.is_loading = true;
-
-                                    let tx = app.event_tx.clone();
-                                    AudioPlayer::search_async(query, tx);
+                                    let (prefixed, query) = Provider::from_query(&query);
+                                    let provider = prefixed.unwrap_or(app.search_provider);
+                                    // Ctrl+Enter (where the terminal reports it) skips the cache
+                                    let force = key.modifiers.contains(KeyModifiers::CONTROL);
+                                    app.start_search(query.to_string(), provider, force);
 
                                     app.search_input.clear();
-                                    app.reset_cursor();
                                 }
                             }
                             KeyCode::Esc => {
                                 app.input_mode = InputMode::Normal;
                             }
+                            KeyCode::Tab => {
+                                app.search_provider = app.search_provider.next();
+                            }
                             KeyCode::Backspace => {
-                                app.delete_char();
+                                app.search_input.backspace();
                             }
                             KeyCode::Left => {
-                                app.move_cursor_left();
+                                app.search_input.move_left();
                             }
                             KeyCode::Right => {
-                                app.move_cursor_right();
+                                app.search_input.move_right();
+                            }
+                            KeyCode::Up => {
+                                if let Some(query) = app.search_history.older(app.search_input.as_str()) {
+                                    app.search_input.set(query);
+                                }
+                            }
+                            KeyCode::Down => {
+                                if let Some(query) = app.search_history.newer() {
+                                    app.search_input.set(query);
+                                }
                             }
                             KeyCode::Char(to_insert) => {
-                                app.enter_char(to_insert);
+                                app.search_input.insert(to_insert);
                             }
                             _ => {}
                         }
                     },
+                    InputMode::FeedUrl | InputMode::ExportPath | InputMode::BackupPath | InputMode::RestorePath => {
+                        match key.code {
+                            KeyCode::Enter => {
+                                let input = app.prompt_input().as_str().trim().to_string();
+                                app.prompt_input().clear();
+                                match app.input_mode {
+                                    _ if input.is_empty() => {},
+                                    InputMode::FeedUrl => app.add_feed(input),
+                                    InputMode::BackupPath => app.backup_library(&input),
+                                    InputMode::RestorePath => app.restore_library(&input),
+                                    _ => app.export_m3u(&input),
+                                }
+                                app.input_mode = InputMode::Normal;
+                            }
+                            KeyCode::Esc => app.input_mode = InputMode::Normal,
+                            KeyCode::Backspace => app.prompt_input().backspace(),
+                            KeyCode::Left => app.prompt_input().move_left(),
+                            KeyCode::Right => app.prompt_input().move_right(),
+                            KeyCode::Char(to_insert) => app.prompt_input().insert(to_insert),
+                            _ => {}
+                        }
+                    },
+                    InputMode::StationName | InputMode::StationUrl => {
+                        match key.code {
+                            KeyCode::Enter => app.submit_station_input(),
+                            KeyCode::Esc => app.cancel_station_input(),
+                            KeyCode::Backspace => app.search_input.backspace(),
+                            KeyCode::Left => app.search_input.move_left(),
+                            KeyCode::Right => app.search_input.move_right(),
+                            KeyCode::Char(to_insert) => app.search_input.insert(to_insert),
+                            _ => {}
+                        }
+                    },
+                    InputMode::LibrarySearch => {
+                        match key.code {
+                            KeyCode::Enter => {
+                                app.play_library_hit();
+                                app.input_mode = InputMode::Normal;
+                            }
+                            KeyCode::Esc => {
+                                app.library_hits.clear();
+                                app.input_mode = InputMode::Normal;
+                            }
+                            KeyCode::Left => app.search_input.move_left(),
+                            KeyCode::Right => app.search_input.move_right(),
+                            KeyCode::Backspace => {
+                                app.search_input.backspace();
+                                app.library_query_changed();
+                            }
+                            KeyCode::Char(to_insert) => {
+                                app.search_input.insert(to_insert);
+                                app.library_query_changed();
+                            }
+                            _ => {}
+                        }
+                    },
+                    InputMode::FileBrowser => {
+                        match key.code {
+                            KeyCode::Enter => app.file_browser_enter(),
+                            KeyCode::Backspace => app.file_browser.open_parent(app.event_tx.clone()),
+                            KeyCode::Char('a') => app.queue_selected_file(),
+                            KeyCode::Char(' ') => {
+                                app.graph_config.pause = !app.graph_config.pause;
+                                app.player.toggle_pause();
+                            },
+                            KeyCode::Char('/') => {
+                                app.file_browser.start_filter();
+                                app.input_mode = InputMode::FileFilter;
+                            },
+                            // The first Esc drops the filter, the next one stops browsing
+                            KeyCode::Esc if app.file_browser.filter.is_some() => app.file_browser.clear_filter(),
+                            KeyCode::Esc => app.input_mode = InputMode::Normal,
+                            KeyCode::Left => app.previous_tab(),
+                            KeyCode::Right => app.next_tab(),
+                            KeyCode::Char(c @ '1'..='5') => {
+                                if let Some(tab) = Tab::from_digit(c) {
+                                    app.switch_tab(tab);
+                                }
+                            },
+                            _ => {}
+                        }
+                    },
+                    InputMode::FileFilter => {
+                        // Typing narrows the listing; the matches stay up after Enter until Esc
+                        let filter = app.file_browser.filter.as_mut().map(|f| &mut f.input);
+                        match (key.code, filter) {
+                            (KeyCode::Enter, _) => {
+                                app.input_mode = InputMode::FileBrowser;
+                                app.file_browser_enter();
+                            },
+                            (KeyCode::Esc, _) => {
+                                app.file_browser.clear_filter();
+                                app.input_mode = InputMode::FileBrowser;
+                            },
+                            (KeyCode::Left, Some(input)) => input.move_left(),
+                            (KeyCode::Right, Some(input)) => input.move_right(),
+                            (KeyCode::Backspace, Some(input)) => {
+                                input.backspace();
+                                app.file_browser.filter_changed();
+                            },
+                            (KeyCode::Char(c), Some(input)) => {
+                                input.insert(c);
+                                app.file_browser.filter_changed();
+                            },
+                            _ => {}
+                        }
+                    },
                     InputMode::SearchResults => {
                         match key.code {
-                            KeyCode::Down => app.next_search_result(),
-                            KeyCode::Up => app.previous_search_result(),
                             KeyCode::Esc => {
                                 app.input_mode = InputMode::Normal;
                                 app.search_results.clear();
+                                app.all_results.clear();
                             },
-                            KeyCode::Enter => {
-                                let selected_track = if let Some(selected_idx) = app.search_results_state.selected() {
-                                    app.search_results.get(selected_idx).cloned()
-                                } else {
-                                    None
-                                };
-
-                                if let Some((title, url)) = selected_track {
-                                    app.loading_status = Some(format!("Downloading: {}...", title));
-                                    app.is_loading = true;
-
-                                    let tx = app.event_tx.clone();
-                                    AudioPlayer::load_source_async(url, tx);
-
-                                    app.input_mode = InputMode::Normal;
-                                }
+                            KeyCode::Char('d') => app.sort_results(ResultSort::Duration),
+                            KeyCode::Char('t') => app.sort_results(ResultSort::Title),
+                            KeyCode::Char('i') => app.open_track_info(),
+                            KeyCode::Char('h') => app.toggle_short_results(),
+                            KeyCode::Char('f') => app.toggle_favorite_result(),
+                            KeyCode::Enter | KeyCode::Char('s') if app.search_results_state.selected().is_some() => {
+                                app.play_selected_result(key.code == KeyCode::Char('s'));
+                                app.input_mode = InputMode::Normal;
                             },
                             _ => {}
                         }
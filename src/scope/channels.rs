@@ -0,0 +1,129 @@
+use std::borrow::Cow;
+
+use super::Matrix;
+
+// What Shift makes of the number row on a US layout, 1 through 9. Terminals without the
+// kitty keyboard protocol send these rather than Shift+digit.
+const SHIFTED_DIGITS: &str = "!@#$%^&*(";
+
+/// Which channels the scope shows: one soloed, and any muted. Silenced channels are zeroed
+/// between get_window and the display modes, so each keeps its place and color.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ChannelFilter {
+	pub solo: Option<usize>,
+	muted: u32, // Bit per channel
+}
+
+/// What a channel is called on the scope: L and R, or MID and SIDE after `mid_side`
+pub fn channel_label(index: usize, mid_side: bool) -> Cow<'static, str> {
+	match (index, mid_side) {
+		(0, false) => "L".into(),
+		(1, false) => "R".into(),
+		(0, true) => "MID".into(),
+		(1, true) => "SIDE".into(),
+		_ => format!("{}", index).into(),
+	}
+}
+
+/// Stereo as mid (L+R)/2 and side (L-R)/2 in place of left and right. Anything but two channels is left alone.
+pub fn mid_side(data: &mut Matrix<f64>) {
+	if let [left, right] = &mut data[..] {
+		for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+			(*l, *r) = ((*l + *r) / 2.0, (*l - *r) / 2.0);
+		}
+	}
+}
+
+/// The channel (0-based) for a digit or a shifted digit, and whether it was shifted
+pub fn channel_key(c: char) -> Option<(usize, bool)> {
+	match c {
+		'1'..='9' => Some((c as usize - '1' as usize, false)),
+		_ => SHIFTED_DIGITS.find(c).map(|i| (i, true)),
+	}
+}
+
+impl ChannelFilter {
+	/// Solo `channel`, or un-solo it if it already is; nothing for a channel there isn't
+	pub fn toggle_solo(&mut self, channel: usize, channels: usize) {
+		if channel < channels {
+			self.solo = if self.solo == Some(channel) { None } else { Some(channel) };
+		}
+	}
+
+	pub fn toggle_mute(&mut self, channel: usize, channels: usize) {
+		if channel < channels && channel < 32 {
+			self.muted ^= 1 << channel;
+		}
+	}
+
+	pub fn is_muted(&self, channel: usize) -> bool {
+		channel < 32 && self.muted & (1 << channel) != 0
+	}
+
+	pub fn is_silenced(&self, channel: usize) -> bool {
+		self.solo.is_some_and(|solo| solo != channel) || self.is_muted(channel)
+	}
+
+	pub fn apply(&self, data: &mut Matrix<f64>) {
+		for (n, channel) in data.iter_mut().enumerate() {
+			if self.is_silenced(n) {
+				channel.fill(0.0);
+			}
+		}
+	}
+
+	/// "SOLO L  MUTE R" style, using `name` for each channel; None when everything's heard
+	pub fn describe<S: std::fmt::Display>(&self, name: impl Fn(usize) -> S) -> Option<String> {
+		let mut parts = Vec::new();
+		if let Some(solo) = self.solo {
+			parts.push(format!("SOLO {}", name(solo)));
+		}
+		let muted: Vec<String> = (0..32).filter(|n| self.is_muted(*n)).map(|n| name(n).to_string()).collect();
+		if !muted.is_empty() {
+			parts.push(format!("MUTE {}", muted.join(" ")));
+		}
+		(!parts.is_empty()).then(|| parts.join("  "))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn solo_and_mute_zero_the_others() {
+		let mut filter = ChannelFilter::default();
+		filter.toggle_solo(5, 2);
+		assert_eq!(filter.solo, None);
+		filter.toggle_solo(1, 2);
+		let mut data = vec![vec![0.5; 2], vec![0.25; 2]];
+		filter.apply(&mut data);
+		assert_eq!(data, [vec![0.0; 2], vec![0.25; 2]]);
+
+		filter.toggle_solo(1, 2);
+		filter.toggle_mute(0, 2);
+		let mut data = vec![vec![0.5; 2], vec![0.25; 2]];
+		filter.apply(&mut data);
+		assert_eq!(data, [vec![0.0; 2], vec![0.25; 2]]);
+		assert_eq!(filter.describe(|n| n.to_string()), Some("MUTE 0".to_string()));
+		filter.toggle_mute(0, 2);
+		assert_eq!(filter.describe(|n| n.to_string()), None);
+	}
+
+	#[test]
+	fn mid_side_splits_what_is_shared_from_what_differs() {
+		let mut data = vec![vec![0.5, 1.0, 0.5], vec![0.5, -1.0, 0.0]];
+		mid_side(&mut data);
+		assert_eq!(data, [vec![0.5, 0.0, 0.25], vec![0.0, 1.0, 0.25]]);
+		let mut mono = vec![vec![0.5]];
+		mid_side(&mut mono);
+		assert_eq!(mono, [vec![0.5]]);
+	}
+
+	#[test]
+	fn shifted_digits_map_to_their_channel() {
+		assert_eq!(channel_key('1'), Some((0, false)));
+		assert_eq!(channel_key('@'), Some((1, true)));
+		assert_eq!(channel_key('0'), None);
+	}
+}
@@ -1,4 +1,11 @@
 pub mod oscilloscope;
+pub mod peak_hold;
+pub mod persistence;
+pub mod spectrogram;
+pub mod spectroscope;
+pub mod vectorscope;
+
+use std::borrow::Cow;
 
 use crossterm::event::Event;
 use ratatui::{
@@ -18,11 +25,14 @@ pub enum Dimension {
 pub struct GraphConfig {
 	pub pause: bool,
 	pub samples: u32,
-	#[allow(dead_code)]
-	pub sampling_rate: u32,  // Se mantiene porque es relevante para la visualización
+	pub sampling_rate: u32, // Of what's on the scope, kept in step with the player by App::sync_sample_rate
 	pub scale: f64,
 	pub width: u32,
+	pub columns: u32, // Points the chart has across; traces with over two samples a point are decimated, 0 never
 	pub scatter: bool,
+	pub persistence: u32, // Frames of fading trail behind the trace, 0 for none
+	pub mid_side: bool,   // Stereo is shown as mid and side rather than left and right
+	pub references: bool, // Zero lines under the trace
 	pub show_ui: bool,
 	pub marker_type: Marker,
 	pub palette: Vec<Color>,
@@ -42,18 +52,19 @@ impl GraphConfig {
 #[allow(clippy::ptr_arg)] // TODO temporarily! it's a shitty solution
 pub trait DisplayMode {
 	// MUST define
-	fn axis(&self, cfg: &GraphConfig, dimension: Dimension) -> Axis<'_>; // TODO simplify this
-	fn process(&mut self, cfg: &GraphConfig, data: &Matrix<f64>) -> Vec<DataSet>;
+	fn axis(&self, cfg: &GraphConfig, dimension: Dimension) -> Axis<'static>; // TODO simplify this
+	/// This frame's datasets, built in buffers the mode keeps so a steady frame doesn't allocate
+	fn process(&mut self, cfg: &GraphConfig, data: &Matrix<f64>) -> &[DataSet];
 
 	// SHOULD override
-	fn channel_name(&self, index: usize) -> String {
-		format!("{}", index)
+	fn channel_name(&self, index: usize) -> Cow<'static, str> {
+		format!("{}", index).into()
 	}
 	fn handle(&mut self, _event: Event) {}
 }
 
 pub struct DataSet {
-	name: Option<String>,
+	name: Option<Cow<'static, str>>,
 	data: Vec<(f64, f64)>,
 	marker_type: Marker,
 	graph_type: GraphType,
@@ -64,7 +75,7 @@ impl<'a> From<&'a DataSet> for Dataset<'a> {
 	fn from(ds: &'a DataSet) -> Dataset<'a> {
 		let mut out = Dataset::default(); // TODO creating a binding is kinda ugly, is it avoidable?
 		if let Some(name) = &ds.name {
-			out = out.name(name.clone());
+			out = out.name(name.as_ref());
 		}
 		out.marker(ds.marker_type)
 			.graph_type(ds.graph_type)
@@ -83,13 +94,83 @@ impl DataSet {
 		color: Color,
 	) -> Self {
 		DataSet {
-			name,
+			name: name.map(Cow::Owned),
 			data,
 			marker_type,
 			graph_type,
 			color,
 		}
 	}
+
+	pub fn color(&self) -> Color {
+		self.color
+	}
+}
+
+/// Datasets kept from one frame to the next, so each frame refills the last one's buffers
+#[derive(Default)]
+pub struct DataSets {
+	sets: Vec<DataSet>,
+	len: usize, // How many of `sets` this frame has filled
+}
+
+impl DataSets {
+	pub fn clear(&mut self) {
+		self.len = 0;
+	}
+
+	/// The next dataset of this frame, emptied, for its points to go in
+	pub fn next(
+		&mut self,
+		name: Option<Cow<'static, str>>,
+		marker_type: Marker,
+		graph_type: GraphType,
+		color: Color,
+	) -> &mut Vec<(f64, f64)> {
+		if self.len == self.sets.len() {
+			self.sets.push(DataSet::new(None, Vec::new(), marker_type, graph_type, color));
+		}
+		let set = &mut self.sets[self.len];
+		self.len += 1;
+		set.name = name;
+		set.marker_type = marker_type;
+		set.graph_type = graph_type;
+		set.color = color;
+		set.data.clear();
+		&mut set.data
+	}
+
+	pub fn sets(&self) -> &[DataSet] {
+		&self.sets[..self.len]
+	}
+}
+
+/// Samples as chart points into `out`, x being the index. Past two samples a column each column
+/// keeps only its lowest and highest, in the order they came, so spikes survive where striding
+/// would skip them.
+pub fn decimate(samples: &[f64], columns: usize, out: &mut Vec<(f64, f64)>) {
+	out.clear();
+	if columns == 0 || samples.len() <= columns * 2 {
+		out.extend(samples.iter().enumerate().map(|(i, s)| (i as f64, *s)));
+		return;
+	}
+	for column in 0..columns {
+		let start = column * samples.len() / columns;
+		let end = (column + 1) * samples.len() / columns;
+		let (mut low, mut high) = (start, start);
+		for (i, sample) in samples[start..end].iter().enumerate() {
+			if *sample < samples[low] {
+				low = start + i;
+			}
+			if *sample > samples[high] {
+				high = start + i;
+			}
+		}
+		out.push((low.min(high) as f64, samples[low.min(high)]));
+		if low != high {
+			out.push((low.max(high) as f64, samples[low.max(high)]));
+		}
+	}
 }
 
 pub fn update_value_f(
@@ -1,3 +1,6 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
 use crossterm::event::{Event, KeyCode, KeyModifiers};
 use ratatui::{
 	style::Style,
@@ -5,16 +8,27 @@ use ratatui::{
 	widgets::{Axis, GraphType},
 };
 
+use crate::scope::channels::channel_label;
 use crate::scope::Matrix;
+use crate::scope::style::dim_color;
+
+use super::peak_hold::PeakHold;
+use super::{decimate, update_value_f, update_value_i, DataSet, DataSets, Dimension, DisplayMode, GraphConfig};
 
-use super::{update_value_f, update_value_i, DataSet, Dimension, DisplayMode, GraphConfig};
+// Samples run -1.0..1.0, so this is 2.5% of full scale
+const THRESHOLD_STEP: f64 = 0.025;
+// How fast a held peak comes down once its hold is over, in full scales a second
+const PEAK_FALL_PER_SEC: f64 = 0.5;
 
 pub struct Oscilloscope {
 	pub triggering: bool,
 	pub falling_edge: bool,
 	pub threshold: f64,
 	pub depth: u32,
-	pub peaks: bool,
+	pub peaks: bool,    // Lines at each channel's held peak, above and below zero
+	pub hold: PeakHold, // One slot per channel
+	loudest: Vec<f64>,  // This frame's peak per channel, for `hold`
+	sets: DataSets,
 }
 
 impl Default for Oscilloscope {
@@ -24,16 +38,44 @@ impl Default for Oscilloscope {
 			falling_edge: false,
 			threshold: 0.0,
 			depth: 0,
-			peaks: true,
+			peaks: false,
+			hold: PeakHold::new(Duration::from_secs(2), PEAK_FALL_PER_SEC, 0.0),
+			loudest: Vec::new(),
+			sets: DataSets::default(),
 		}
 	}
 }
 
+impl Oscilloscope {
+	/// How many samples `process` wants to draw `samples` of them: twice as many while triggering,
+	/// leaving room to wait for a crossing
+	pub fn window_len(&self, samples: u32) -> usize {
+		samples as usize * if self.triggering { 2 } else { 1 }
+	}
+
+	/// Where in `data` the trace starts. The window holds extra samples while triggering (see
+	/// `window_len`) so the trace can start at the first crossing and still fill the screen.
+	/// Without a crossing it runs free from the start.
+	pub fn trigger_offset(&self, data: &Matrix<f64>) -> usize {
+		match data.first().filter(|_| self.triggering) {
+			Some(first) => (0..first.len())
+				.find(|i| triggered(first, *i, self.threshold, self.depth.max(1), self.falling_edge))
+				.unwrap_or(0),
+			None => 0,
+		}
+	}
+
+	/// "↑ +0.25" style: the edge it fires on and the threshold
+	pub fn trigger_label(&self) -> String {
+		format!("{} {:+.2}", if self.falling_edge { "↓" } else { "↑" }, self.threshold)
+	}
+}
+
 impl DisplayMode for Oscilloscope {
-	fn axis(&self, cfg: &GraphConfig, dimension: Dimension) -> Axis<'_> {
+	fn axis(&self, cfg: &GraphConfig, dimension: Dimension) -> Axis<'static> {
 		let (name, bounds) = match dimension {
-			Dimension::X => ("time -", [0.0, cfg.samples as f64]),
-			Dimension::Y => ("| amplitude", [-cfg.scale, cfg.scale]),
+			Dimension::X => (time_title(cfg), [0.0, cfg.samples as f64]),
+			Dimension::Y => ("| amplitude".to_string(), [-cfg.scale, cfg.scale]),
 		};
 		let mut a = Axis::default();
 		if cfg.show_ui {
@@ -43,80 +85,54 @@ impl DisplayMode for Oscilloscope {
 		a.style(Style::default().fg(cfg.axis_color)).bounds(bounds)
 	}
 
-	fn process(&mut self, cfg: &GraphConfig, data: &Matrix<f64>) -> Vec<DataSet> {
-		let mut out = Vec::new();
+	fn process(&mut self, cfg: &GraphConfig, data: &Matrix<f64>) -> &[DataSet] {
+		self.sets.clear();
 
-		let mut trigger_offset = 0;
 		if self.depth == 0 {
 			self.depth = 1
 		}
-		if self.triggering {
-			for i in 0..data[0].len() {
-				if triggered(&data[0], i, self.threshold, self.depth, self.falling_edge) {
-					// triggered
-					break;
-				}
-				trigger_offset += 1;
-			}
+		let trigger_offset = self.trigger_offset(data);
+		let width = cfg.samples as f64;
+
+		if cfg.references {
+			self.sets.next(None, cfg.marker_type, GraphType::Line, cfg.axis_color).extend([(0.0, 0.0), (width, 0.0)]);
 		}
 
 		if self.triggering {
-			out.push(DataSet::new(
-				Some("T".into()),
-				vec![(0.0, self.threshold)],
-				cfg.marker_type,
-				GraphType::Scatter,
-				cfg.labels_color,
-			));
+			self.sets
+				.next(Some("T".into()), cfg.marker_type, GraphType::Line, cfg.labels_color)
+				.extend([(0.0, self.threshold), (width, self.threshold)]);
+		}
+
+		let drawn = trigger_offset..trigger_offset + cfg.samples as usize;
+		if self.peaks {
+			self.loudest.clear();
+			self.loudest.extend(
+				data.iter().map(|channel| channel.iter().take(drawn.end).skip(drawn.start).fold(0.0, |m, s| s.abs().max(m))),
+			);
+			self.hold.update(&self.loudest, Instant::now());
 		}
 
 		for (n, channel) in data.iter().enumerate().rev() {
-			let (mut min, mut max) = (0.0, 0.0);
-			let mut tmp = Vec::new();
-			for (i, sample) in channel.iter().enumerate() {
-				if *sample < min {
-					min = *sample
-				};
-				if *sample > max {
-					max = *sample
-				};
-				if i >= trigger_offset {
-					tmp.push(((i - trigger_offset) as f64, *sample));
+			if let Some(peak) = self.hold.held().get(n).copied().filter(|_| self.peaks) {
+				for level in [peak, -peak] {
+					self.sets
+						.next(None, cfg.marker_type, GraphType::Line, dim_color(cfg.palette(n), 0.5))
+						.extend([(0.0, level), (width, level)]);
 				}
 			}
 
-			if self.peaks {
-				out.push(DataSet::new(
-					None,
-					vec![(0.0, min), (0.0, max)],
-					cfg.marker_type,
-					GraphType::Scatter,
-					cfg.palette(n),
-				))
-			}
-
-			out.push(DataSet::new(
-				Some(self.channel_name(n)),
-				tmp,
-				cfg.marker_type,
-				if cfg.scatter {
-					GraphType::Scatter
-				} else {
-					GraphType::Line
-				},
-				cfg.palette(n),
-			));
+			let name = if cfg.mid_side { channel_label(n, true) } else { self.channel_name(n) };
+			let graph_type = if cfg.scatter { GraphType::Scatter } else { GraphType::Line };
+			let points = self.sets.next(Some(name), cfg.marker_type, graph_type, cfg.palette(n));
+			decimate(&channel[drawn.start.min(channel.len())..drawn.end.min(channel.len())], cfg.columns as usize, points);
 		}
 
-		out
+		self.sets.sets()
 	}
 
-	fn channel_name(&self, index: usize) -> String {
-		match index {
-			0 => "L".into(),
-			1 => "R".into(),
-			_ => format!("{}", index),
-		}
+	fn channel_name(&self, index: usize) -> Cow<'static, str> {
+		channel_label(index, false)
 	}
 
 	fn handle(&mut self, event: Event) {
@@ -129,14 +145,24 @@ impl DisplayMode for Oscilloscope {
 			};
 			match key.code {
 				KeyCode::PageUp => {
-					update_value_f(&mut self.threshold, 250.0, magnitude, 0.0..32768.0)
+					update_value_f(&mut self.threshold, THRESHOLD_STEP, magnitude, -1.0..1.0)
 				}
 				KeyCode::PageDown => {
-					update_value_f(&mut self.threshold, -250.0, magnitude, 0.0..32768.0)
+					update_value_f(&mut self.threshold, -THRESHOLD_STEP, magnitude, -1.0..1.0)
+				}
+				KeyCode::Up if key.modifiers == KeyModifiers::ALT => {
+					update_value_f(&mut self.threshold, THRESHOLD_STEP, 1.0, -1.0..1.0)
+				}
+				KeyCode::Down if key.modifiers == KeyModifiers::ALT => {
+					update_value_f(&mut self.threshold, -THRESHOLD_STEP, 1.0, -1.0..1.0)
 				}
 				KeyCode::Char('t') => self.triggering = !self.triggering,
-				KeyCode::Char('e') => self.falling_edge = !self.falling_edge,
-				KeyCode::Char('p') => self.peaks = !self.peaks,
+				// Lowercase e edits the station on RADIO
+				KeyCode::Char('E') => self.falling_edge = !self.falling_edge,
+				KeyCode::Char('p') => {
+					self.peaks = !self.peaks;
+					self.hold.reset();
+				}
 				KeyCode::Char('=') => update_value_i(&mut self.depth, true, 1, 1.0, 1..65535),
 				KeyCode::Char('-') => update_value_i(&mut self.depth, false, 1, 1.0, 1..65535),
 				KeyCode::Char('+') => update_value_i(&mut self.depth, true, 10, 1.0, 1..65535),
@@ -179,3 +205,89 @@ fn triggered(data: &[f64], index: usize, threshold: f64, depth: u32, falling_edg
 		}
 	}
 }
+
+/// "time - 4.5 ms": how long the trace spans at the current sample rate
+fn time_title(cfg: &GraphConfig) -> String {
+	match cfg.sampling_rate {
+		0 => "time -".to_string(),
+		rate => format!("time - {:.1} ms", cfg.samples as f64 * 1000.0 / rate as f64),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn triggering_starts_the_trace_at_the_crossing() {
+		let cfg = GraphConfig { samples: 4, palette: vec![ratatui::style::Color::White], ..Default::default() };
+		let mut scope = Oscilloscope { triggering: true, ..Default::default() };
+		let data = vec![vec![-0.5, -0.5, 0.5, 0.5, -0.5, -0.5, 0.5, 0.5]];
+		let sets = scope.process(&cfg, &data);
+		assert_eq!(sets[0].data, [(0.0, 0.0), (4.0, 0.0)]);
+		assert_eq!(sets[1].data, [(0.0, -0.5), (1.0, 0.5), (2.0, 0.5), (3.0, -0.5)]);
+
+		// Nothing crosses a threshold above the signal, so it runs free
+		scope.threshold = 0.75;
+		let sets = scope.process(&cfg, &data);
+		assert_eq!(sets[1].data[0], (0.0, -0.5));
+		assert_eq!(sets[1].data.len(), 4);
+	}
+
+	#[test]
+	fn peak_hold_draws_lines_at_the_loudest_drawn_sample() {
+		let cfg = GraphConfig { samples: 3, palette: vec![ratatui::style::Color::White], ..Default::default() };
+		let mut scope = Oscilloscope { peaks: true, ..Default::default() };
+		// The last sample is past what's drawn
+		let sets = scope.process(&cfg, &vec![vec![0.25, -0.5, 0.1, 0.9]]);
+		assert_eq!(sets[0].data, [(0.0, 0.5), (3.0, 0.5)]);
+		assert_eq!(sets[1].data, [(0.0, -0.5), (3.0, -0.5)]);
+		assert_eq!(sets[2].data.len(), 3);
+	}
+
+	#[test]
+	fn time_axis_follows_the_sample_rate() {
+		let cfg = GraphConfig { samples: 441, sampling_rate: 44_100, ..Default::default() };
+		assert_eq!(time_title(&cfg), "time - 10.0 ms");
+		assert_eq!(time_title(&GraphConfig { sampling_rate: 22_050, ..cfg }), "time - 20.0 ms");
+	}
+
+	#[test]
+	fn reference_line_sits_under_the_trace_at_zero() {
+		let cfg = GraphConfig { samples: 3, references: true, palette: vec![ratatui::style::Color::White], ..Default::default() };
+		let mut scope = Oscilloscope::default();
+		let sets = scope.process(&cfg, &vec![vec![0.1, 0.2, 0.3]]);
+		assert_eq!(sets[0].data, [(0.0, 0.0), (3.0, 0.0)]);
+		assert_eq!(sets[1].data.len(), 3);
+	}
+
+	#[test]
+	fn decimation_keeps_spikes() {
+		let mut samples = vec![0.0; 1000];
+		samples[437] = 0.9;
+		samples[438] = -0.8;
+		let mut points = Vec::new();
+		decimate(&samples, 10, &mut points);
+		assert!(points.len() <= 20);
+		assert!(points.contains(&(437.0, 0.9)) && points.contains(&(438.0, -0.8)));
+		assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+		// Two samples a column or fewer are drawn as they are
+		decimate(&samples[..20], 10, &mut points);
+		assert_eq!(points.len(), 20);
+	}
+
+	#[test]
+	fn points_drawn_stay_flat_as_the_window_grows() {
+		let mut scope = Oscilloscope::default();
+		// Points in the trace for a window of `samples` across 160 columns, as the RADIO tab draws it
+		let mut points = |samples: u32| {
+			let cfg = GraphConfig { samples, columns: 160, scale: 1.0, palette: vec![ratatui::style::Color::White], ..Default::default() };
+			let data = vec![(0..samples).map(|i| (i as f64 * 0.05).sin()).collect::<Vec<f64>>()];
+			scope.process(&cfg, &data)[0].data.len()
+		};
+		let small = points(2_000);
+		assert!(small <= 320, "{}", small);
+		// A hundred times the samples is no more to draw; the time it takes is in benches/scope.rs
+		assert_eq!(points(200_000), small);
+	}
+}
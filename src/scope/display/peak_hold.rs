@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+/// The highest value seen in each slot (a channel or a band), held for a while and then let
+/// down at a steady rate so old peaks fade out
+pub struct PeakHold {
+	pub hold: Duration,
+	fall_per_sec: f64,
+	floor: f64,
+	peaks: Vec<(f64, Instant)>, // The peak and when it was hit
+	held: Vec<f64>,
+}
+
+impl PeakHold {
+	/// Peaks stay put for `hold`, then drop `fall_per_sec` a second until they reach `floor`
+	pub fn new(hold: Duration, fall_per_sec: f64, floor: f64) -> Self {
+		PeakHold { hold, fall_per_sec, floor, peaks: Vec::new(), held: Vec::new() }
+	}
+
+	/// Take in this frame's values, one per slot, and give back what's held for each
+	pub fn update(&mut self, values: &[f64], now: Instant) -> &[f64] {
+		if self.peaks.len() != values.len() {
+			self.peaks = vec![(self.floor, now); values.len()];
+		}
+		self.held.clear();
+		for (peak, value) in self.peaks.iter_mut().zip(values) {
+			let since = now.saturating_duration_since(peak.1).saturating_sub(self.hold);
+			let held = (peak.0 - self.fall_per_sec * since.as_secs_f64()).max(self.floor);
+			if *value >= held {
+				*peak = (*value, now);
+			}
+			self.held.push(held.max(*value));
+		}
+		&self.held
+	}
+
+	pub fn held(&self) -> &[f64] {
+		&self.held
+	}
+
+	/// Forget every peak, for a new track
+	pub fn reset(&mut self) {
+		self.peaks.clear();
+		self.held.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn holds_then_falls_linearly() {
+		let start = Instant::now();
+		let mut hold = PeakHold::new(Duration::from_secs(2), 0.5, 0.0);
+		assert_eq!(hold.update(&[0.8, 0.1], start), [0.8, 0.1]);
+		assert_eq!(hold.update(&[0.2, 0.3], start + Duration::from_secs(1)), [0.8, 0.3]);
+		// A second past the hold, half a unit lower; never under the floor
+		let later = hold.update(&[0.0, 0.0], start + Duration::from_secs(3)).to_vec();
+		assert!((later[0] - 0.3).abs() < 1e-9, "{:?}", later);
+		assert_eq!(hold.update(&[0.0, 0.0], start + Duration::from_secs(10))[0], 0.0);
+	}
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use super::DataSet;
+
+// Longest trail, in frames
+pub const MAX_PERSISTENCE: u32 = 8;
+
+/// The last few frames' datasets, for phosphor-style trails behind the live trace.
+/// The oldest frame's buffers are reused for the newest, so a full ring stops allocating.
+#[derive(Default)]
+pub struct Persistence {
+	frames: VecDeque<Vec<DataSet>>, // Oldest first
+}
+
+impl Persistence {
+	/// Remember `frame`, forgetting all but the last `keep`
+	pub fn push(&mut self, frame: &[DataSet], keep: usize) {
+		while self.frames.len() > keep {
+			self.frames.pop_front();
+		}
+		if keep == 0 {
+			return;
+		}
+		let mut slot = if self.frames.len() == keep { self.frames.pop_front().unwrap_or_default() } else { Vec::new() };
+		slot.truncate(frame.len());
+		for (i, set) in frame.iter().enumerate() {
+			match slot.get_mut(i) {
+				Some(old) => {
+					old.data.clear();
+					old.data.extend_from_slice(&set.data);
+					old.marker_type = set.marker_type;
+					old.graph_type = set.graph_type;
+					old.color = set.color;
+				}
+				// Trails go unnamed so they stay out of the legend
+				None => slot.push(DataSet::new(None, set.data.clone(), set.marker_type, set.graph_type, set.color)),
+			}
+		}
+		self.frames.push_back(slot);
+	}
+
+	/// Every remembered dataset with how bright its trail should be, oldest and dimmest first.
+	/// Marks of a point or two (held peaks, the trigger level) are left out: they'd just smear.
+	pub fn trails(&self) -> impl Iterator<Item = (&DataSet, f64)> {
+		let count = self.frames.len();
+		self.frames.iter().enumerate().flat_map(move |(age, frame)| {
+			let brightness = (age + 1) as f64 / (count + 1) as f64;
+			frame
+				.iter()
+				.filter(|set| set.data.len() > 2)
+				.map(move |set| (set, brightness))
+		})
+	}
+
+	pub fn clear(&mut self) {
+		self.frames.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ratatui::{style::Color, symbols::Marker, widgets::GraphType};
+
+	fn frame(value: f64) -> Vec<DataSet> {
+		vec![DataSet::new(Some("L".into()), vec![(0.0, value), (1.0, value), (2.0, value)], Marker::Braille, GraphType::Line, Color::Green)]
+	}
+
+	#[test]
+	fn keeps_the_last_frames_oldest_dimmest() {
+		let mut persistence = Persistence::default();
+		for value in [1.0, 2.0, 3.0] {
+			persistence.push(&frame(value), 2);
+		}
+		let trails: Vec<(f64, f64)> = persistence.trails().map(|(set, brightness)| (set.data[0].1, brightness)).collect();
+		assert_eq!(trails, [(2.0, 1.0 / 3.0), (3.0, 2.0 / 3.0)]);
+		assert!(persistence.trails().all(|(set, _)| set.name.is_none()));
+
+		persistence.push(&frame(4.0), 0);
+		assert_eq!(persistence.trails().count(), 0);
+	}
+}
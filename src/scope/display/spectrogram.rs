@@ -0,0 +1,51 @@
+use std::collections::VecDeque;
+
+/// The last few windows' band levels, oldest first, for the scrolling waterfall.
+/// It holds as many lines as the panel shows and no more.
+#[derive(Default)]
+pub struct Spectrogram {
+	lines: VecDeque<Vec<f64>>,
+}
+
+impl Spectrogram {
+	/// Add the newest line, keeping at most `height` lines. When the band count changed with the
+	/// panel's width, the older lines are stretched to match rather than thrown away.
+	pub fn push(&mut self, levels: &[f64], height: usize) {
+		let bands = levels.len();
+		if self.lines.front().is_some_and(|line| line.len() != bands) {
+			for line in self.lines.iter_mut() {
+				*line = (0..bands).map(|i| line[i * line.len() / bands]).collect();
+			}
+		}
+		// The line scrolling off the top is reused for the new one
+		let mut line = if self.lines.len() >= height { self.lines.pop_front().unwrap_or_default() } else { Vec::new() };
+		line.clear();
+		line.extend_from_slice(levels);
+		self.lines.push_back(line);
+		while self.lines.len() > height {
+			self.lines.pop_front();
+		}
+	}
+
+	pub fn lines(&self) -> &VecDeque<Vec<f64>> {
+		&self.lines
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn keeps_the_newest_lines_and_follows_resizes() {
+		let mut history = Spectrogram::default();
+		for i in 0..5 {
+			history.push(&[i as f64; 4], 3);
+		}
+		assert_eq!(history.lines().len(), 3);
+		assert_eq!(history.lines()[0], [2.0; 4]);
+
+		history.push(&[9.0; 2], 2);
+		assert_eq!(history.lines(), &[vec![4.0; 2], vec![9.0; 2]]);
+	}
+}
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode};
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+use crate::scope::Matrix;
+
+use super::peak_hold::PeakHold;
+
+// Samples per FFT; ~46ms at 44.1kHz, ~21Hz per bin
+pub const FFT_SIZE: usize = 2048;
+// Bars bottom out here
+pub const FLOOR_DB: f64 = -80.0;
+// How fast a bar falls back once the level drops, so it doesn't flicker
+const DECAY_DB_PER_SEC: f64 = 160.0;
+// How fast a held peak comes down once its hold is over
+const PEAK_FALL_DB_PER_SEC: f64 = 20.0;
+const LOW_HZ: f64 = 20.0;
+const HIGH_HZ: f64 = 20_000.0;
+
+/// Band levels of the current window, in dB with falling peaks.
+/// The FFT plan and buffers are kept between frames.
+pub struct Spectroscope {
+	planner: FftPlanner<f64>,
+	fft: Option<Arc<dyn Fft<f64>>>,
+	buffer: Vec<Complex<f64>>,
+	scratch: Vec<Complex<f64>>,
+	window: Vec<f64>,
+	levels: Vec<f64>,
+	fresh: Vec<f64>, // This window's levels, before the falling peaks
+	last_update: Option<Instant>,
+	pub log_scale: bool, // Bands spaced by octave rather than by Hz; `l` flips it
+	pub peak_hold: bool, // Marks over each bar at its held peak; `p` flips it
+	pub hold: PeakHold,  // One slot per band
+}
+
+impl Default for Spectroscope {
+	fn default() -> Self {
+		Spectroscope {
+			planner: FftPlanner::new(),
+			fft: None,
+			buffer: Vec::new(),
+			scratch: Vec::new(),
+			window: Vec::new(),
+			levels: Vec::new(),
+			fresh: Vec::new(),
+			last_update: None,
+			log_scale: true,
+			peak_hold: false,
+			hold: PeakHold::new(Duration::from_secs(2), PEAK_FALL_DB_PER_SEC, FLOOR_DB),
+		}
+	}
+}
+
+// Hann window of `len` points
+fn hann(len: usize) -> Vec<f64> {
+	(0..len)
+		.map(|i| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / (len - 1) as f64).cos())
+		.collect()
+}
+
+/// Bin ranges for `bands` bands between LOW_HZ and HIGH_HZ (or Nyquist), log-spaced or evenly spaced.
+/// Every band gets at least one bin, so the low end repeats bins rather than leaving gaps.
+pub fn band_bins(bands: usize, fft_size: usize, sample_rate: u32, log_scale: bool) -> impl Iterator<Item = (usize, usize)> {
+	let nyquist = sample_rate as f64 / 2.0;
+	let hz_per_bin = sample_rate as f64 / fft_size as f64;
+	let high = HIGH_HZ.min(nyquist);
+	let last_bin = fft_size / 2 - 1;
+	let edge = move |i: usize| {
+		let at = i as f64 / bands as f64;
+		if log_scale {
+			LOW_HZ * (high / LOW_HZ).powf(at)
+		} else {
+			LOW_HZ + (high - LOW_HZ) * at
+		}
+	};
+	(0..bands).map(move |i| {
+		let (lo, hi) = (edge(i), edge(i + 1));
+		let start = ((lo / hz_per_bin) as usize).clamp(1, last_bin);
+		let end = ((hi / hz_per_bin) as usize).clamp(start + 1, last_bin + 1);
+		(start, end)
+	})
+}
+
+/// The band the frequency `hz` falls in, None when it's outside the bands
+pub fn band_of(hz: f64, bands: usize, fft_size: usize, sample_rate: u32, log_scale: bool) -> Option<usize> {
+	let bin = (hz * fft_size as f64 / sample_rate as f64) as usize;
+	band_bins(bands, fft_size, sample_rate, log_scale).position(|(start, end)| (start..end).contains(&bin))
+}
+
+impl Spectroscope {
+	/// Band levels (FLOOR_DB..=0) for the first FFT_SIZE samples of `data`, channels mixed down
+	pub fn process(&mut self, data: &Matrix<f64>, sample_rate: u32, bands: usize) -> &[f64] {
+		let now = Instant::now();
+		let elapsed = self.last_update.map_or(0.0, |t| now.duration_since(t).as_secs_f64());
+		self.last_update = Some(now);
+
+		self.measure(data, sample_rate, bands);
+		if self.levels.len() != bands {
+			self.levels = vec![FLOOR_DB; bands];
+		}
+		let fall = DECAY_DB_PER_SEC * elapsed;
+		for (level, db) in self.levels.iter_mut().zip(&self.fresh) {
+			*level = db.max(*level - fall);
+		}
+		if self.peak_hold {
+			self.hold.update(&self.levels, now);
+		}
+		&self.levels
+	}
+
+	/// Like `process` but without the falling peaks: just this window's levels
+	pub fn snapshot(&mut self, data: &Matrix<f64>, sample_rate: u32, bands: usize) -> &[f64] {
+		self.measure(data, sample_rate, bands);
+		&self.fresh
+	}
+
+	// This window's levels into `fresh`, in buffers kept from frame to frame
+	fn measure(&mut self, data: &Matrix<f64>, sample_rate: u32, bands: usize) {
+		if self.fft.is_none() {
+			self.fft = Some(self.planner.plan_fft_forward(FFT_SIZE));
+			self.window = hann(FFT_SIZE);
+			self.buffer = vec![Complex::default(); FFT_SIZE];
+		}
+		let fft = self.fft.as_ref().unwrap();
+		if self.scratch.len() != fft.get_inplace_scratch_len() {
+			self.scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+		}
+
+		let channels = data.len().max(1) as f64;
+		for (i, slot) in self.buffer.iter_mut().enumerate() {
+			let sample: f64 = data.iter().map(|c| c.get(i).copied().unwrap_or(0.0)).sum::<f64>() / channels;
+			*slot = Complex::new(sample * self.window[i], 0.0);
+		}
+		fft.process_with_scratch(&mut self.buffer, &mut self.scratch);
+
+		// A full scale sine comes out at about 0dB
+		let norm = 2.0 / self.window.iter().sum::<f64>();
+		self.fresh.clear();
+		self.fresh.extend(band_bins(bands, FFT_SIZE, sample_rate, self.log_scale).map(|(start, end)| {
+			let magnitude = self.buffer[start..end].iter().map(|c| c.norm()).fold(0.0, f64::max) * norm;
+			(20.0 * magnitude.max(1e-12).log10()).clamp(FLOOR_DB, 0.0)
+		}));
+	}
+
+	pub fn handle(&mut self, event: Event) {
+		if let Event::Key(key) = event {
+			match key.code {
+				KeyCode::Char('l') => {
+					// The bands move, so what was held no longer lines up
+					self.log_scale = !self.log_scale;
+					self.hold.reset();
+				}
+				KeyCode::Char('p') => {
+					self.peak_hold = !self.peak_hold;
+					self.hold.reset();
+				}
+				_ => {}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bands_cover_the_range_in_order() {
+		for log_scale in [true, false] {
+			let bins: Vec<_> = band_bins(60, FFT_SIZE, 44_100, log_scale).collect();
+			assert_eq!(bins.len(), 60);
+			assert!(bins.iter().all(|(start, end)| start < end && *end <= FFT_SIZE / 2));
+			assert!(bins.windows(2).all(|w| w[0].0 <= w[1].0));
+		}
+	}
+
+	#[test]
+	fn a_sine_peaks_in_its_band() {
+		let rate = 44_100;
+		let freq = 1000.0;
+		let samples: Vec<f64> = (0..FFT_SIZE)
+			.map(|i| (2.0 * std::f64::consts::PI * freq * i as f64 / rate as f64).sin())
+			.collect();
+		let mut scope = Spectroscope::default();
+		let levels = scope.process(&vec![samples], rate, 30).to_vec();
+
+		let loudest = levels.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0;
+		let (start, end) = band_bins(30, FFT_SIZE, rate, true).nth(loudest).unwrap();
+		let hz_per_bin = rate as f64 / FFT_SIZE as f64;
+		assert!((start as f64 * hz_per_bin) <= freq * 1.1 && freq * 0.9 <= (end as f64 * hz_per_bin));
+		assert!(levels[loudest] > -3.0, "{}", levels[loudest]);
+	}
+
+	#[test]
+	fn linear_bands_are_even_in_hz() {
+		let bins: Vec<_> = band_bins(4, FFT_SIZE, 44_100, false).collect();
+		let widths: Vec<usize> = bins.iter().map(|(start, end)| end - start).collect();
+		assert!(widths.iter().all(|w| w.abs_diff(widths[0]) <= 1), "{:?}", widths);
+		let log: Vec<_> = band_bins(4, FFT_SIZE, 44_100, true).collect();
+		assert!(log[0].1 - log[0].0 < log[3].1 - log[3].0);
+	}
+}
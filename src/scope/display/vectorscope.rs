@@ -0,0 +1,87 @@
+use ratatui::{
+	style::Style,
+	text::Span,
+	widgets::{Axis, GraphType},
+};
+
+use crate::scope::Matrix;
+
+use super::{DataSet, DataSets, Dimension, DisplayMode, GraphConfig};
+
+/// Left channel against right: a mono signal is a diagonal line, wide stereo fills the square
+#[derive(Default)]
+pub struct Vectorscope {
+	pub correlation: Option<f64>, // Of the last window processed, None when it was silent
+	sets: DataSets,
+}
+
+/// How alike the channels are: +1 the same, 0 unrelated, -1 out of phase. None for silence.
+pub fn correlation(left: &[f64], right: &[f64]) -> Option<f64> {
+	let (mut lr, mut ll, mut rr) = (0.0, 0.0, 0.0);
+	for (l, r) in left.iter().zip(right) {
+		lr += l * r;
+		ll += l * l;
+		rr += r * r;
+	}
+	let power = (ll * rr).sqrt();
+	(power > 1e-12).then(|| (lr / power).clamp(-1.0, 1.0))
+}
+
+impl DisplayMode for Vectorscope {
+	fn axis(&self, cfg: &GraphConfig, dimension: Dimension) -> Axis<'static> {
+		let name = match (dimension, cfg.mid_side) {
+			(Dimension::X, false) => "left -",
+			(Dimension::Y, false) => "| right",
+			(Dimension::X, true) => "mid -",
+			(Dimension::Y, true) => "| side",
+		};
+		let mut a = Axis::default();
+		if cfg.show_ui {
+			a = a.title(Span::styled(name, Style::default().fg(cfg.labels_color)));
+		}
+		a.style(Style::default().fg(cfg.axis_color)).bounds([-cfg.scale, cfg.scale])
+	}
+
+	fn process(&mut self, cfg: &GraphConfig, data: &Matrix<f64>) -> &[DataSet] {
+		self.sets.clear();
+		let Some(left) = data.first() else { return self.sets.sets() };
+		// Mono plays the same on both sides
+		let right = data.get(1).unwrap_or(left);
+		self.correlation = correlation(left, right);
+		if cfg.references {
+			for line in [[(-cfg.scale, 0.0), (cfg.scale, 0.0)], [(0.0, -cfg.scale), (0.0, cfg.scale)]] {
+				self.sets.next(None, cfg.marker_type, GraphType::Line, cfg.axis_color).extend(line);
+			}
+		}
+		// Lines between samples would just scribble across the square
+		self.sets
+			.next(None, cfg.marker_type, GraphType::Scatter, cfg.palette(0))
+			.extend(left.iter().zip(right).map(|(l, r)| (*l, *r)));
+		self.sets.sets()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mono_lies_on_the_diagonal() {
+		let cfg = GraphConfig { scale: 1.0, palette: vec![ratatui::style::Color::White], ..Default::default() };
+		let mut scope = Vectorscope::default();
+		let sets = scope.process(&cfg, &vec![vec![0.5, -0.25]]);
+		assert_eq!(sets[0].data, [(0.5, 0.5), (-0.25, -0.25)]);
+		assert_eq!(scope.correlation, Some(1.0));
+		let sets = scope.process(&cfg, &vec![vec![0.5, -0.25], vec![0.1, 0.2]]);
+		assert_eq!(sets[0].data, [(0.5, 0.1), (-0.25, 0.2)]);
+	}
+
+	#[test]
+	fn correlation_reads_phase() {
+		let wave = [0.5, -0.5, 0.25, -0.25];
+		let flipped = wave.map(|s| -s);
+		assert_eq!(correlation(&wave, &flipped), Some(-1.0));
+		assert_eq!(correlation(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+		assert_eq!(correlation(&wave, &[0.0; 4]), None);
+	}
+}
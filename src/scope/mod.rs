@@ -1,3 +1,8 @@
+pub mod channels;
 pub mod display;
+pub mod pitch;
+pub mod processing;
+pub mod style;
+pub mod widget;
 
 pub type Matrix<T> = Vec<Vec<T>>;
@@ -0,0 +1,158 @@
+use std::time::{Duration, Instant};
+
+use super::Matrix;
+
+// Lowest and highest fundamental looked for
+const MIN_HZ: f64 = 40.0;
+const MAX_HZ: f64 = 2000.0;
+// YIN's dip has to reach below this to count; noise and chords stay well above it
+const THRESHOLD: f64 = 0.15;
+// Quieter than this (RMS) is silence, not a note
+const SILENCE: f64 = 0.005;
+// How often a reading is taken; the detector is too slow to run every frame
+const INTERVAL: Duration = Duration::from_millis(250);
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// A detected fundamental and the equal-tempered note (A4 = 440 Hz) nearest it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pitch {
+	pub frequency: f64,
+	pub midi: i32,
+	pub cents: f64, // How far off the note, -50 to +50
+}
+
+impl Pitch {
+	pub fn of(frequency: f64) -> Self {
+		let exact = 69.0 + 12.0 * (frequency / 440.0).log2();
+		let midi = exact.round() as i32;
+		Pitch { frequency, midi, cents: (exact - midi as f64) * 100.0 }
+	}
+
+	/// Note name with octave, like "A4" or "C#3"
+	pub fn note(&self) -> String {
+		format!("{}{}", NOTE_NAMES[self.midi.rem_euclid(12) as usize], self.midi.div_euclid(12) - 1)
+	}
+}
+
+/// Samples looked at per reading at `sample_rate`: two periods of the lowest note, rounded up to a
+/// power of two for room to compare them (4096 at 44.1 and 48 kHz, 8192 at 88.2 and 96 kHz)
+pub fn window(sample_rate: u32) -> usize {
+	(2 * (sample_rate as f64 / MIN_HZ).ceil() as usize).next_power_of_two()
+}
+
+/// The fundamental of `samples` by YIN, None when there's no clear single pitch
+pub fn detect(samples: &[f64], sample_rate: u32) -> Option<f64> {
+	let rate = sample_rate as f64;
+	let min_lag = (rate / MAX_HZ).floor().max(2.0) as usize;
+	let max_lag = (rate / MIN_HZ).ceil() as usize;
+	if samples.len() < max_lag * 2 {
+		return None;
+	}
+	let rms = (samples.iter().map(|s| s * s).sum::<f64>() / samples.len() as f64).sqrt();
+	if rms < SILENCE {
+		return None;
+	}
+
+	// Cumulative mean normalized difference: near 0 at lags that line the signal up with itself
+	let width = samples.len() - max_lag;
+	let mut cmnd = vec![1.0; max_lag + 1];
+	let mut running = 0.0;
+	for lag in 1..=max_lag {
+		let difference: f64 = (0..width).map(|j| (samples[j] - samples[j + lag]).powi(2)).sum();
+		running += difference;
+		cmnd[lag] = if running > 0.0 { difference * lag as f64 / running } else { 1.0 };
+	}
+
+	// The first dip under the threshold, followed down to its bottom
+	let mut lag = (min_lag..max_lag).find(|&lag| cmnd[lag] < THRESHOLD)?;
+	while lag + 1 < max_lag && cmnd[lag + 1] < cmnd[lag] {
+		lag += 1;
+	}
+	// Between samples, from a parabola through the bottom and its neighbours
+	let (a, b, c) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+	let curve = a - 2.0 * b + c;
+	let offset = if curve > 0.0 { (a - c) / (2.0 * curve) } else { 0.0 };
+	Some(rate / (lag as f64 + offset))
+}
+
+/// Pitch readings taken a few times a second from the mix of all channels
+#[derive(Debug, Default)]
+pub struct PitchTracker {
+	pub reading: Option<Pitch>,
+	last: Option<Instant>,
+}
+
+impl PitchTracker {
+	/// Whether it's time for another reading
+	pub fn due(&self, now: Instant) -> bool {
+		self.last.is_none_or(|last| now.duration_since(last) >= INTERVAL)
+	}
+
+	pub fn update(&mut self, window: &Matrix<f64>, sample_rate: u32, now: Instant) {
+		self.last = Some(now);
+		let len = window.iter().map(Vec::len).min().unwrap_or(0);
+		let mono: Vec<f64> = (0..len).map(|i| window.iter().map(|channel| channel[i]).sum::<f64>() / window.len() as f64).collect();
+		self.reading = detect(&mono, sample_rate).map(Pitch::of);
+	}
+
+	pub fn clear(&mut self) {
+		*self = PitchTracker::default();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f64::consts::PI;
+
+	fn wave_at(rate: u32, frequency: f64, shape: impl Fn(f64) -> f64) -> Vec<f64> {
+		(0..window(rate)).map(|i| 0.5 * shape(frequency * i as f64 / rate as f64)).collect()
+	}
+
+	fn wave(frequency: f64, shape: impl Fn(f64) -> f64) -> Vec<f64> {
+		wave_at(44_100, frequency, shape)
+	}
+
+	#[test]
+	fn finds_the_fundamental_of_tones() {
+		let sine = detect(&wave(220.0, |t| (2.0 * PI * t).sin()), 44_100).unwrap();
+		assert!((sine - 220.0).abs() < 0.5, "{}", sine);
+		// A sawtooth's harmonics don't pull it up an octave
+		let saw = detect(&wave(110.0, |t| 2.0 * (t - (t + 0.5).floor())), 44_100).unwrap();
+		assert!((saw - 110.0).abs() < 0.5, "{}", saw);
+	}
+
+	#[test]
+	fn noise_and_silence_have_no_pitch() {
+		let mut seed = 1u32;
+		let noise: Vec<f64> = (0..window(44_100))
+			.map(|_| {
+				seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+				seed as f64 / u32::MAX as f64 - 0.5
+			})
+			.collect();
+		assert_eq!(detect(&noise, 44_100), None);
+		assert_eq!(detect(&vec![0.0; window(44_100)], 44_100), None);
+	}
+
+	#[test]
+	fn high_sample_rates_get_a_window_long_enough() {
+		assert_eq!((window(44_100), window(48_000), window(96_000)), (4096, 4096, 8192));
+		let mut tracker = PitchTracker::default();
+		let tone = wave_at(96_000, 110.0, |t| (2.0 * PI * t).sin());
+		tracker.update(&vec![tone.clone(), tone], 96_000, Instant::now());
+		let reading = tracker.reading.expect("a 110 Hz tone at 96 kHz");
+		assert_eq!(reading.note(), "A2");
+	}
+
+	#[test]
+	fn names_the_nearest_note() {
+		let a = Pitch::of(440.0);
+		assert_eq!((a.note(), a.cents.abs() < 1e-9), ("A4".to_string(), true));
+		let sharp_c = Pitch::of(261.626 * 2f64.powf(10.0 / 1200.0));
+		assert_eq!(sharp_c.note(), "C4");
+		assert!((sharp_c.cents - 10.0).abs() < 0.1, "{}", sharp_c.cents);
+		assert_eq!(Pitch::of(30.87).note(), "B0");
+	}
+}
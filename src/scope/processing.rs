@@ -0,0 +1,114 @@
+use std::f64::consts::PI;
+
+use super::Matrix;
+
+// Below this the DC block takes frequencies out
+const DC_CUTOFF_HZ: f64 = 10.0;
+// Each gain step, and how far gain goes either way (x0.1 to x10), in dB
+const GAIN_STEP_DB: i32 = 2;
+const GAIN_RANGE_DB: std::ops::RangeInclusive<i32> = -20..=20;
+
+/// What's done to the scope's window before it's drawn, in order: DC block, then gain.
+/// Playback never sees any of it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Processing {
+	pub dc_block: bool,
+	gains_db: Vec<i32>, // Per channel; channels past the end are at 0 dB. Whole steps always find their way back to 0.
+}
+
+impl Processing {
+	pub fn apply(&self, data: &mut Matrix<f64>, sample_rate: u32) {
+		if self.dc_block {
+			dc_block(data, sample_rate);
+		}
+		for (channel, db) in data.iter_mut().zip(&self.gains_db) {
+			if *db != 0 {
+				let gain = db_to_gain(*db);
+				channel.iter_mut().for_each(|sample| *sample *= gain);
+			}
+		}
+	}
+
+	/// One step louder (`up`) or quieter for `channel`
+	pub fn step_gain(&mut self, channel: usize, up: bool) {
+		if self.gains_db.len() <= channel {
+			self.gains_db.resize(channel + 1, 0);
+		}
+		let db = self.gains_db[channel] + if up { GAIN_STEP_DB } else { -GAIN_STEP_DB };
+		self.gains_db[channel] = db.clamp(*GAIN_RANGE_DB.start(), *GAIN_RANGE_DB.end());
+	}
+
+	/// Channels with a gain other than 0 dB, as (channel, dB)
+	pub fn gains(&self) -> impl Iterator<Item = (usize, i32)> + '_ {
+		self.gains_db.iter().copied().enumerate().filter(|(_, db)| *db != 0)
+	}
+}
+
+/// One-pole high-pass at DC_CUTOFF_HZ over each channel. Windows don't follow on from each other,
+/// so the filter starts from the window's mean: a constant offset comes out as exactly zero.
+pub fn dc_block(data: &mut Matrix<f64>, sample_rate: u32) {
+	let r = (-2.0 * PI * DC_CUTOFF_HZ / sample_rate.max(1) as f64).exp();
+	for channel in data.iter_mut() {
+		if channel.is_empty() {
+			continue;
+		}
+		let mut previous_in = channel.iter().sum::<f64>() / channel.len() as f64;
+		let mut previous_out = 0.0;
+		for sample in channel.iter_mut() {
+			let out = *sample - previous_in + r * previous_out;
+			previous_in = *sample;
+			previous_out = out;
+			*sample = out;
+		}
+	}
+}
+
+pub fn db_to_gain(db: i32) -> f64 {
+	10f64.powf(db as f64 / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dc_block_removes_a_constant_offset() {
+		let mut data = vec![vec![0.4; 512]];
+		dc_block(&mut data, 44_100);
+		assert!(data[0].iter().all(|s| s.abs() < 1e-12));
+	}
+
+	#[test]
+	fn dc_block_passes_a_sine() {
+		let sine: Vec<f64> = (0..2048).map(|i| 0.5 * (2.0 * PI * 1000.0 * i as f64 / 44_100.0 + 0.7).sin()).collect();
+		let mut data = vec![sine.iter().map(|s| s + 0.3).collect()];
+		dc_block(&mut data, 44_100);
+		let span = |s: &[f64]| s.iter().cloned().fold(f64::MIN, f64::max) - s.iter().cloned().fold(f64::MAX, f64::min);
+		assert!((span(&data[0]) / span(&sine) - 1.0).abs() < 0.01);
+		let mean = data[0].iter().sum::<f64>() / data[0].len() as f64;
+		assert!(mean.abs() < 0.01, "{}", mean);
+	}
+
+	#[test]
+	fn gain_steps_per_channel_and_returns_to_unity() {
+		let mut processing = Processing::default();
+		processing.step_gain(1, true);
+		let mut data = vec![vec![0.2], vec![0.2]];
+		processing.apply(&mut data, 44_100);
+		assert_eq!(data[0], [0.2]);
+		assert!((data[1][0] - 0.2 * 1.2589).abs() < 1e-4, "{}", data[1][0]);
+		processing.step_gain(1, false);
+		assert_eq!(processing.gains().count(), 0);
+
+		// Held against the bottom, the way back up still lands on unity
+		for _ in 0..30 {
+			processing.step_gain(0, false);
+		}
+		assert_eq!(processing.gains().collect::<Vec<_>>(), [(0, -20)]);
+		assert!((db_to_gain(-20) - 0.1).abs() < 1e-12);
+		for _ in 0..10 {
+			processing.step_gain(0, true);
+		}
+		assert_eq!(processing.gains().count(), 0);
+	}
+}
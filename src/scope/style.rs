@@ -0,0 +1,59 @@
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+
+/// `color` at `brightness` (0.0 black to 1.0 as is). Named colors are taken at xterm's
+/// values, since the terminal's own can't be asked for; Reset can't be dimmed at all.
+pub fn dim_color(color: Color, brightness: f64) -> Color {
+	let (r, g, b) = match color {
+		Color::Rgb(r, g, b) => (r, g, b),
+		Color::Black => (0, 0, 0),
+		Color::Red => (205, 0, 0),
+		Color::Green => (0, 205, 0),
+		Color::Yellow => (205, 205, 0),
+		Color::Blue => (0, 0, 238),
+		Color::Magenta => (205, 0, 205),
+		Color::Cyan => (0, 205, 205),
+		Color::Gray => (229, 229, 229),
+		Color::DarkGray => (127, 127, 127),
+		Color::LightRed => (255, 0, 0),
+		Color::LightGreen => (0, 255, 0),
+		Color::LightYellow => (255, 255, 0),
+		Color::LightBlue => (92, 92, 255),
+		Color::LightMagenta => (255, 0, 255),
+		Color::LightCyan => (0, 255, 255),
+		Color::White => (255, 255, 255),
+		_ => return color,
+	};
+	let scale = |c: u8| (c as f64 * brightness.clamp(0.0, 1.0)).round() as u8;
+	Color::Rgb(scale(r), scale(g), scale(b))
+}
+
+/// The biggest square that fits in the middle of `area`, counting a cell as twice as tall as wide
+pub fn square_box(area: Rect) -> Rect {
+	let height = area.height.min(area.width / 2);
+	let width = height * 2;
+	Rect {
+		x: area.x + (area.width - width) / 2,
+		y: area.y + (area.height - height) / 2,
+		width,
+		height,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dimming_scales_toward_black() {
+		assert_eq!(dim_color(Color::Rgb(200, 100, 0), 0.5), Color::Rgb(100, 50, 0));
+		assert_eq!(dim_color(Color::Green, 0.2), Color::Rgb(0, 41, 0));
+		assert_eq!(dim_color(Color::Reset, 0.5), Color::Reset);
+	}
+
+	#[test]
+	fn square_box_pads_the_longer_side() {
+		assert_eq!(square_box(Rect::new(0, 0, 40, 10)), Rect::new(10, 0, 20, 10));
+		assert_eq!(square_box(Rect::new(2, 1, 20, 30)), Rect::new(2, 11, 20, 10));
+	}
+}
@@ -0,0 +1,97 @@
+use ratatui::{
+	buffer::Buffer,
+	layout::Rect,
+	style::Style,
+	widgets::{Block, Chart, Dataset, Widget},
+};
+
+use super::display::persistence::Persistence;
+use super::display::{Dimension, DisplayMode, GraphConfig};
+use super::style::{dim_color, square_box};
+use super::Matrix;
+
+/// A display mode drawn over `data` as a chart: processing, axes and the Chart in one widget
+pub struct ScopeWidget<'a> {
+	cfg: &'a GraphConfig,
+	mode: &'a mut dyn DisplayMode,
+	data: &'a Matrix<f64>,
+	block: Option<Block<'a>>,
+	persistence: Option<&'a mut Persistence>,
+	square: bool,
+}
+
+impl<'a> ScopeWidget<'a> {
+	pub fn new(cfg: &'a GraphConfig, mode: &'a mut dyn DisplayMode, data: &'a Matrix<f64>) -> Self {
+		ScopeWidget { cfg, mode, data, block: None, persistence: None, square: false }
+	}
+
+	pub fn block(mut self, block: Block<'a>) -> Self {
+		self.block = Some(block);
+		self
+	}
+
+	/// Fading trails of the last `cfg.persistence` frames underneath, this frame added once drawn
+	pub fn persistence(mut self, persistence: &'a mut Persistence) -> Self {
+		self.persistence = Some(persistence);
+		self
+	}
+
+	/// Chart in the biggest square inside the block, so a circle looks like one
+	pub fn square(mut self, square: bool) -> Self {
+		self.square = square;
+		self
+	}
+}
+
+impl Widget for ScopeWidget<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		let (x_axis, y_axis) = (self.mode.axis(self.cfg, Dimension::X), self.mode.axis(self.cfg, Dimension::Y));
+		let datasets = self.mode.process(self.cfg, self.data);
+		let trails = self.persistence.as_deref().into_iter().flat_map(Persistence::trails);
+		let chart = Chart::new(
+			trails
+				.map(|(set, brightness)| Dataset::from(set).style(Style::default().fg(dim_color(set.color(), brightness))))
+				.chain(datasets.iter().map(Dataset::from))
+				.collect(),
+		)
+		.x_axis(x_axis)
+		.y_axis(y_axis);
+
+		let inner = match self.block {
+			Some(block) => {
+				let inner = block.inner(area);
+				block.render(area, buf);
+				inner
+			}
+			None => area,
+		};
+		chart.render(if self.square { square_box(inner) } else { inner }, buf);
+
+		if let Some(persistence) = self.persistence {
+			persistence.push(datasets, self.cfg.persistence as usize);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::scope::display::oscilloscope::Oscilloscope;
+	use ratatui::style::Color;
+	use ratatui::symbols::Marker;
+
+	#[test]
+	fn draws_the_trace_and_remembers_it_for_trails() {
+		let cfg = GraphConfig { samples: 8, scale: 1.0, persistence: 2, marker_type: Marker::Block, palette: vec![Color::Red], ..Default::default() };
+		let mut scope = Oscilloscope::default();
+		let mut persistence = Persistence::default();
+		let data = vec![vec![0.9; 8]];
+		let area = Rect::new(0, 0, 10, 6);
+		let mut buf = Buffer::empty(area);
+		ScopeWidget::new(&cfg, &mut scope, &data).block(Block::bordered()).persistence(&mut persistence).render(area, &mut buf);
+
+		assert_eq!(buf[(0, 0)].symbol(), "┌");
+		assert!((1..9).any(|x| buf[(x, 1)].fg == Color::Red), "the trace runs along the top row inside the border");
+		assert_eq!(persistence.trails().count(), 1);
+	}
+}
@@ -0,0 +1,90 @@
+use image::RgbImage;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Widget},
+};
+use crate::app::artwork::ART_PIXELS;
+use crate::ui::theme::Theme;
+
+fn pixel(art: &RgbImage, x: u32, y: u32) -> Color {
+    let [r, g, b] = art.get_pixel(x.min(ART_PIXELS - 1), y.min(ART_PIXELS - 1)).0;
+    Color::Rgb(r, g, b)
+}
+
+/// The cover in half blocks: each cell is two pixels, the top one as the ▀'s color and the bottom
+/// one as the background. Without a cover, a dim checkerboard holds the space.
+pub struct ArtworkView<'a> {
+    pub artwork: Option<&'a RgbImage>, // ART_PIXELS square, from app::artwork::decode
+    pub theme: &'a Theme,
+}
+
+impl Widget for ArtworkView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.primary))
+            .style(Style::default().bg(self.theme.bg));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.is_empty() {
+            return;
+        }
+
+        let (width, rows) = (inner.width as u32, inner.height as u32);
+        for row in 0..rows {
+            for column in 0..width {
+                // A picture is all color, so monochrome keeps to the placeholder
+                let (top, bottom) = match self.artwork.filter(|_| !self.theme.mono) {
+                    Some(art) => {
+                        let x = column * ART_PIXELS / width;
+                        (pixel(art, x, 2 * row * ART_PIXELS / (2 * rows)), pixel(art, x, (2 * row + 1) * ART_PIXELS / (2 * rows)))
+                    },
+                    None => {
+                        let on = (column / 2 + row) % 2 == 0;
+                        let color = if on { self.theme.dim } else { self.theme.bg };
+                        (color, color)
+                    },
+                };
+                if let Some(cell) = buf.cell_mut((inner.x + column as u16, inner.y + row as u16)) {
+                    cell.set_symbol("▀").set_fg(top).set_bg(bottom);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn art(color: impl Fn(u32, u32) -> [u8; 3]) -> RgbImage {
+        RgbImage::from_fn(ART_PIXELS, ART_PIXELS, |x, y| Rgb(color(x, y)))
+    }
+
+    #[test]
+    fn half_blocks_carry_two_pixels_per_cell() {
+        // Top half white, bottom half black
+        let art = art(|_, y| if y < ART_PIXELS / 2 { [255, 255, 255] } else { [0, 0, 0] });
+        let theme = Theme::by_name("green");
+        let area = Rect::new(0, 0, 6, 6); // 4x4 cells inside the border
+        let mut buf = Buffer::empty(area);
+        ArtworkView { artwork: Some(&art), theme: &theme }.render(area, &mut buf);
+        let top = &buf[(1, 1)];
+        assert_eq!((top.symbol(), top.fg, top.bg), ("▀", Color::Rgb(255, 255, 255), Color::Rgb(255, 255, 255)));
+        let bottom = &buf[(1, 4)];
+        assert_eq!((bottom.fg, bottom.bg), (Color::Rgb(0, 0, 0), Color::Rgb(0, 0, 0)));
+    }
+
+    #[test]
+    fn monochrome_leaves_the_picture_out() {
+        let art = art(|_, _| [255, 0, 0]);
+        let theme = crate::ui::theme::MONO;
+        let area = Rect::new(0, 0, 6, 6);
+        let mut buf = Buffer::empty(area);
+        ArtworkView { artwork: Some(&art), theme: &theme }.render(area, &mut buf);
+        assert_eq!((buf[(2, 2)].fg, buf[(2, 2)].bg), (Color::Reset, Color::Reset));
+    }
+}
@@ -0,0 +1,40 @@
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use crate::app::state::App;
+use crate::ui::theme::Theme;
+use crate::ui::util::centered_box;
+
+/// The yes/no box for `app.pending_confirm`, drawn over whatever tab is showing
+pub fn render(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(question) = app.pending_confirm.and_then(|action| app.confirm_question(action)) else { return };
+    let area = centered_box(50, 7, f.area());
+    f.render_widget(Clear, area);
+
+    let key = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+    let lines = vec![
+        Line::from(Span::styled(question, Style::default().fg(theme.primary))),
+        Line::default(),
+        Line::from(vec![
+            Span::styled("[Y]", key),
+            Span::styled(" yes    ", Style::default().fg(theme.primary)),
+            Span::styled("[N]", key),
+            Span::styled(" no", Style::default().fg(theme.primary)),
+        ]),
+    ];
+    let dialog = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("CONFIRM")
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.bg)),
+        );
+    f.render_widget(dialog, area);
+}
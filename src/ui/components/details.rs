@@ -0,0 +1,74 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+use crate::app::state::App;
+use crate::ui::text::{align_right, wrap};
+use crate::ui::theme::Theme;
+use crate::ui::util::centered_box;
+
+// Widest the box gets, borders included
+const MAX_WIDTH: u16 = 76;
+
+/// A box of "label  value" rows over whatever's showing, long values wrapping under themselves.
+/// `keys` are (key, what it does) pairs listed along the bottom.
+pub fn render_pairs(f: &mut Frame, title: &str, rows: &[(&str, String)], keys: &[(&str, &str)], theme: &Theme) {
+    let label_width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0);
+    let width = MAX_WIDTH.min(f.area().width);
+    let value_width = (width as usize).saturating_sub(label_width + 4);
+
+    let mut lines = Vec::new();
+    for (label, value) in rows {
+        for (i, part) in wrap(value, value_width).into_iter().enumerate() {
+            let label = if i == 0 { *label } else { "" };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}  ", align_right(label, label_width)), Style::default().fg(theme.accent)),
+                Span::styled(part, Style::default().fg(theme.primary)),
+            ]));
+        }
+    }
+    lines.push(Line::default());
+    let key_style = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+    lines.push(Line::from(
+        keys.iter()
+            .flat_map(|(key, action)| [Span::styled(format!("[{}]", key), key_style), Span::styled(format!(" {}   ", action), Style::default().fg(theme.primary))])
+            .collect::<Vec<_>>(),
+    ).centered());
+
+    let area = centered_box(width, lines.len() as u16 + 2, f.area());
+    f.render_widget(Clear, area);
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .border_style(Style::default().fg(theme.accent))
+            .style(Style::default().bg(theme.bg)),
+    );
+    f.render_widget(popup, area);
+}
+
+/// The `i` popup, while it's open
+pub fn render(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(details) = &app.track_info else { return };
+    render_pairs(f, "TRACK INFO", &details.rows, &[("Y", "copy URL"), ("Esc", "close")], theme);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn long_values_wrap_under_their_own_column() {
+        let mut terminal = Terminal::new(TestBackend::new(30, 8)).unwrap();
+        let rows = [("URL", "https://example.com/watch?v=abc".to_string()), ("Plays", "2".to_string())];
+        terminal.draw(|f| render_pairs(f, "INFO", &rows, &[("Esc", "close")], &Theme::by_name("green"))).unwrap();
+        let line = |y: u16| (0..30).map(|x| terminal.backend().buffer()[(x, y)].symbol().to_string()).collect::<String>();
+        assert_eq!(line(1), "│  URL  https://example.com/w│");
+        assert_eq!(line(2), "│       atch?v=abc           │");
+        assert_eq!(line(3), "│Plays  2                    │");
+    }
+}
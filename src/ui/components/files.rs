@@ -0,0 +1,113 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use crate::app::state::{App, InputMode};
+use crate::ui::text::truncate;
+use crate::ui::theme::Theme;
+use crate::ui::util::input_spans;
+
+// `name` cut to `width` in `style`, with the chars at `matched` (the filter's hits) underlined
+fn name_spans(name: &str, width: usize, matched: &[usize], style: Style) -> Vec<Span<'static>> {
+    let name = truncate(name, width);
+    if matched.is_empty() {
+        return vec![Span::styled(name, style)];
+    }
+    let hit = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| Span::styled(c.to_string(), if matched.contains(&i) { hit } else { style }))
+        .collect()
+}
+
+/// The MAP tab's directory listing. Only the rows that fit in `rows` are built, so a directory
+/// with thousands of files costs no more per frame than a small one; draw it with the returned state.
+pub fn render(app: &mut App, theme: &Theme, rows: usize, width: usize) -> (List<'static>, ListState) {
+    let browsing = matches!(app.input_mode, InputMode::FileBrowser | InputMode::FileFilter);
+    let playing = app.playing_url().map(str::to_string);
+    let browser = &mut app.file_browser;
+    let window = browser.window(rows);
+    let selected = browser.list_state.selected().map(|i| i - window.start);
+
+    let items: Vec<ListItem> = if browser.loading && browser.entries.is_empty() {
+        vec![ListItem::new("  Reading directory...").style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM))]
+    } else if browser.entries.is_empty() {
+        vec![ListItem::new("  (empty)").style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM))]
+    } else if browser.row_count() == 0 {
+        vec![ListItem::new("  (no matches)").style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM))]
+    } else {
+        browser
+            .shown(window)
+            .into_iter()
+            .map(|(entry, matched)| {
+                let (icon, style) = if entry.is_dir {
+                    ("▸ ", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+                } else if playing.as_deref().is_some_and(|url| entry.path.to_string_lossy() == url) {
+                    ("♪ ", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+                } else if entry.is_audio() {
+                    ("  ", Style::default().fg(theme.primary))
+                } else {
+                    ("  ", Style::default().fg(theme.primary).add_modifier(Modifier::DIM))
+                };
+                let mut spans = vec![Span::styled(icon, Style::default().fg(theme.accent))];
+                if entry.is_dir {
+                    spans.extend(name_spans(&entry.name, width.saturating_sub(1), matched, style));
+                    spans.push(Span::styled("/", style));
+                } else {
+                    spans.extend(name_spans(&entry.name, width, matched, style));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
+
+    let count = match &browser.filter {
+        Some(_) => format!("{} of {} entries", browser.row_count(), browser.entries.len()),
+        None => format!("{} entries", browser.entries.len()),
+    };
+    let title = format!(
+        " {} ({}){} ",
+        browser.cwd.display(),
+        count,
+        if browser.loading { " loading..." } else { "" }
+    );
+    let border = if browsing { theme.accent } else { theme.primary };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(if browsing {
+                    "[Enter] open/play  [Bksp] up  [A] queue  [/] filter  [Esc] done"
+                } else {
+                    "[Enter] browse"
+                })
+                .border_style(Style::default().fg(border))
+                .style(Style::default().bg(theme.bg)),
+        )
+        .highlight_style(theme.highlight())
+        .highlight_symbol(if browsing { theme.marker() } else { "   " });
+
+    let mut state = ListState::default();
+    state.select(selected);
+    (list, state)
+}
+
+/// The filter's text under the listing, `width` wide, and the cursor's column inside it
+pub fn render_filter<'a>(app: &App, theme: &Theme, width: u16) -> (Paragraph<'a>, u16) {
+    let editing = matches!(app.input_mode, InputMode::FileFilter);
+    let empty = Default::default();
+    let input = app.file_browser.filter.as_ref().map_or(&empty, |f| &f.input);
+    let (spans, cursor) = input_spans("/", input, width.saturating_sub(2), theme);
+    let paragraph = Paragraph::new(Line::from(spans))
+        .style(Style::default().fg(theme.primary))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(if editing { "FILTER (Enter: open/play, Esc: clear)" } else { "FILTER ([/] edit, Esc: clear)" })
+                .border_style(Style::default().fg(if editing { theme.accent } else { theme.primary }))
+                .style(Style::default().bg(theme.bg)),
+        );
+    (paragraph, cursor)
+}
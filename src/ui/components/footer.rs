@@ -1,32 +1,55 @@
 use ratatui::{
-    layout::Alignment,
     style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
-use crate::ui::theme::{COLOR_RED, COLOR_YELLOW, PIPBOY_BG, PIPBOY_GREEN};
+use crate::app::keys::{context_for, hints, KeyContext};
+use crate::app::state::{App, InputMode};
+use crate::app::status::StatusLevel;
+use crate::ui::theme::Theme;
+use super::spinner;
 
-pub fn render(app: &crate::app::state::App) -> Paragraph<'_> {
-    let mut footer_spans = vec![
-        Span::styled("[Enter] ", Style::default().fg(PIPBOY_GREEN).add_modifier(Modifier::BOLD)),
-        Span::styled("TURN OFF  ", Style::default().fg(COLOR_YELLOW)),
-        Span::styled("[T] ", Style::default().fg(PIPBOY_GREEN).add_modifier(Modifier::BOLD)),
-        Span::styled("PERK  ", Style::default().fg(COLOR_YELLOW)),
-        Span::styled("[Q] ", Style::default().fg(PIPBOY_GREEN).add_modifier(Modifier::BOLD)),
-        Span::styled("QUIT", Style::default().fg(COLOR_YELLOW)),
-    ];
+/// Keys that work in the current tab and mode, the latest status message and yt-dlp's version along the top
+pub fn render<'a>(app: &'a App, theme: &Theme) -> Paragraph<'a> {
+    let context = context_for(app.current_tab, &app.input_mode);
+    // Text inputs take every printable key, so only the global keys of Normal mode apply there
+    let contexts: &[KeyContext] = match app.input_mode {
+        InputMode::Normal => &[context, KeyContext::Global],
+        _ => &[context],
+    };
 
-    if let Some(err) = &app.player.error_message {
-         footer_spans.push(Span::styled(format!("  ERROR: {}", err), Style::default().fg(COLOR_RED).add_modifier(Modifier::BOLD)));
+    let mut footer_spans = Vec::new();
+    for (keys, description) in hints(contexts) {
+        if !footer_spans.is_empty() {
+            footer_spans.push(Span::styled(" · ", Style::default().fg(theme.primary).add_modifier(Modifier::DIM)));
+        }
+        footer_spans.push(Span::styled(format!("{} ", keys), Style::default().fg(theme.primary).add_modifier(Modifier::BOLD)));
+        footer_spans.push(Span::styled(description.to_lowercase(), Style::default().fg(theme.accent)));
     }
 
+    let yt_dlp = match &app.yt_dlp_status {
+        Some(Ok(version)) => Span::styled(format!(" yt-dlp {} ", version), Style::default().fg(theme.primary)),
+        Some(Err(_)) => Span::styled(" yt-dlp MISSING ", Style::default().fg(theme.error)),
+        None => Span::raw(""),
+    };
+    let status = match app.status.current() {
+        // The spinner already says this one
+        Some(message) if message.level == StatusLevel::Progress && app.busy() => Span::raw(""),
+        Some(message) if message.level == StatusLevel::Error => {
+            Span::styled(format!(" {} ", message.text), Style::default().fg(theme.error).add_modifier(Modifier::BOLD))
+        },
+        Some(message) => Span::styled(format!(" {} ", message.text), Style::default().fg(theme.accent)),
+        None => Span::raw(""),
+    };
+
     Paragraph::new(Line::from(footer_spans))
-        .style(Style::default().bg(PIPBOY_BG))
-        .alignment(Alignment::Center)
+        .style(Style::default().bg(theme.bg))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(PIPBOY_GREEN))
-                .style(Style::default().bg(PIPBOY_BG)),
+                .title_top(Line::from(spinner::render(app, theme).into_iter().chain([status]).collect::<Vec<_>>()))
+                .title_top(Line::from(yt_dlp).right_aligned())
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg)),
         )
 }
@@ -1,40 +1,52 @@
 use ratatui::{
-    layout::Alignment,
+    layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Tabs},
+    Frame,
 };
-use crate::ui::theme::{PIPBOY_BG, PIPBOY_DARK, PIPBOY_GREEN};
+use unicode_width::UnicodeWidthStr;
+use crate::app::state::App;
+use crate::app::tab::Tab;
+use crate::audio::stream::is_offline;
+use super::now_playing;
+use crate::ui::theme::Theme;
 
-pub fn render(app: &crate::app::state::App) -> Paragraph<'_> {
-    let tabs = vec!["STAT", "INV", "DATA", "MAP", "RADIO"];
-    let tab_spans: Vec<Span> = tabs
+// Around each tab's title
+const PADDING: &str = "  ";
+
+/// The tabs in the middle, OFFLINE on the left of the border and what's playing on its right
+pub fn render(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.primary))
+        .style(Style::default().bg(theme.bg));
+    if is_offline() {
+        block = block.title_top(Span::styled(
+            " OFFLINE ",
+            Style::default().fg(theme.dim).bg(theme.warning).add_modifier(Modifier::BOLD),
+        ));
+    }
+    // RADIO has the full now playing panel
+    if app.current_tab != Tab::Radio
+        && let Some(text) = now_playing::compact(app, area.width as usize / 2) {
+        block = block.title_top(Line::from(Span::styled(format!(" {} ", text), Style::default().fg(theme.accent))).right_aligned());
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Without colors the current tab gets the selection marker too
+    let titles: Vec<String> = Tab::ALL
         .iter()
-        .enumerate()
-        .flat_map(|(i, t)| {
-            let style = if i == app.current_tab {
-                Style::default()
-                    .fg(PIPBOY_DARK)
-                    .bg(PIPBOY_GREEN)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(PIPBOY_GREEN)
-            };
-            vec![
-                Span::raw("  "),
-                Span::styled(format!("{}", t), style),
-                Span::raw("  "),
-            ]
-        })
+        .map(|tab| if theme.mono && *tab == app.current_tab { format!("▶ {}", tab.title()) } else { tab.title().to_string() })
         .collect();
-
-    Paragraph::new(Line::from(tab_spans))
-        .style(Style::default().bg(PIPBOY_BG))
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(PIPBOY_GREEN))
-                .style(Style::default().bg(PIPBOY_BG)),
-        )
+    let width = titles.iter().map(|t| t.width() + 2 * PADDING.len()).sum::<usize>() as u16;
+    let centered = Rect { x: inner.x + inner.width.saturating_sub(width) / 2, width: width.min(inner.width), ..inner };
+    let tabs = Tabs::new(titles)
+        .select(app.current_tab.index())
+        .style(Style::default().fg(theme.primary))
+        .highlight_style(theme.highlight())
+        .divider("")
+        .padding(PADDING, PADDING);
+    f.render_widget(tabs, centered);
 }
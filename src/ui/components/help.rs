@@ -0,0 +1,85 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use crate::app::history::{format_ago, now_secs};
+use crate::app::keys::{bindings_for, KeyContext};
+use crate::app::state::App;
+use crate::app::status::StatusLevel;
+use crate::ui::text::align_right;
+use crate::ui::theme::Theme;
+use crate::ui::util::centered_rect;
+
+// Which contexts go in which column of the overlay
+const COLUMNS: [&[KeyContext]; 3] = [
+    &[KeyContext::Global, KeyContext::TextInput, KeyContext::Confirm, KeyContext::Files, KeyContext::FileFilter],
+    &[KeyContext::Stat, KeyContext::Inv, KeyContext::Data, KeyContext::SearchInput, KeyContext::Map],
+    &[KeyContext::Results, KeyContext::Radio],
+];
+
+fn section_lines(context: KeyContext, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(
+        context.title(),
+        Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+    ))];
+    for binding in bindings_for(context) {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", align_right(&binding.label(), 11)), Style::default().fg(theme.accent)),
+            Span::styled(binding.description, Style::default().fg(theme.primary)),
+        ]));
+    }
+    lines.push(Line::default());
+    lines
+}
+
+// The last status messages, newest first
+fn log_lines(app: &App, theme: &Theme) -> Vec<Line<'static>> {
+    let now = now_secs();
+    app.status
+        .log
+        .iter()
+        .rev()
+        .map(|message| {
+            let color = if message.level == StatusLevel::Error { theme.error } else { theme.primary };
+            Line::from(vec![
+                Span::styled(format!("{:>10}  ", format_ago(now, message.logged_at)), Style::default().fg(theme.accent)),
+                Span::styled(message.text.clone(), Style::default().fg(color)),
+            ])
+        })
+        .collect()
+}
+
+/// Full list of key bindings (or the message log), drawn over whatever tab is showing
+pub fn render(f: &mut Frame, app: &App, theme: &Theme) {
+    let area = centered_rect(90, 90, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(if app.help_log {
+            "MESSAGES (Tab: keys, ↑↓ scroll, ?/Esc/Q close)"
+        } else {
+            "HELP (Tab: message log, ?/Esc/Q close)"
+        })
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if app.help_log {
+        f.render_widget(Paragraph::new(log_lines(app, theme)).scroll((app.help_scroll, 0)), inner);
+        return;
+    }
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(1, 3), Constraint::Ratio(1, 3)])
+        .split(inner);
+    for (contexts, column) in COLUMNS.iter().zip(columns.iter()) {
+        let lines: Vec<Line> = contexts.iter().flat_map(|c| section_lines(*c, theme)).collect();
+        f.render_widget(Paragraph::new(lines), *column);
+    }
+}
@@ -0,0 +1,90 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+use crate::app::history::{format_ago, now_secs, HistoryFocus, ARTIST_LIST_LEN, HISTORY_LIST_LEN, RECENT_LIST_LEN};
+use crate::app::state::App;
+use crate::ui::text::{row_room, row_text};
+use crate::ui::theme::Theme;
+
+// The focused list gets the bright border and the highlight
+fn history_list(theme: &Theme, title: &str, items: Vec<ListItem<'static>>, focused: bool) -> List<'static> {
+    let border = if focused {
+        Style::default().fg(theme.accent)
+    } else {
+        Style::default().fg(theme.primary)
+    };
+    let highlight = if focused {
+        theme.highlight()
+    } else {
+        Style::default()
+    };
+
+    List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title.to_string())
+                .border_style(border)
+                .style(Style::default().bg(theme.bg)),
+        )
+        .highlight_style(highlight)
+        .highlight_symbol(if focused { theme.marker() } else { "   " })
+}
+
+// A row's text beside `used` cells of numbers. The focused list's selected row scrolls.
+fn row(app: &App, text: &str, width: u16, used: usize, focus: HistoryFocus, index: usize) -> String {
+    let selected = app.history.focus == focus && app.history.selected_in(focus) == Some(index);
+    row_text(text, row_room(width, app.theme.marker(), used), selected, app.tick)
+}
+
+/// `width` is each list's area, for fitting the titles
+pub fn render_recent(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let now = now_secs();
+    let items = app
+        .history
+        .recent_unique(RECENT_LIST_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(i, play)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>9} ", format_ago(now, play.played_at)), Style::default().fg(theme.accent)),
+                Span::styled(row(app, &play.title, width, 10, HistoryFocus::Recent, i), Style::default().fg(theme.primary)),
+            ]))
+        })
+        .collect();
+    history_list(theme, "RECENTLY PLAYED [H] switch  [Enter] play  [F] favorite", items, app.history.focus == HistoryFocus::Recent)
+}
+
+pub fn render_top(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let items = app
+        .history
+        .top_played(HISTORY_LIST_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(i, track)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>3}x ", track.play_count), Style::default().fg(theme.accent)),
+                Span::styled(row(app, &track.title, width, 5, HistoryFocus::Top, i), Style::default().fg(theme.primary)),
+            ]))
+        })
+        .collect();
+    history_list(theme, "MOST PLAYED", items, app.history.focus == HistoryFocus::Top)
+}
+
+pub fn render_artists(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let items = app
+        .history
+        .top_artists(ARTIST_LIST_LEN)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (artist, plays))| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>3}x ", plays), Style::default().fg(theme.accent)),
+                Span::styled(row(app, &artist, width, 5, HistoryFocus::Artists, i), Style::default().fg(theme.primary)),
+            ]))
+        })
+        .collect();
+    history_list(theme, "TOP ARTISTS [Enter] play all", items, app.history.focus == HistoryFocus::Artists)
+}
@@ -0,0 +1,63 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use crate::app::state::App;
+use crate::ui::text::{marquee, truncate};
+use crate::ui::theme::{color_by_name, Theme};
+use crate::format::format_time;
+
+/// Title on the first line, artist and where it came from on the second, cut to fit `width`
+pub fn render<'a>(app: &'a App, theme: &Theme, width: u16) -> Paragraph<'a> {
+    let inner = width.saturating_sub(2) as usize;
+    let lines = match &app.now_playing {
+        Some(now) => {
+            // Radio has no progress to show, so say how long it's been on instead
+            let source = if app.playing_station().is_some() {
+                format!("RADIO · on air {}", format_time(now.started_at.elapsed()))
+            } else if app.current_path.is_some() {
+                "FILE".to_string()
+            } else {
+                "STREAM".to_string()
+            };
+            let artist = now.artist.as_deref().unwrap_or("Unknown artist");
+            // A station with a color of its own shows it here too
+            let color = app
+                .playing_station()
+                .and_then(|i| app.radio_stations[i].color.as_deref().and_then(color_by_name))
+                .filter(|_| !theme.mono)
+                .unwrap_or(theme.accent);
+            vec![
+                Line::from(Span::styled(
+                    marquee(&now.title, inner, app.tick),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                )),
+                Line::from(Span::styled(
+                    truncate(&format!("{} · {}", artist, source), inner),
+                    Style::default().fg(theme.primary),
+                )),
+            ]
+        }
+        None => vec![Line::from(Span::styled("Nothing playing", Style::default().fg(theme.primary).add_modifier(Modifier::DIM)))],
+    };
+
+    let favorite = app.now_playing.as_ref().is_some_and(|now| app.favorites.is_favorite(&now.url));
+    Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(if favorite { "NOW PLAYING ★" } else { "NOW PLAYING" })
+            .border_style(Style::default().fg(theme.primary))
+            .style(Style::default().bg(theme.bg)),
+    )
+}
+
+/// "♪ Artist - Title" for the header on tabs without the panel
+pub fn compact(app: &App, width: usize) -> Option<String> {
+    let now = app.now_playing.as_ref()?;
+    let text = match &now.artist {
+        Some(artist) => format!("♪ {} - {}", artist, now.title),
+        None => format!("♪ {}", now.title),
+    };
+    Some(marquee(&text, width, app.tick))
+}
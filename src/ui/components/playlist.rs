@@ -1,30 +1,127 @@
 use ratatui::{
     style::{Modifier, Style},
-    widgets::{Block, Borders, List, ListItem},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, Paragraph},
 };
-use crate::ui::theme::{PIPBOY_BG, PIPBOY_DARK, PIPBOY_GREEN};
+use crate::app::state::{App, InputMode};
+use crate::app::stations::Station;
+use crate::ui::text::{row_room, row_text};
+use crate::ui::theme::{color_by_name, Theme};
+use crate::ui::util::input_spans;
 
-pub fn render(radio_stations: &[String]) -> List<'_> {
-    let items: Vec<ListItem> = radio_stations
+// One station, its name cut (or scrolling, when selected) to fit a list `width` wide
+// and drawn in the station's own color when it has one
+fn station_item(station: &Station, playing: bool, selected: bool, width: u16, tick: u64, theme: &Theme) -> ListItem<'static> {
+    let name = row_text(&station.name, row_room(width, station_marker(theme), 2), selected, tick);
+    let color = station.color.as_deref().and_then(color_by_name).filter(|_| !theme.mono);
+    if playing {
+        ListItem::new(format!("♪ {}", name)).style(Style::default().fg(color.unwrap_or(theme.accent)).add_modifier(Modifier::BOLD))
+    } else {
+        ListItem::new(format!("  {}", name)).style(Style::default().fg(color.unwrap_or(theme.primary)))
+    }
+}
+
+/// `width` is the list's area
+pub fn render(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let playing = app.playing_station();
+    let selected = app.radio_state.selected();
+    let items: Vec<ListItem> = app
+        .radio_stations
         .iter()
-        .map(|station| {
-            ListItem::new(station.clone())
-                .style(Style::default().fg(PIPBOY_GREEN))
-        })
+        .enumerate()
+        .map(|(i, station)| station_item(station, playing == Some(i), selected == Some(i), width, app.tick, theme))
         .collect();
 
+    station_list(items, theme)
+}
+
+// Narrower than `Theme::marker`, leaving the station names more room
+fn station_marker(theme: &Theme) -> &'static str {
+    if theme.mono { "▶ " } else { "▮ " }
+}
+
+fn station_list(items: Vec<ListItem<'static>>, theme: &Theme) -> List<'static> {
     List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(PIPBOY_GREEN))
-                .style(Style::default().bg(PIPBOY_BG)),
+                .title("STATIONS [Enter] play [A]dd [E]dit [D]elete [C]olor")
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg)),
         )
-        .highlight_style(
-            Style::default()
-                .bg(PIPBOY_GREEN)
-                .fg(PIPBOY_DARK)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("▮ ")
+        .highlight_style(theme.highlight())
+        .highlight_symbol(station_marker(theme))
+}
+
+/// Name/URL prompt while adding or editing a station, `width` wide, and the cursor's column inside it
+pub fn render_input<'a>(app: &'a App, theme: &Theme, width: u16) -> (Paragraph<'a>, u16) {
+    let label = match app.input_mode {
+        InputMode::StationUrl => "Stream URL > ",
+        _ => "Station name > ",
+    };
+    let (spans, cursor) = input_spans(label, &app.search_input, width.saturating_sub(2), theme);
+    let paragraph = Paragraph::new(Line::from(spans))
+        .style(Style::default().fg(theme.primary))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("STATION (Enter: next, Esc: cancel)")
+                .border_style(Style::default().fg(theme.accent))
+                .style(Style::default().bg(theme.bg)),
+        );
+    (paragraph, cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, widgets::ListState, Terminal};
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn wide_names_stay_inside_the_box() {
+        let theme = Theme::by_name("green");
+        let width = 16;
+        let names = ["東京ラジオ・ジャズ専門局", "🎷🎺 Jazz 🎹🎸 FM", "KBS 클래식 FM"];
+        let items = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let station = Station { name: name.to_string(), url: String::new(), color: None };
+                station_item(&station, i == 2, i == 1, width, 0, &theme)
+            })
+            .collect();
+        let mut terminal = Terminal::new(TestBackend::new(width, 5)).unwrap();
+        terminal
+            .draw(|f| f.render_stateful_widget(station_list(items, &theme), f.area(), &mut ListState::default().with_selected(Some(1))))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        // The cell after a wide glyph is its second half, not a space of its own
+        let row = |y: u16| {
+            let mut text = String::new();
+            let mut x = 0;
+            while x < width {
+                let symbol = buffer[(x, y)].symbol();
+                text.push_str(symbol);
+                x += symbol.width().max(1) as u16;
+            }
+            text
+        };
+        assert_eq!(row(1), "│    東京ラジ… │");
+        assert_eq!(row(2), "│▮   🎷🎺 Jazz │");
+        assert_eq!(row(3), "│  ♪ KBS 클래… │");
+    }
+
+    #[test]
+    fn stations_show_their_color_unless_monochrome() {
+        let station = Station { name: "KEXP".to_string(), url: String::new(), color: Some("magenta".to_string()) };
+        let draw = |theme: &Theme| {
+            let items = vec![station_item(&station, false, false, 20, 0, theme)];
+            let mut terminal = Terminal::new(TestBackend::new(20, 3)).unwrap();
+            terminal.draw(|f| f.render_widget(station_list(items, theme), f.area())).unwrap();
+            terminal.backend().buffer()[(3, 1)].fg
+        };
+        assert_eq!(draw(&Theme::by_name("green")), ratatui::style::Color::Magenta);
+        assert_eq!(draw(&crate::ui::theme::MONO), ratatui::style::Color::Reset);
+    }
 }
@@ -0,0 +1,162 @@
+use std::time::Duration;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+use crate::app::podcasts::format_total_duration;
+use crate::app::state::{App, InputMode};
+use crate::ui::text::{row_room, row_text, truncate};
+use unicode_width::UnicodeWidthStr;
+use crate::ui::theme::Theme;
+use crate::ui::util::input_spans;
+use crate::format::format_time;
+
+/// `width` is the box's; long input scrolls to keep the cursor in view.
+/// While typing, also returns the cursor's column inside the box.
+pub fn render_input<'a>(app: &'a App, theme: &Theme, width: u16) -> (Paragraph<'a>, Option<u16>) {
+    let label = match app.input_mode {
+        InputMode::FeedUrl => Some("Feed URL > "),
+        InputMode::BackupPath => Some("Back up library to > "),
+        InputMode::RestorePath => Some("Restore library from > "),
+        InputMode::ExportPath => Some("Export M3U to > "),
+        InputMode::LibrarySearch => Some("Find in library > "),
+        _ => None,
+    };
+    let mut cursor = None;
+    let line = match label {
+        Some(label) => {
+            let input = if app.input_mode == InputMode::FeedUrl { &app.feed_input } else { &app.search_input };
+            let (spans, column) = input_spans(label, input, width.saturating_sub(2), theme);
+            cursor = Some(column);
+            Line::from(spans)
+        },
+        None if app.podcasts.favorites_open => Line::from("Enter: play from here  F: unfavorite  X: export M3U  Esc: back"),
+        None if app.podcasts.open_feed.is_some() => Line::from("Enter: play episode  F: favorite  X: export M3U  Esc: back to feeds  R: refresh"),
+        None => Line::from("A: add feed  Enter: open  D: remove  R: refresh all  /: find  X: export M3U  B/L: backup/restore"),
+    };
+
+    let paragraph = Paragraph::new(line)
+        .style(Style::default().fg(theme.primary))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("PODCASTS")
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg)),
+        );
+    (paragraph, cursor)
+}
+
+fn styled_list(theme: &Theme, title: String, items: Vec<ListItem<'static>>) -> List<'static> {
+    List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg)),
+        )
+        .highlight_style(theme.highlight())
+        .highlight_symbol(theme.marker())
+}
+
+/// "title — where it's saved" for each library search hit
+// A title and what follows it in `room` cells. The title keeps at least half the room, then
+// whatever `rest` doesn't need; `rest` gets what's left after that.
+fn split_room(title: &str, rest: &str, room: usize) -> (String, String) {
+    let title = truncate(title, room.saturating_sub(rest.width()).max(room / 2));
+    let rest = truncate(rest, room.saturating_sub(title.width()));
+    (title, rest)
+}
+
+/// `width` is the list's area
+pub fn render_library_hits(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let room = row_room(width, theme.marker(), 0);
+    let items = app
+        .library_hits
+        .iter()
+        .map(|hit| {
+            let (title, source) = split_room(&hit.title, &format!(" — {}", hit.source), room);
+            ListItem::new(Line::from(vec![
+                Span::styled(title, Style::default().fg(theme.primary)),
+                Span::styled(source, Style::default().fg(theme.primary).add_modifier(Modifier::DIM)),
+            ]))
+        })
+        .collect();
+    styled_list(theme, format!("LIBRARY ({} found)", app.library_hits.len()), items)
+}
+
+// The selected row's title scrolls when it doesn't fit in what's left of the row
+fn row_title(app: &App, title: &str, selected: bool, width: u16, used: usize) -> String {
+    row_text(title, row_room(width, app.theme.marker(), used), selected, app.tick)
+}
+
+/// `width` is the list's area, for scrolling the selected title
+pub fn render_list(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let podcasts = &app.podcasts;
+    let (title, items): (String, Vec<ListItem>) = match podcasts.opened() {
+        None if podcasts.favorites_open => (
+            "★ FAVORITES".to_string(),
+            app.favorites
+                .items
+                .iter()
+                .enumerate()
+                .map(|(i, favorite)| {
+                    let selected = podcasts.episode_state.selected() == Some(i);
+                    let title = row_title(app, &favorite.title, selected, width, 2);
+                    ListItem::new(Span::styled(format!("★ {}", title), Style::default().fg(theme.primary)))
+                })
+                .collect(),
+        ),
+        Some(feed) => (
+            feed.title.to_uppercase(),
+            feed.episodes
+                .iter()
+                .enumerate()
+                .map(|(i, episode)| {
+                    let duration = episode
+                        .duration
+                        .map(|d| format_time(Duration::from_secs_f64(d.max(0.0))))
+                        .unwrap_or_else(|| "--:--".to_string());
+                    let style = if episode.played {
+                        Style::default().fg(theme.primary).add_modifier(Modifier::DIM)
+                    } else {
+                        Style::default().fg(theme.primary)
+                    };
+                    let published = episode.published.as_ref().map(|p| format!("  ({})", p)).unwrap_or_default();
+                    let selected = podcasts.episode_state.selected() == Some(i);
+                    // Status marks and duration take 11 cells before the title
+                    let title = row_title(app, &episode.title, selected, width, 11 + published.width());
+                    ListItem::new(Line::from(vec![
+                        Span::styled(if episode.played { "  " } else { "● " }, Style::default().fg(theme.accent)),
+                        Span::styled(if app.favorites.is_favorite(&episode.url) { "★ " } else { "  " }, Style::default().fg(theme.accent)),
+                        Span::styled(format!("{:>6} ", duration), Style::default().fg(theme.accent)),
+                        Span::styled(title, style),
+                        Span::styled(published, Style::default().fg(theme.primary).add_modifier(Modifier::DIM)),
+                    ]))
+                })
+                .collect(),
+        ),
+        None => {
+            let favorites = ListItem::new(Line::from(vec![
+                Span::styled("★ Favorites", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)),
+                Span::styled(format!(" ({})", app.favorites.items.len()), Style::default().fg(theme.primary).add_modifier(Modifier::DIM)),
+            ]));
+            let feeds = podcasts.feeds.iter().map(|feed| {
+                let unplayed = feed.episodes.iter().filter(|e| !e.played).count();
+                let summary = format!(" ({} · {})", feed.episodes.len(), format_total_duration(feed.total_duration()));
+                let new = format!("  [{} new]", unplayed);
+                let (title, summary) = split_room(&feed.title, &summary, row_room(width, theme.marker(), new.width()));
+                ListItem::new(Line::from(vec![
+                    Span::styled(title, Style::default().fg(theme.primary)),
+                    Span::styled(summary, Style::default().fg(theme.primary).add_modifier(Modifier::DIM)),
+                    Span::styled(new, Style::default().fg(theme.accent)),
+                ]))
+            });
+            ("FEEDS".to_string(), std::iter::once(favorites).chain(feeds).collect())
+        },
+    };
+
+    styled_list(theme, title, items)
+}
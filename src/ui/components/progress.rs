@@ -1,39 +1,101 @@
 use std::time::Duration;
 use ratatui::{
+    layout::Rect,
     style::Style,
-    widgets::{Block, Borders, Gauge},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, Paragraph},
 };
-use crate::ui::theme::{PIPBOY_BG, PIPBOY_DARK, PIPBOY_GREEN};
+use crate::audio::player::PlaybackState;
+use crate::format::format_time;
+use crate::ui::theme::Theme;
+use unicode_width::UnicodeWidthStr;
 
-fn format_time(duration: Duration) -> String {
-    let seconds = duration.as_secs();
-    let minutes = seconds / 60;
-    let seconds = seconds % 60;
-    format!("{:02}:{:02}", minutes, seconds)
+// Tagged artist/title of the loaded file, else the title we picked it by
+fn now_playing(app: &crate::app::state::App) -> Option<String> {
+    app.player
+        .now_playing
+        .as_ref()
+        .and_then(|tags| tags.display())
+        .or_else(|| app.current_track.as_ref().map(|t| t.title.clone()))
 }
 
-pub fn render(app: &crate::app::state::App) -> Gauge<'_> {
-    let mut ratio = 0.0;
-    let mut label = String::from("00:00 / 00:00");
+/// "01:23 / 04:56", or "-03:33 / 04:56" counting down. Time left stops at zero when playback
+/// runs past the length, and `estimated` lengths (guessed from the file size) get a "~".
+pub fn time_label(elapsed: Duration, total: Duration, remaining: bool, estimated: bool) -> String {
+    let total_text = format!("{}{}", if estimated { "~" } else { "" }, format_time(total));
+    if remaining {
+        format!("-{} / {}", format_time(total.saturating_sub(elapsed)), total_text)
+    } else {
+        format!("{} / {}", format_time(elapsed), total_text)
+    }
+}
+
+fn label(app: &crate::app::state::App) -> String {
+    match (app.player.state(), app.player.total_duration) {
+        (PlaybackState::Loading, _) => String::from("LOADING..."),
+        (PlaybackState::Errored(_), _) => String::new(),
+        // Using the new helper from AudioPlayer to get accurate sync time (handles pause)
+        (_, Some(total)) => time_label(app.player.get_current_time(), total, app.show_remaining, app.player.duration_estimated),
+        _ => String::from("00:00 / 00:00"),
+    }
+}
 
-    // Using the new helper from AudioPlayer to get accurate sync time (handles pause)
-    if let Some(total) = app.player.total_duration {
-        let elapsed = app.player.get_current_time();
-        let total_secs = total.as_secs_f64();
+/// Where the gauge in `area` draws its label, for clicks that flip it between played and left
+pub fn label_area(app: &crate::app::state::App, area: Rect) -> Rect {
+    let width = (label(app).width() as u16).min(area.width.saturating_sub(2));
+    let inner_height = area.height.saturating_sub(2);
+    Rect::new(area.x + 1 + (area.width.saturating_sub(2) - width) / 2, area.y + 1 + inner_height.saturating_sub(1) / 2, width, inner_height.min(1))
+}
 
-        if total_secs > 0.0 {
-            ratio = (elapsed.as_secs_f64() / total_secs).min(1.0);
-        }
-        label = format!("{} / {}", format_time(elapsed), format_time(total));
+pub fn render(app: &crate::app::state::App, theme: &Theme) -> Gauge<'static> {
+    let mut ratio = 0.0;
+    let state = app.player.state();
+    if !matches!(state, PlaybackState::Loading | PlaybackState::Errored(_))
+        && let Some(total) = app.player.total_duration
+        && total.as_secs_f64() > 0.0 {
+        ratio = (app.player.get_current_time().as_secs_f64() / total.as_secs_f64()).min(1.0);
     }
+    let label = label(app);
 
     Gauge::default()
         .block(Block::default()
             .borders(Borders::ALL)
-            .title("PROGRESS")
-            .border_style(Style::default().fg(PIPBOY_GREEN))
-            .style(Style::default().bg(PIPBOY_BG)))
-        .gauge_style(Style::default().fg(PIPBOY_GREEN).bg(PIPBOY_DARK))
+            .title(match now_playing(app) {
+                Some(name) => format!("PROGRESS [{}] {}", state.label(), name),
+                None => format!("PROGRESS [{}]", state.label()),
+            })
+            .border_style(Style::default().fg(theme.primary))
+            .style(Style::default().bg(theme.bg)))
+        .gauge_style(Style::default().fg(theme.primary).bg(theme.dim))
         .ratio(ratio)
         .label(label)
 }
+
+/// One-line "CH 3/12: Title" readout shown under the gauge for tracks with chapters
+pub fn render_chapter(app: &crate::app::state::App, theme: &Theme) -> Paragraph<'static> {
+    let chapters = app.current_chapters();
+    let text = match app.current_chapter_index() {
+        Some(i) => format!(" CH {}/{}: {}  [,/.] SKIP", i + 1, chapters.len(), chapters[i].title),
+        None => format!(" {} CHAPTERS  [,/.] SKIP", chapters.len()),
+    };
+    Paragraph::new(Line::from(Span::styled(text, Style::default().fg(theme.accent))))
+        .style(Style::default().bg(theme.bg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_up_or_down() {
+        let (elapsed, total) = (Duration::from_secs(83), Duration::from_secs(296));
+        assert_eq!(time_label(elapsed, total, false, false), "01:23 / 04:56");
+        assert_eq!(time_label(elapsed, total, true, false), "-03:33 / 04:56");
+    }
+
+    #[test]
+    fn a_short_guess_stops_at_zero_and_says_so() {
+        let (elapsed, total) = (Duration::from_secs(310), Duration::from_secs(296));
+        assert_eq!(time_label(elapsed, total, true, true), "-00:00 / ~04:56");
+    }
+}
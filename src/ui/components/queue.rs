@@ -0,0 +1,59 @@
+use std::time::Duration;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+use crate::app::state::App;
+use crate::ui::text::{row_room, row_text};
+use crate::ui::theme::Theme;
+use crate::format::format_time;
+
+// The duration column and its gap
+const DURATION_CELLS: usize = 7;
+
+/// The next few queue entries under the RADIO tab's controls; U moves the keys here.
+/// `width` is the list's area.
+pub fn render_up_next(app: &App, theme: &Theme, width: u16) -> List<'static> {
+    let upcoming = app.up_next();
+    let selected = app.up_next_state.selected().filter(|_| app.queue_focused);
+    let items: Vec<ListItem> = if upcoming.is_empty() {
+        vec![ListItem::new("  queue empty").style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM))]
+    } else {
+        upcoming
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let duration = entry.duration.map(|d| format_time(Duration::from_secs_f64(d.max(0.0)))).unwrap_or_else(|| "--:--".to_string());
+                let title = row_text(&entry.title, row_room(width, theme.marker(), DURATION_CELLS), selected == Some(i), app.tick);
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:>6} ", duration), Style::default().fg(theme.accent)),
+                    Span::styled(title, Style::default().fg(theme.primary)),
+                ]))
+            })
+            .collect()
+    };
+
+    let remaining = app.play_queue.len().saturating_sub(app.queue_index.map_or(app.play_queue.len(), |i| i + 1));
+    let title = if app.queue_focused {
+        format!("UP NEXT ({}) [Enter] jump [D] remove [U] back", remaining)
+    } else {
+        format!("UP NEXT ({}) [U] focus", remaining)
+    };
+    let (border, highlight) = if app.queue_focused {
+        (Style::default().fg(theme.accent), theme.highlight())
+    } else {
+        (Style::default().fg(theme.primary), Style::default())
+    };
+
+    List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(border)
+                .style(Style::default().bg(theme.bg)),
+        )
+        .highlight_style(highlight)
+        .highlight_symbol(if app.queue_focused { theme.marker() } else { "   " })
+}
@@ -1,28 +1,129 @@
 use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
     style::Style,
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Paragraph},
 };
-use crate::ui::theme::{PIPBOY_BG, PIPBOY_GREEN, COLOR_YELLOW};
+use crate::audio::player::PlaybackState;
+use crate::scope::channels::channel_label;
+use crate::scope::display::spectroscope::FLOOR_DB;
+use crate::scope::style::dim_color;
+use crate::ui::theme::Theme;
 
-pub fn render_controls(app: &crate::app::state::App) -> Paragraph<'static> {
+pub fn render_controls(app: &crate::app::state::App, theme: &Theme) -> Paragraph<'static> {
     let vol_percent = (app.player.volume * 100.0) as u32;
+    let pause_label = match app.player.state() {
+        PlaybackState::Paused => "RESUME",
+        _ => "PAUSE",
+    };
+    let trigger = if app.oscilloscope.triggering {
+        format!("   [T] TRIGGER {}  [Alt+↑↓] LEVEL  [E] EDGE", app.oscilloscope.trigger_label())
+    } else {
+        "   [T] TRIGGER OFF".to_string()
+    };
     let mut controls = vec![
-        Line::from(Span::styled("   [Shift+Arrows] ZOOM/WIDTH", Style::default().fg(PIPBOY_GREEN))),
-        Line::from(Span::styled("   [S] SCATTER  [T] TRIGGER", Style::default().fg(PIPBOY_GREEN))),
-        Line::from(Span::styled(format!("   [Space] PAUSE  [+/-] VOL: {}%", vol_percent), Style::default().fg(PIPBOY_GREEN))),
+        Line::from(Span::styled("   [Shift+Arrows] ZOOM/WIDTH", Style::default().fg(theme.primary))),
+        Line::from(Span::styled(format!("   [S] SCATTER  [V] VIEW  [W] TRAIL {}  [M] {}", app.graph_config.persistence, if app.graph_config.mid_side { "M/S" } else { "L/R" }), Style::default().fg(theme.primary))),
+        Line::from(Span::styled(
+            format!(
+                "   [B] {}  [G] REF {}  [U] LABELS {}  {:.1} kHz",
+                if app.graph_config.marker_type == Marker::Braille { "BRAILLE" } else { "DOTS" },
+                if app.graph_config.references { "ON" } else { "OFF" },
+                if app.graph_config.show_ui { "ON" } else { "OFF" },
+                app.graph_config.sampling_rate as f64 / 1000.0,
+            ),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(Span::styled(trigger, Style::default().fg(theme.primary))),
+        Line::from(Span::styled(format!("   [Space] {}  [X] STOP", pause_label), Style::default().fg(theme.primary))),
+        Line::from(Span::styled(format!("   [+/-] VOL: {}%", vol_percent), Style::default().fg(theme.primary))),
     ];
 
-    if app.player.is_streaming_mode {
-        controls.insert(0, Line::from(Span::styled("   [!] OPTIMIZED MODE (NO SCOPE)", Style::default().fg(COLOR_YELLOW))));
+    // Gains are applied before mid/side, so they're always on L and R
+    let gains: String = app.processing.gains().map(|(n, db)| format!("  {} {:+} dB", channel_label(n, false), db)).collect();
+    controls.push(Line::from(Span::styled(
+        format!("   [D] DC {}  [[ ]] [{{ }}] GAIN{}", if app.processing.dc_block { "BLOCK" } else { "OFF" }, gains),
+        Style::default().fg(theme.primary),
+    )));
+    let pitch = match &app.pitch.reading {
+        Some(pitch) => format!("   PITCH {:<3} {:>7.1} Hz  {:+.0}¢", pitch.note(), pitch.frequency, pitch.cents),
+        None => "   PITCH —".to_string(),
+    };
+    controls.push(Line::from(Span::styled(pitch, Style::default().fg(theme.accent))));
+    if let Some(channels) = app.channel_filter.describe(|n| channel_label(n, app.graph_config.mid_side)) {
+        controls.push(Line::from(Span::styled(format!("   {}", channels), Style::default().fg(theme.accent))));
+    }
+    if let Some(mic) = &app.mic {
+        let recording = match mic.recording_for() {
+            Some(elapsed) => format!("● REC {}", crate::format::format_time(elapsed)),
+            None => "[R] RECORD".to_string(),
+        };
+        controls.insert(0, Line::from(Span::styled(format!("   [M] MIC ON  {}", recording), Style::default().fg(theme.warning))));
+    } else if app.player.is_remote_stream {
+        controls.insert(0, Line::from(Span::styled("   [!] LIVE STREAM (NO SCOPE/SEEK)", Style::default().fg(theme.warning))));
+    } else if app.player.is_streaming_mode {
+        controls.insert(0, Line::from(Span::styled("   [!] OPTIMIZED MODE (NO SCOPE)", Style::default().fg(theme.warning))));
     }
 
     Paragraph::new(controls)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(PIPBOY_GREEN))
-                .style(Style::default().bg(PIPBOY_BG))
-                .title("SCOPE CTRL"),
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg))
+                .title(format!("SCOPE CTRL [{}]", app.player.state().label())),
+        )
+}
+
+/// One bar per band, dB above the floor
+pub fn render_spectrum(levels: &[f64], log_scale: bool, theme: &Theme) -> BarChart<'static> {
+    let bars: Vec<Bar> = levels
+        .iter()
+        .map(|db| Bar::default().value((db - FLOOR_DB).round() as u64).text_value(String::new()))
+        .collect();
+    BarChart::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(if log_scale { "SPECTRUM [LOG]" } else { "SPECTRUM [LIN]" })
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg)),
         )
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(theme.primary))
+        .max(-FLOOR_DB as u64)
+}
+
+/// A dim mark over each of `render_spectrum`'s bars at its held peak, drawn once the chart is
+pub fn render_peak_marks(buf: &mut Buffer, area: Rect, held: &[f64], theme: &Theme) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    if inner.is_empty() {
+        return;
+    }
+    let style = Style::default().fg(dim_color(theme.accent, 0.6));
+    for (column, db) in held.iter().enumerate().take(inner.width as usize) {
+        let filled = ((db - FLOOR_DB) / -FLOOR_DB * inner.height as f64) as u16;
+        let y = inner.bottom().saturating_sub(filled + 1).max(inner.y);
+        buf.set_string(inner.x + column as u16, y, "─", style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_marks_sit_above_their_level() {
+        let area = Rect::new(0, 0, 5, 6);
+        let mut buf = Buffer::empty(area);
+        render_peak_marks(&mut buf, area, &[0.0, FLOOR_DB, FLOOR_DB / 2.0], &Theme::by_name("green"));
+        let column = |x: u16| (1..5).find(|y| buf[(x, *y)].symbol() == "─");
+        assert_eq!(column(1), Some(1));
+        assert_eq!(column(2), Some(4));
+        assert_eq!(column(3), Some(2));
+    }
 }
@@ -1,12 +1,24 @@
 use ratatui::{
+    layout::Constraint,
     style::{Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Cell, HighlightSpacing, LineGauge, Paragraph, Row, Table},
 };
 use crate::app::state::{App, InputMode};
-use crate::ui::theme::{PIPBOY_BG, PIPBOY_GREEN, COLOR_YELLOW, PIPBOY_DARK};
+use std::time::Duration;
+use crate::audio::stream::is_offline;
+use crate::ui::text::{row_room, row_text, truncate};
+use crate::format::format_time;
+use super::spinner;
+use crate::format::format_bytes;
+use crate::ui::theme::Theme;
+use crate::ui::util::input_spans;
 
-pub fn render_input(app: &App) -> Paragraph<'_> {
+/// `width` is the box's; a long query scrolls to keep the cursor in view.
+/// While typing, also returns the cursor's column inside the box.
+pub fn render_input<'a>(app: &'a App, theme: &Theme, width: u16) -> (Paragraph<'a>, Option<u16>) {
+    let mut cursor = None;
     let (msg, style) = match app.input_mode {
         InputMode::Normal => (
             vec![
@@ -14,65 +26,158 @@ pub fn render_input(app: &App) -> Paragraph<'_> {
                 Span::styled("/", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" to search audio..."),
             ],
-            Style::default().fg(PIPBOY_GREEN),
-        ),
-        InputMode::Editing => (
-            vec![
-                Span::raw("> "),
-                Span::styled(&app.search_input, Style::default().fg(COLOR_YELLOW)),
-                Span::styled("█", Style::default().fg(PIPBOY_GREEN).add_modifier(Modifier::SLOW_BLINK)),
-            ],
-            Style::default().fg(COLOR_YELLOW),
+            Style::default().fg(theme.primary),
         ),
+        InputMode::Editing => {
+            let tag = Span::styled(format!("[{}] ", app.search_provider.tag()), Style::default().fg(theme.primary));
+            let room = width.saturating_sub(2 + tag.width() as u16);
+            let (input, column) = input_spans("> ", &app.search_input, room, theme);
+            cursor = Some(tag.width() as u16 + column);
+            let mut spans = vec![tag];
+            spans.extend(input);
+            (spans, Style::default().fg(theme.accent))
+        },
         InputMode::SearchResults => (
             vec![
-                Span::raw("Select a track and press Enter to play. Esc to cancel."),
+                Span::raw("Enter to play, S to stream. F favorite. D/T sort by duration/title, H hide <1:00. Esc to cancel."),
             ],
-            Style::default().fg(PIPBOY_GREEN),
+            Style::default().fg(theme.primary),
+        ),
+        // Inputs that belong to other tabs; this one just shows where it left off
+        _ => (
+            vec![Span::styled(
+                app.last_search.as_ref().map(|q| format!("Last search: {}", q)).unwrap_or_default(),
+                Style::default().add_modifier(Modifier::DIM),
+            )],
+            Style::default().fg(theme.primary),
         ),
     };
 
-    let mut text = vec![Line::from(msg)];
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("SEARCH QUERY [{}] (Tab: provider)", app.search_provider.name()))
+        .border_style(style)
+        .style(Style::default().bg(theme.bg));
+    // The download gauge below says it better
+    if app.download.is_none()
+        && let Some(spinner) = spinner::render(app, theme) {
+        block = block.title_top(Line::from(spinner).right_aligned());
+    }
 
-    if let Some(status) = &app.loading_status {
-        text.push(Line::from(Span::styled(format!("[STATUS]: {}", status), Style::default().fg(PIPBOY_GREEN))));
+    (Paragraph::new(Line::from(msg)).block(block), cursor)
+}
+
+/// "Title  62%  1.2 MB/s  1 of 3" then a thin bar, while the chosen result downloads.
+/// `width` is the line's; the title gets what the numbers and a short bar leave.
+pub fn render_download(app: &App, theme: &Theme, width: u16) -> Option<LineGauge<'static>> {
+    let download = app.download.as_ref()?;
+    let ratio = download.ratio();
+    let mut numbers = match ratio {
+        Some(ratio) => format!(" {:>3}%", (ratio * 100.0) as u64),
+        None => format!(" {}", format_bytes(download.downloaded)),
+    };
+    if let Some(speed) = download.speed() {
+        numbers.push_str(&format!("  {}/s", format_bytes(speed)));
+    }
+    let queued = app.downloads_queued();
+    if queued > 1 {
+        numbers.push_str(&format!("  1 of {}", queued));
     }
+    numbers.push(' ');
+    let room = (width as usize).saturating_sub(numbers.len() + width as usize / 4 + 1);
 
-    Paragraph::new(text)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("SEARCH QUERY")
-                .border_style(style)
-                .style(Style::default().bg(PIPBOY_BG)),
-        )
+    Some(
+        LineGauge::default()
+            .ratio(ratio.unwrap_or(0.0))
+            .label(Line::from(vec![
+                Span::styled(format!(" {}", truncate(&download.title, room)), Style::default().fg(theme.primary)),
+                Span::styled(numbers, Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)),
+            ]))
+            .filled_symbol(symbols::line::THICK_HORIZONTAL)
+            .filled_style(Style::default().fg(theme.accent))
+            .unfilled_style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM))
+            .style(Style::default().bg(theme.bg)),
+    )
 }
 
-pub fn render_results(search_results: &Vec<(String, String)>, input_mode: &InputMode) -> List<'static> {
-    let items: Vec<ListItem> = search_results
+// Below this the uploader column is dropped so titles keep some room
+const NARROW_WIDTH: u16 = 60;
+const UPLOADER_WIDTH: u16 = 20;
+const DURATION_WIDTH: u16 = 7;
+
+/// `width` is the table's area; the selected row's title scrolls when it doesn't fit
+pub fn render_results(app: &App, theme: &Theme, width: u16) -> Table<'static> {
+    let selected = app.search_results_state.selected();
+    let playing = app.playing_url();
+    let show_uploader = width >= NARROW_WIDTH;
+    // Beside the duration and uploader columns, each with a gap before it
+    let columns = DURATION_WIDTH + 1 + if show_uploader { UPLOADER_WIDTH + 1 } else { 0 };
+    let title_width = row_room(width, theme.marker(), columns as usize);
+
+    let rows: Vec<Row> = app
+        .search_results
         .iter()
-        .map(|(title, _url)| {
-            ListItem::new(vec![Line::from(Span::styled(title.clone(), Style::default().fg(PIPBOY_GREEN)))])
+        .enumerate()
+        .map(|(i, result)| {
+            // Offline, anything not cached can't be played
+            let unavailable = is_offline() && !result.cached;
+            let duration = result.duration.map(|d| format_time(Duration::from_secs_f64(d.max(0.0)))).unwrap_or_else(|| "--:--".to_string());
+            let star = if app.favorites.is_favorite(&result.url) { "★ " } else { "  " };
+            let is_playing = playing == Some(result.url.as_str());
+            let marker = if is_playing { "♪ " } else { "" };
+            let title = row_text(&result.title, title_width.saturating_sub(if is_playing { 4 } else { 2 }), selected == Some(i), app.tick);
+            let title_style = if is_playing {
+                Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.primary)
+            };
+            let mut cells = vec![Cell::from(Line::from(vec![
+                Span::styled(star, Style::default().fg(theme.accent)),
+                Span::styled(marker, title_style),
+                Span::styled(title, title_style),
+            ]))];
+            if show_uploader {
+                let uploader = truncate(result.uploader.as_deref().unwrap_or(""), UPLOADER_WIDTH as usize);
+                cells.push(Cell::from(uploader).style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM)));
+            }
+            cells.push(Cell::from(Line::from(duration).right_aligned()).style(Style::default().fg(theme.accent)));
+            let row = Row::new(cells);
+            if unavailable {
+                row.style(Style::default().add_modifier(Modifier::DIM | Modifier::CROSSED_OUT))
+            } else {
+                row
+            }
         })
         .collect();
 
-    List::new(items)
+    let (header, widths) = if show_uploader {
+        (
+            Row::new(["Title", "Uploader", "Duration"]),
+            vec![Constraint::Fill(1), Constraint::Length(UPLOADER_WIDTH), Constraint::Length(DURATION_WIDTH)],
+        )
+    } else {
+        (Row::new(["Title", "Duration"]), vec![Constraint::Fill(1), Constraint::Length(DURATION_WIDTH)])
+    };
+
+    let mut title = format!("RESULTS [{}] (sort: {})", app.results_provider.tag(), app.results_sort.name());
+    if app.hide_short_results {
+        title.push_str(" [hiding <1:00]");
+    }
+
+    Table::new(rows, widths)
+        .header(header.style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD | Modifier::UNDERLINED)))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("RESULTS")
-                .border_style(if matches!(input_mode, InputMode::SearchResults) {
-                    Style::default().fg(COLOR_YELLOW)
+                .title(title)
+                .border_style(if matches!(app.input_mode, InputMode::SearchResults) {
+                    Style::default().fg(theme.accent)
                 } else {
-                    Style::default().fg(PIPBOY_GREEN)
+                    Style::default().fg(theme.primary)
                 })
-                .style(Style::default().bg(PIPBOY_BG)),
-        )
-        .highlight_style(
-            Style::default()
-                .bg(PIPBOY_GREEN)
-                .fg(PIPBOY_DARK)
-                .add_modifier(Modifier::BOLD),
+                .style(Style::default().bg(theme.bg)),
         )
-        .highlight_symbol(">> ")
+        .row_highlight_style(theme.highlight())
+        .highlight_symbol(theme.marker())
+        .highlight_spacing(HighlightSpacing::Always)
 }
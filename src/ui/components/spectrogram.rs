@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Widget},
+};
+use crate::scope::display::spectroscope::{band_of, FFT_SIZE, FLOOR_DB};
+use crate::ui::theme::Theme;
+
+// Below this share of the range above the floor a line stays dark
+const QUIET: f64 = 0.3;
+// Where the frequency labels go along the bottom
+const LABELS: [(f64, &str); 3] = [(100.0, "100"), (1_000.0, "1k"), (10_000.0, "10k")];
+
+/// The waterfall: one history line per half cell, newest at the bottom, louder bands in hotter colors
+pub struct SpectrogramView<'a> {
+    pub lines: &'a VecDeque<Vec<f64>>, // Oldest first
+    pub sample_rate: u32,
+    pub log_scale: bool,
+    pub theme: &'a Theme,
+}
+
+impl SpectrogramView<'_> {
+    /// How many bands and history lines fit in `area`, borders included
+    pub fn capacity(area: Rect) -> (usize, usize) {
+        (area.width.saturating_sub(2).max(1) as usize, area.height.saturating_sub(2) as usize * 2)
+    }
+
+    // None for quiet, else a step up the theme's ramp
+    fn heat(&self, db: f64) -> Option<Color> {
+        let ramp: &[Color] = if self.theme.mono { &[Color::Reset] } else { &[self.theme.primary, self.theme.accent, self.theme.error] };
+        let level = (db - FLOOR_DB) / -FLOOR_DB;
+        (level >= QUIET).then(|| ramp[(((level - QUIET) / (1.0 - QUIET)) * ramp.len() as f64).min(ramp.len() as f64 - 1.0) as usize])
+    }
+}
+
+impl Widget for SpectrogramView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(if self.log_scale { "WATERFALL [LOG]" } else { "WATERFALL [LIN]" })
+            .border_style(Style::default().fg(self.theme.primary))
+            .style(Style::default().bg(self.theme.bg));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.is_empty() {
+            return;
+        }
+
+        // The bottom row of cells holds the newest two lines; missing history stays dark
+        let rows = inner.height as usize;
+        let line = |index: usize, column: usize| -> Option<Color> {
+            let from_end = rows * 2 - index;
+            let line = self.lines.len().checked_sub(from_end).map(|i| &self.lines[i])?;
+            line.get(column).and_then(|db| self.heat(*db))
+        };
+        for row in 0..rows {
+            for column in 0..inner.width as usize {
+                let (symbol, fg, bg) = match (line(row * 2, column), line(row * 2 + 1, column)) {
+                    (None, None) => (" ", self.theme.bg, self.theme.bg),
+                    (Some(top), None) => ("▀", top, self.theme.bg),
+                    (None, Some(bottom)) => ("▄", bottom, self.theme.bg),
+                    (Some(top), Some(bottom)) if top == bottom => ("█", top, self.theme.bg),
+                    (Some(top), Some(bottom)) => ("▀", top, bottom),
+                };
+                if let Some(cell) = buf.cell_mut((inner.x + column as u16, inner.y + row as u16)) {
+                    cell.set_symbol(symbol).set_fg(fg).set_bg(bg);
+                }
+            }
+        }
+
+        // Frequency marks on the bottom border, under the bands they name
+        let bands = inner.width as usize;
+        for (hz, label) in LABELS {
+            if let Some(band) = band_of(hz, bands, FFT_SIZE, self.sample_rate, self.log_scale)
+                && band + label.len() <= bands
+            {
+                buf.set_string(inner.x + band as u16, area.bottom() - 1, label, Style::default().fg(self.theme.accent));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_lines_fill_the_bottom_row() {
+        let theme = Theme::by_name("green");
+        let (loud, middling, quiet) = (0.0, FLOOR_DB * 0.4, FLOOR_DB);
+        let lines = VecDeque::from([vec![loud, quiet], vec![quiet, middling], vec![loud, loud]]);
+        let area = Rect::new(0, 0, 4, 4);
+        let mut buf = Buffer::empty(area);
+        SpectrogramView { lines: &lines, sample_rate: 44_100, log_scale: true, theme: &theme }.render(area, &mut buf);
+        // Two rows hold four lines and there are only three, so the top half of the top row is dark
+        assert_eq!(buf[(1, 1)].symbol(), "▄");
+        assert_eq!(buf[(2, 1)].symbol(), " ");
+        assert_eq!(buf[(1, 2)].symbol(), "▄");
+        assert_eq!(buf[(2, 2)].symbol(), "▀");
+        assert_eq!((buf[(2, 2)].fg, buf[(2, 2)].bg), (theme.accent, theme.error));
+    }
+}
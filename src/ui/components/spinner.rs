@@ -0,0 +1,55 @@
+use ratatui::{
+    style::Style,
+    text::Span,
+};
+use crate::app::state::App;
+use crate::app::status::StatusLevel;
+use crate::ui::theme::Theme;
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+// Frames drawn per spinner step; the loop draws about every 16ms
+const TICKS_PER_FRAME: u64 = 5;
+
+pub fn frame(tick: u64) -> &'static str {
+    FRAMES[(tick / TICKS_PER_FRAME) as usize % FRAMES.len()]
+}
+
+/// "⠹ Downloading: 3.1 MB / 5.0 MB (62%)" while something is loading
+pub fn render(app: &App, theme: &Theme) -> Option<Span<'static>> {
+    if !app.busy() {
+        return None;
+    }
+    // What's going on is whatever last reported progress
+    let task = app.status.log.iter().rev().find(|m| m.level == StatusLevel::Progress).map(|m| m.text.as_str()).unwrap_or("Loading...");
+    let percent = percent(app.download.as_ref().and_then(|d| d.ratio()));
+    Some(Span::styled(format!(" {} {}{} ", frame(app.tick), task, percent), Style::default().fg(theme.accent)))
+}
+
+/// " (62%)", never past 100 even if a server sends more than it said it would; nothing without a size
+fn percent(ratio: Option<f64>) -> String {
+    match ratio {
+        Some(ratio) => format!(" ({}%)", (ratio.clamp(0.0, 1.0) * 100.0) as u64),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_advance_every_few_ticks_and_wrap() {
+        assert_eq!(frame(0), FRAMES[0]);
+        assert_eq!(frame(TICKS_PER_FRAME - 1), FRAMES[0]);
+        assert_eq!(frame(TICKS_PER_FRAME), FRAMES[1]);
+        assert_eq!(frame(TICKS_PER_FRAME * FRAMES.len() as u64), FRAMES[0]);
+    }
+
+    #[test]
+    fn percent_stays_within_bounds() {
+        assert_eq!(percent(Some(0.625)), " (62%)");
+        assert_eq!(percent(Some(1.7)), " (100%)");
+        assert_eq!(percent(Some(-0.1)), " (0%)");
+        assert_eq!(percent(None), "");
+    }
+}
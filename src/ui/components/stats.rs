@@ -0,0 +1,117 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::path::Path;
+use crate::app::history::{now_secs, DAY_SECS};
+use crate::app::podcasts::format_total_duration;
+use crate::app::state::App;
+use crate::audio::stream::CookieSource;
+use crate::format::format_bytes;
+use crate::ui::theme::Theme;
+
+pub fn render(app: &App, theme: &Theme) -> Paragraph<'static> {
+    let usage = app.cache_usage;
+    let limit = app.settings.cache_max_bytes();
+    let queue = app.prefetch.stats();
+
+    let now = now_secs();
+    let listened = |since: u64| format_total_duration(app.history.listened_since(since) as f64);
+    let episodes: usize = app.podcasts.feeds.iter().map(|f| f.episodes.len()).sum();
+    let player = &app.player;
+
+    let lines = vec![
+        Line::from(Span::styled("LISTENING", Style::default().fg(theme.primary).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!(
+                "   Last 24h: {}  Last 7 days: {}  ({} plays in all)",
+                listened(now.saturating_sub(DAY_SECS)),
+                listened(now.saturating_sub(7 * DAY_SECS)),
+                app.history.plays.len()
+            ),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(""),
+        Line::from(Span::styled("LIBRARY", Style::default().fg(theme.primary).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!(
+                "   {} feeds ({} episodes), {} favorites, {} stations",
+                app.podcasts.feeds.len(),
+                episodes,
+                app.favorites.items.len(),
+                app.radio_stations.len()
+            ),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(""),
+        Line::from(Span::styled("CACHE", Style::default().fg(theme.primary).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!("   {} / {} ({} files)", format_bytes(usage.bytes), format_bytes(limit), usage.files),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(Span::styled("   [C] CLEAR CACHE  [V] CHECK LIBRARY", Style::default().fg(theme.accent))),
+        Line::from(""),
+        Line::from(Span::styled("AUDIO", Style::default().fg(theme.primary).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!(
+                "   Output: {} @ {:.1} kHz, {} ch",
+                player.device_name.as_deref().unwrap_or("none"),
+                player.sample_rate as f64 / 1000.0,
+                player.channels
+            ),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(Span::styled(
+            format!("   Download quality: {}", app.settings.quality().name()),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(Span::styled(
+            match app.current_bitrate_kbps() {
+                Some(kbps) => format!("   Now playing: ~{} kbps", kbps),
+                None => "   Now playing: --".to_string(),
+            },
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(Span::styled("   [B] CHANGE QUALITY", Style::default().fg(theme.accent))),
+        Line::from(Span::styled(
+            format!("   [K] SPONSORBLOCK: {}", if app.settings.sponsorblock { "ON" } else { "OFF" }),
+            Style::default().fg(theme.accent),
+        )),
+        Line::from(Span::styled(format!("   [T] THEME: {}", theme.name.to_uppercase()), Style::default().fg(theme.accent))),
+        Line::from(""),
+        Line::from(Span::styled("PREFETCH", Style::default().fg(theme.primary).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!("   {} downloading, {} queued", queue.in_progress, queue.pending),
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(Span::styled(
+            match app.queue_index {
+                Some(i) => format!("   Play queue: track {} of {}", i + 1, app.play_queue.len()),
+                None => "   Play queue: empty".to_string(),
+            },
+            Style::default().fg(theme.primary),
+        )),
+        Line::from(""),
+        Line::from(Span::styled("COOKIES", Style::default().fg(theme.primary).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            match &app.cookies {
+                Some(source @ CookieSource::File(path)) if !Path::new(path).is_file() => {
+                    format!("   {} (MISSING)", source.describe())
+                },
+                Some(source) => format!("   {}", source.describe()),
+                None => "   NOT CONFIGURED (age-restricted tracks will fail)".to_string(),
+            },
+            Style::default().fg(theme.primary),
+        )),
+    ];
+
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("STATS")
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg)),
+        )
+}
@@ -1,17 +1,84 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    widgets::Chart,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    widgets::{Paragraph, Wrap},
     Frame,
 };
-use crate::app::state::App;
-use crate::scope::display::{DisplayMode, Dimension};
-use crate::ui::theme::{PIPBOY_BG, PIPBOY_GREEN};
+use crate::app::history::HistoryFocus;
+use crate::app::mouse::Region;
+use crate::app::state::{App, InputMode, ScopeView, UP_NEXT_LEN};
+use crate::app::tab::Tab;
+use crate::scope::display::{spectroscope::FFT_SIZE, DisplayMode};
+use crate::scope::widget::ScopeWidget;
 use ratatui::widgets::{Block, Borders};
 use ratatui::style::Style;
+use ratatui::symbols::Marker;
 
 use super::components;
+use super::components::artwork::ArtworkView;
+use super::components::spectrogram::SpectrogramView;
+use crate::format::format_time;
+use super::theme::Theme;
+use super::util::{place_cursor, render_scrollbar};
+
+// Height of the RADIO tab's cover, borders included
+const ARTWORK_ROWS: u16 = 8;
+// Smallest terminal the interface is drawn in at all, and the size everything fits in
+const MIN_SIZE: (u16, u16) = (60, 18);
+const FULL_SIZE: (u16, u16) = (90, 24);
+
+/// The RADIO tab's optional panels that fit in `area`. Below FULL_SIZE they go in a fixed
+/// order: up next first, then the cover, then the scope controls.
+#[derive(Debug, PartialEq)]
+struct RadioPanels {
+    up_next: bool,
+    artwork: bool,
+    controls: bool,
+}
+
+impl RadioPanels {
+    fn fitting(area: Rect) -> Self {
+        RadioPanels {
+            up_next: area.width >= FULL_SIZE.0 && area.height >= FULL_SIZE.1,
+            artwork: area.width >= 75 && area.height >= 21,
+            controls: area.height >= 20,
+        }
+    }
+}
+
+fn too_small(area: Rect) -> bool {
+    area.width < MIN_SIZE.0 || area.height < MIN_SIZE.1
+}
+
+/// Just a note to make the window bigger, in the middle of the screen
+fn draw_too_small(f: &mut Frame, theme: &Theme) {
+    let area = f.area();
+    let message = format!(
+        "Terminal too small (need {}x{}, have {}x{})",
+        MIN_SIZE.0, MIN_SIZE.1, area.width, area.height
+    );
+    f.render_widget(Block::default().style(Style::default().bg(theme.bg)), area);
+    // Room to wrap onto three lines when it's narrower than the message
+    let rows = 3.min(area.height);
+    let middle = Rect::new(area.x, area.y + (area.height - rows) / 2, area.width, rows);
+    let paragraph = Paragraph::new(message)
+        .style(Style::default().fg(theme.accent).bg(theme.bg))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, middle);
+}
 
 pub fn draw(f: &mut Frame, app: &mut App) {
+    // A copy, so it can be handed out while app is borrowed for list state
+    let theme = app.theme;
+    // Interactive widgets register where they end up as they're drawn
+    app.hit_regions.clear();
+
+    // Anything smaller squeezes panels to nothing; the next frame after a resize draws it all again
+    if too_small(f.area()) {
+        draw_too_small(f, &theme);
+        return;
+    }
+
     // Main layout
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -23,85 +90,328 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         .split(f.area());
 
     // Header
-    f.render_widget(components::header::render(app), chunks[0]);
-
-    if app.current_tab == 2 {
-        // DATA Tab - Search Interface
-        let content_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3), // Search Input
-                Constraint::Min(0),    // Results List
-            ])
-            .split(chunks[1]);
-
-        f.render_widget(components::search::render_input(app), content_chunks[0]);
-
-        // Render results list statefully - Passing fields instead of full app to fix borrow error
-        let results_widget = components::search::render_results(&app.search_results, &app.input_mode);
-        f.render_stateful_widget(
-            results_widget,
-            content_chunks[1],
-            &mut app.search_results_state
-        );
-
-    } else {
-        // RADIO Tab (Default Layout)
-        let content_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(65),  // Left panel (radio list)
-                Constraint::Percentage(35),  // Right panel (waveform + controls)
-            ])
-            .split(chunks[1]);
-
-        // Playlist
-        let playlist_widget = components::playlist::render(&app.radio_stations);
-        f.render_stateful_widget(
-            playlist_widget,
-            content_chunks[0],
-            &mut app.radio_state
-        );
-
-        // Right panel
-        let right_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(50),  // Waveform
-                Constraint::Percentage(25),  // Progress
-                Constraint::Percentage(25),  // Controls
-            ])
-            .split(content_chunks[1]);
-
-        // Oscilloscope (Inline generation because of borrow checker issues with Chart data)
-        let window_size = app.graph_config.samples as usize;
-        let data = app.player.get_window(window_size);
-        let datasets_data = app.oscilloscope.process(&app.graph_config, &data);
-
-        let ratatui_datasets: Vec<ratatui::widgets::Dataset> = datasets_data
-            .iter()
-            .map(|ds| ds.into())
-            .collect();
-
-        let chart = Chart::new(ratatui_datasets)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(PIPBOY_GREEN))
-                    .style(Style::default().bg(PIPBOY_BG)),
-            )
-            .x_axis(app.oscilloscope.axis(&app.graph_config, Dimension::X))
-            .y_axis(app.oscilloscope.axis(&app.graph_config, Dimension::Y));
-
-        f.render_widget(chart, right_chunks[0]);
-
-        // Progress Bar
-        f.render_widget(components::progress::render(app), right_chunks[1]);
-
-        // Controls
-        f.render_widget(components::scope_view::render_controls(app), right_chunks[2]);
+    components::header::render(f, chunks[0], app, &theme);
+
+    match app.current_tab {
+        Tab::Data => {
+            // DATA Tab - Search Interface
+            let content_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3), // Search Input
+                    Constraint::Length(if app.download.is_some() { 1 } else { 0 }), // Download gauge
+                    Constraint::Min(0),    // Results List
+                ])
+                .split(chunks[1]);
+
+            let (input, cursor) = components::search::render_input(app, &theme, content_chunks[0].width);
+            f.render_widget(input, content_chunks[0]);
+            if let Some(column) = cursor {
+                place_cursor(f, content_chunks[0], column);
+            }
+            if let Some(gauge) = components::search::render_download(app, &theme, content_chunks[1].width) {
+                f.render_widget(gauge, content_chunks[1]);
+            }
+
+            // Render results table statefully - Passing fields instead of full app to fix borrow error
+            let results_widget = components::search::render_results(app, &theme, content_chunks[2].width);
+            f.render_stateful_widget(
+                results_widget,
+                content_chunks[2],
+                &mut app.search_results_state
+            );
+            app.hit_regions.add_list(Region::Results, content_chunks[2], 1, app.search_results_state.offset());
+            render_scrollbar(f, content_chunks[2], 1, app.search_results.len(), app.search_results_state.selected(), &theme);
+        },
+        Tab::Inv => {
+            // INV Tab - Podcasts
+            let content_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(0)])
+                .split(chunks[1]);
+
+            let (input, cursor) = components::podcasts::render_input(app, &theme, content_chunks[0].width);
+            f.render_widget(input, content_chunks[0]);
+            if let Some(column) = cursor {
+                place_cursor(f, content_chunks[0], column);
+            }
+            if matches!(app.input_mode, InputMode::LibrarySearch) {
+                let hits = components::podcasts::render_library_hits(app, &theme, content_chunks[1].width);
+                f.render_stateful_widget(hits, content_chunks[1], &mut app.library_state);
+                app.hit_regions.add_list(Region::LibraryHits, content_chunks[1], 0, app.library_state.offset());
+                render_scrollbar(f, content_chunks[1], 0, app.library_hits.len(), app.library_state.selected(), &theme);
+            } else {
+                let list = components::podcasts::render_list(app, &theme, content_chunks[1].width);
+                let state = if app.podcasts.is_open() {
+                    &mut app.podcasts.episode_state
+                } else {
+                    &mut app.podcasts.feed_state
+                };
+                f.render_stateful_widget(list, content_chunks[1], state);
+                let (offset, selected) = (state.offset(), state.selected());
+                app.hit_regions.add_list(Region::Podcasts, content_chunks[1], 0, offset);
+                let len = app.podcasts.showing_len(app.favorites.items.len());
+                render_scrollbar(f, content_chunks[1], 0, len, selected, &theme);
+            }
+        },
+        Tab::Stat => {
+            // STAT Tab - settings on the left, listening history on the right
+            let content_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(chunks[1]);
+            let history_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)])
+                .split(content_chunks[1]);
+
+            f.render_widget(components::stats::render(app, &theme), content_chunks[0]);
+            let recent = components::history::render_recent(app, &theme, history_chunks[0].width);
+            f.render_stateful_widget(recent, history_chunks[0], &mut app.history.recent_state);
+            app.hit_regions.add_list(Region::History(HistoryFocus::Recent), history_chunks[0], 0, app.history.recent_state.offset());
+            render_scrollbar(f, history_chunks[0], 0, app.history.list_len(HistoryFocus::Recent), app.history.recent_state.selected(), &theme);
+            let top = components::history::render_top(app, &theme, history_chunks[1].width);
+            f.render_stateful_widget(top, history_chunks[1], &mut app.history.top_state);
+            app.hit_regions.add_list(Region::History(HistoryFocus::Top), history_chunks[1], 0, app.history.top_state.offset());
+            render_scrollbar(f, history_chunks[1], 0, app.history.list_len(HistoryFocus::Top), app.history.top_state.selected(), &theme);
+            let artists = components::history::render_artists(app, &theme, history_chunks[2].width);
+            f.render_stateful_widget(artists, history_chunks[2], &mut app.history.artist_state);
+            app.hit_regions.add_list(Region::History(HistoryFocus::Artists), history_chunks[2], 0, app.history.artist_state.offset());
+            render_scrollbar(f, history_chunks[2], 0, app.history.list_len(HistoryFocus::Artists), app.history.artist_state.selected(), &theme);
+        },
+        Tab::Map => {
+            // MAP Tab - local files, with the filter underneath while there is one
+            let area = if app.file_browser.filter.is_some() {
+                let filter_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(chunks[1]);
+                let (input, column) = components::files::render_filter(app, &theme, filter_chunks[1].width);
+                f.render_widget(input, filter_chunks[1]);
+                if matches!(app.input_mode, InputMode::FileFilter) {
+                    place_cursor(f, filter_chunks[1], column);
+                }
+                filter_chunks[0]
+            } else {
+                chunks[1]
+            };
+            let rows = area.height.saturating_sub(2) as usize;
+            let width = area.width.saturating_sub(7) as usize; // Borders, highlight and icon
+            let (list, mut state) = components::files::render(app, &theme, rows, width);
+            f.render_stateful_widget(list, area, &mut state);
+            app.hit_regions.add_list(Region::Files, area, 0, app.file_browser.offset());
+            render_scrollbar(f, area, 0, app.file_browser.row_count(), app.file_browser.list_state.selected(), &theme);
+        },
+        Tab::Radio => {
+            // RADIO Tab (Default Layout)
+            let content_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(65),  // Left panel (radio list)
+                    Constraint::Percentage(35),  // Right panel (waveform + controls)
+                ])
+                .split(chunks[1]);
+
+            // Stations, with the add/edit prompt underneath while it's open
+            let list_area = if matches!(app.input_mode, InputMode::StationName | InputMode::StationUrl) {
+                let station_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(content_chunks[0]);
+                let (input, column) = components::playlist::render_input(app, &theme, station_chunks[1].width);
+                f.render_widget(input, station_chunks[1]);
+                place_cursor(f, station_chunks[1], column);
+                station_chunks[0]
+            } else {
+                content_chunks[0]
+            };
+            let playlist_widget = components::playlist::render(app, &theme, list_area.width);
+            f.render_stateful_widget(
+                playlist_widget,
+                list_area,
+                &mut app.radio_state
+            );
+            app.hit_regions.add_list(Region::Stations, list_area, 0, app.radio_state.offset());
+            render_scrollbar(f, list_area, 0, app.radio_stations.len(), app.radio_state.selected(), &theme);
+
+            // Right panel, with whatever's optional left out when the terminal is small
+            let panels = RadioPanels::fitting(f.area());
+            let right_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),          // Waveform
+                    Constraint::Length(if panels.artwork { ARTWORK_ROWS } else { 4 }), // Cover and now playing
+                    Constraint::Percentage(25),  // Progress
+                    Constraint::Percentage(if panels.controls { 25 } else { 0 }), // Controls
+                    Constraint::Length(if panels.up_next { UP_NEXT_LEN as u16 + 2 } else { 0 }), // Up next
+                ])
+                .split(content_chunks[1]);
+
+            let vectors = app.scope_view == ScopeView::Vectors;
+            let mut chart_block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.primary))
+                .style(Style::default().bg(theme.bg));
+            if let Some(mic) = &app.mic {
+                chart_block = chart_block.title(match mic.recording_for() {
+                    Some(elapsed) => format!("MIC ● REC {}", format_time(elapsed)),
+                    None => "MIC".to_string(),
+                });
+            }
+            if vectors {
+                // As of the last frame drawn; close enough at the frame rate
+                let correlation = app.vectorscope.correlation.map_or("--".to_string(), |c| format!("{:+.2}", c));
+                chart_block = chart_block.title(format!("VECTORS  CORR {}", correlation));
+            }
+
+            let (wave_area, spectrum_area) = match app.scope_view {
+                ScopeView::Waveform | ScopeView::Vectors => (Some(right_chunks[0]), None),
+                ScopeView::Waterfall => (None, None),
+                ScopeView::Spectrum => (None, Some(right_chunks[0])),
+                ScopeView::Both => {
+                    let halves = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(right_chunks[0]);
+                    (Some(halves[0]), Some(halves[1]))
+                }
+            };
+            if let Some(area) = wave_area {
+                let window_size = if vectors { app.graph_config.samples as usize } else { app.oscilloscope.window_len(app.graph_config.samples) };
+                app.fill_scope_window(window_size);
+                // Braille has two dots to a cell, inside the block's borders
+                let dots = if app.graph_config.marker_type == Marker::Braille { 2 } else { 1 };
+                app.update_draw_config(area.width.saturating_sub(2) as u32 * dots);
+                let mode: &mut dyn DisplayMode = if vectors { &mut app.vectorscope } else { &mut app.oscilloscope };
+                let mut scope = ScopeWidget::new(&app.draw_config, mode, &app.scope_data).block(chart_block).square(vectors);
+                // The waveform's trails go underneath it, fading with age; monochrome has nothing to fade
+                if !vectors && !theme.mono {
+                    scope = scope.persistence(&mut app.persistence);
+                }
+                f.render_widget(scope, area);
+            }
+            if let Some(area) = spectrum_area {
+                // One band per cell, up to 60
+                let bands = (area.width.saturating_sub(2) as usize).clamp(1, 60);
+                app.fill_scope_window(FFT_SIZE);
+                let log_scale = app.spectroscope.log_scale;
+                let levels = app.spectroscope.process(&app.scope_data, app.player.sample_rate, bands);
+                f.render_widget(components::scope_view::render_spectrum(levels, log_scale, &theme), area);
+                if app.spectroscope.peak_hold {
+                    components::scope_view::render_peak_marks(f.buffer_mut(), area, app.spectroscope.hold.held(), &theme);
+                }
+            }
+
+            if app.scope_view == ScopeView::Waterfall {
+                let area = right_chunks[0];
+                let (bands, height) = SpectrogramView::capacity(area);
+                // Paused, the picture holds still
+                if app.player.scope_live() {
+                    app.fill_scope_window(FFT_SIZE);
+                    let levels = app.spectroscope.snapshot(&app.scope_data, app.player.sample_rate, bands);
+                    app.spectrogram.push(levels, height);
+                }
+                f.render_widget(SpectrogramView { lines: app.spectrogram.lines(), sample_rate: app.player.sample_rate, log_scale: app.spectroscope.log_scale, theme: &theme }, area);
+            }
+
+            // Cells are about twice as tall as wide, so twice the columns makes the cover square
+            let now_playing_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(if panels.artwork { ARTWORK_ROWS * 2 } else { 0 }), Constraint::Min(0)])
+                .split(right_chunks[1]);
+            if panels.artwork {
+                f.render_widget(ArtworkView { artwork: app.artwork.as_ref(), theme: &theme }, now_playing_chunks[0]);
+            }
+            f.render_widget(components::now_playing::render(app, &theme, now_playing_chunks[1].width), now_playing_chunks[1]);
+
+            // Progress Bar (with the current chapter underneath when the track has chapters)
+            if app.current_chapters().is_empty() {
+                f.render_widget(components::progress::render(app, &theme), right_chunks[2]);
+                app.hit_regions.add(Region::Progress, right_chunks[2]);
+                app.hit_regions.add(Region::ProgressLabel, components::progress::label_area(app, right_chunks[2]));
+            } else {
+                let progress_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(3), Constraint::Length(1)])
+                    .split(right_chunks[2]);
+                f.render_widget(components::progress::render(app, &theme), progress_chunks[0]);
+                app.hit_regions.add(Region::Progress, progress_chunks[0]);
+                app.hit_regions.add(Region::ProgressLabel, components::progress::label_area(app, progress_chunks[0]));
+                f.render_widget(components::progress::render_chapter(app, &theme), progress_chunks[1]);
+            }
+
+            // Controls
+            if panels.controls {
+                f.render_widget(components::scope_view::render_controls(app, &theme), right_chunks[3]);
+            }
+
+            // Up next
+            if panels.up_next {
+                app.clamp_up_next();
+                let up_next = components::queue::render_up_next(app, &theme, right_chunks[4].width);
+                f.render_stateful_widget(up_next, right_chunks[4], &mut app.up_next_state);
+                app.hit_regions.add_list(Region::UpNext, right_chunks[4], 0, app.up_next_state.offset());
+                render_scrollbar(f, right_chunks[4], 0, app.up_next().len(), app.up_next_state.selected(), &theme);
+            }
+        },
     }
 
     // Footer
-    f.render_widget(components::footer::render(app), chunks[2]);
+    f.render_widget(components::footer::render(app, &theme), chunks[2]);
+
+    if matches!(app.input_mode, InputMode::Confirm) {
+        components::confirm::render(f, app, &theme);
+    }
+    components::details::render(f, app, &theme);
+    if app.show_help {
+        components::help::render(f, app, &theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn screen(width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let theme = Theme::by_name("green");
+        terminal.draw(|f| draw_too_small(f, &theme)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect()
+    }
+
+    #[test]
+    fn guards_below_the_minimum_only() {
+        assert!(too_small(Rect::new(0, 0, 59, 24)));
+        assert!(too_small(Rect::new(0, 0, 120, 17)));
+        assert!(!too_small(Rect::new(0, 0, 60, 18)));
+    }
+
+    #[test]
+    fn too_small_message_sits_in_the_middle() {
+        let lines = screen(59, 17);
+        assert_eq!(lines[7].trim(), "Terminal too small (need 60x18, have 59x17)");
+        assert!(lines[7].starts_with("        T"));
+        assert!(lines.iter().enumerate().all(|(y, line)| y == 7 || line.trim().is_empty()));
+    }
+
+    #[test]
+    fn too_small_message_wraps_when_narrow() {
+        let lines = screen(20, 6);
+        let text: Vec<&str> = lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty()).collect();
+        assert_eq!(text, ["Terminal too small", "(need 60x18, have", "20x6)"]);
+    }
+
+    #[test]
+    fn panels_drop_in_order_as_the_terminal_shrinks() {
+        let all = RadioPanels { up_next: true, artwork: true, controls: true };
+        assert_eq!(RadioPanels::fitting(Rect::new(0, 0, 90, 24)), all);
+        assert_eq!(RadioPanels::fitting(Rect::new(0, 0, 200, 60)), all);
+        assert_eq!(RadioPanels::fitting(Rect::new(0, 0, 80, 24)), RadioPanels { up_next: false, ..all });
+        assert_eq!(RadioPanels::fitting(Rect::new(0, 0, 90, 20)), RadioPanels { up_next: false, artwork: false, controls: true });
+        assert_eq!(RadioPanels::fitting(Rect::new(0, 0, 60, 18)), RadioPanels { up_next: false, artwork: false, controls: false });
+    }
 }
@@ -1,10 +1,24 @@
 pub mod theme;
 pub mod components {
+    pub mod artwork;
+    pub mod confirm;
+    pub mod details;
+    pub mod files;
     pub mod header;
+    pub mod help;
+    pub mod history;
+    pub mod now_playing;
     pub mod playlist;
+    pub mod podcasts;
     pub mod scope_view;
+    pub mod spectrogram;
     pub mod progress;
+    pub mod queue;
     pub mod footer;
     pub mod search;
+    pub mod spinner;
+    pub mod stats;
 }
 pub mod layout;
+pub mod text;
+pub mod util;
@@ -0,0 +1,195 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Frames per cell of marquee scroll, and frames to hold at each end
+const MARQUEE_STEP: u64 = 8;
+const MARQUEE_PAUSE: u64 = 60;
+
+/// Cut `text` to at most `width` terminal cells, ending in "…" when anything was dropped.
+/// Counts cells rather than chars so wide (CJK, emoji) titles don't overflow.
+pub fn truncate(text: &str, width: usize) -> String {
+    let cells: usize = text.chars().map(|c| c.width().unwrap_or(0)).sum();
+    if cells <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width - 1 {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+/// `text` right-aligned in `width` cells, cut to fit if it's longer
+pub fn align_right(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    format!("{}{}", " ".repeat(width.saturating_sub(text.width())), text)
+}
+
+/// A list row's text in `width` cells: the selected row scrolls through it, the others are cut with "…"
+pub fn row_text(text: &str, width: usize, selected: bool, tick: u64) -> String {
+    if selected {
+        marquee(text, width, tick)
+    } else {
+        truncate(text, width)
+    }
+}
+
+/// Cells left for a row's own text in a bordered list `width` wide, once `used` cells of other
+/// columns are taken. Borders take one cell each side, and every row is indented by the width of
+/// the list's highlight symbol `marker`, selected or not.
+pub fn row_room(width: u16, marker: &str, used: usize) -> usize {
+    (width as usize).saturating_sub(2 + marker.width() + used)
+}
+
+/// `text` broken into lines of at most `width` cells, at spaces where it can and mid-word where it
+/// can't (URLs and paths have none). Always at least one line.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let room = width.saturating_sub(line.width() + if line.is_empty() { 0 } else { 1 });
+        if word.width() <= room {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            // A char wider than the whole line still gets one to itself, never an empty line
+            if !line.is_empty() && line.width() + c.width().unwrap_or(0) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// The part of `text` that's visible `tick` frames into scrolling it through `width` cells:
+/// holds at the start, moves left a cell every few frames, holds at the end, then starts over.
+/// Text that fits is returned as is; otherwise the result is always exactly `width` cells,
+/// with a space standing in for a wide char cut in half at the left edge.
+pub fn marquee(text: &str, width: usize, tick: u64) -> String {
+    let overflow = text.width().saturating_sub(width);
+    if overflow == 0 {
+        return text.to_string();
+    }
+    let cycle = 2 * MARQUEE_PAUSE + overflow as u64 * MARQUEE_STEP;
+    let t = tick % cycle;
+    let offset = (t.saturating_sub(MARQUEE_PAUSE) / MARQUEE_STEP).min(overflow as u64) as usize;
+
+    let mut out = String::new();
+    let mut skipped = 0;
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if skipped < offset {
+            skipped += w;
+            // Half of this one would still be showing
+            if skipped > offset {
+                out.push(' ');
+                used += 1;
+            }
+            continue;
+        }
+        if used + w > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push_str(&" ".repeat(width - used));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_chars_are_never_cut_in_half() {
+        // Each of these is two cells wide
+        assert_eq!(truncate("東京事変", 6), "東京…");
+        assert_eq!(truncate("東京事変", 5), "東京…");
+        assert_eq!(truncate("🎵🎶🎵🎶", 4), "🎵…");
+        assert_eq!(row_text("빅뱅 - 거짓말", 8, false, 0), "빅뱅 - …");
+        assert!(row_text("빅뱅 - 거짓말", 8, true, 0).width() <= 8);
+    }
+
+    #[test]
+    fn rows_make_room_for_borders_and_the_marker() {
+        assert_eq!(row_room(20, ">> ", 0), 15);
+        assert_eq!(row_room(20, "▮ ", 2), 14);
+        assert_eq!(row_room(4, ">> ", 0), 0);
+    }
+
+    #[test]
+    fn aligns_by_cells_not_chars() {
+        assert_eq!(align_right("曲", 4), "  曲");
+        assert_eq!(align_right("Plays", 7), "  Plays");
+        assert_eq!(align_right("ミュージック", 5), "ミュ…");
+    }
+
+    #[test]
+    fn wraps_at_spaces_then_anywhere() {
+        assert_eq!(wrap("Never Gonna Give You Up", 11), ["Never Gonna", "Give You Up"]);
+        assert_eq!(wrap("https://example.com/x", 8), ["https://", "example.", "com/x"]);
+        assert_eq!(wrap("", 8), [""]);
+    }
+
+    #[test]
+    fn wraps_chars_wider_than_the_line_one_per_line() {
+        assert_eq!(wrap("東京", 1), ["東", "京"]);
+        assert_eq!(wrap("a 東b", 1), ["a", "東", "b"]);
+    }
+
+    #[test]
+    fn leaves_short_text_alone() {
+        assert_eq!(truncate("Song", 10), "Song");
+        assert_eq!(truncate("Song", 4), "Song");
+    }
+
+    #[test]
+    fn truncates_by_cell_width() {
+        assert_eq!(truncate("Long title", 5), "Long…");
+        // Each of these takes two cells
+        assert_eq!(truncate("日本語の歌", 6), "日本…");
+        assert_eq!(truncate("abc", 0), "");
+    }
+
+    #[test]
+    fn marquee_holds_then_scrolls_to_the_end() {
+        assert_eq!(marquee("short", 10, 500), "short");
+        assert_eq!(marquee("abcdefgh", 5, 0), "abcde");
+        assert_eq!(marquee("abcdefgh", 5, MARQUEE_PAUSE - 1), "abcde");
+        assert_eq!(marquee("abcdefgh", 5, MARQUEE_PAUSE + MARQUEE_STEP), "bcdef");
+        // Stops at the end, then wraps back to the start
+        assert_eq!(marquee("abcdefgh", 5, MARQUEE_PAUSE + 100 * MARQUEE_STEP), "defgh");
+        let cycle = 2 * MARQUEE_PAUSE + 3 * MARQUEE_STEP;
+        assert_eq!(marquee("abcdefgh", 5, cycle), "abcde");
+    }
+
+    #[test]
+    fn marquee_keeps_wide_text_the_same_width() {
+        let text = "日本語の歌です";
+        for tick in 0..400 {
+            assert_eq!(marquee(text, 7, tick).width(), 7, "tick {}", tick);
+        }
+        assert_eq!(marquee(text, 7, MARQUEE_PAUSE + MARQUEE_STEP), " 本語の");
+    }
+}
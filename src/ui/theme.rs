@@ -1,8 +1,163 @@
-use ratatui::style::Color;
-
-// Use standard ANSI colors to respect the user's terminal theme
-pub const PIPBOY_GREEN: Color = Color::Green;
-pub const PIPBOY_DARK: Color = Color::Black;
-pub const PIPBOY_BG: Color = Color::Reset; // Allows transparency/background of terminal
-pub const COLOR_YELLOW: Color = Color::Yellow;
-pub const COLOR_RED: Color = Color::Red;
+use ratatui::style::{Color, Modifier, Style};
+
+// The colors every component draws with. Components get one of these passed in
+// rather than reaching for constants, so it can change at runtime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub primary: Color, // Text and borders
+    pub accent: Color,  // Keys, values, focused borders
+    pub warning: Color,
+    pub error: Color,
+    pub bg: Color,
+    pub dim: Color, // Text on a highlighted row, the empty part of gauges
+    pub mono: bool, // No colors at all; selection is shown with bold and a marker
+}
+
+// Green uses standard ANSI colors to respect the user's terminal theme;
+// Reset lets the terminal's own background show through
+pub const THEMES: [Theme; 4] = [
+    Theme {
+        name: "green",
+        primary: Color::Green,
+        accent: Color::Yellow,
+        warning: Color::Yellow,
+        error: Color::Red,
+        bg: Color::Reset,
+        dim: Color::Black,
+        mono: false,
+    },
+    Theme {
+        name: "amber",
+        primary: Color::Rgb(255, 176, 0),
+        accent: Color::Rgb(255, 224, 140),
+        warning: Color::Rgb(255, 110, 0),
+        error: Color::Red,
+        bg: Color::Reset,
+        dim: Color::Black,
+        mono: false,
+    },
+    Theme {
+        name: "blue",
+        primary: Color::LightBlue,
+        accent: Color::Cyan,
+        warning: Color::Yellow,
+        error: Color::Red,
+        bg: Color::Reset,
+        dim: Color::Black,
+        mono: false,
+    },
+    Theme {
+        name: "white",
+        primary: Color::White,
+        accent: Color::LightYellow,
+        warning: Color::LightYellow,
+        error: Color::LightRed,
+        bg: Color::Black,
+        dim: Color::Black,
+        mono: false,
+    },
+];
+
+// For terminals without colors (or people who'd rather not): everything in the terminal's own
+// foreground and background
+pub const MONO: Theme = Theme {
+    name: "mono",
+    primary: Color::Reset,
+    accent: Color::Reset,
+    warning: Color::Reset,
+    error: Color::Reset,
+    bg: Color::Reset,
+    dim: Color::Reset,
+    mono: true,
+};
+
+// Colors a station can be marked with, by the names saved for them. Only the standard ANSI
+// ones, so they follow the terminal's own palette like the green theme does.
+pub const NAMED_COLORS: [(&str, Color); 12] = [
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("lightred", Color::LightRed),
+    ("lightgreen", Color::LightGreen),
+    ("lightyellow", Color::LightYellow),
+    ("lightblue", Color::LightBlue),
+    ("lightmagenta", Color::LightMagenta),
+    ("lightcyan", Color::LightCyan),
+];
+
+/// The color saved as `name`, None for names this version doesn't know
+pub fn color_by_name(name: &str) -> Option<Color> {
+    NAMED_COLORS.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, color)| *color)
+}
+
+pub fn color_name(color: Color) -> Option<&'static str> {
+    NAMED_COLORS.iter().find(|(_, c)| *c == color).map(|(name, _)| *name)
+}
+
+/// The color after `color` in NAMED_COLORS: the first when there's none, none after the last
+pub fn next_named_color(color: Option<Color>) -> Option<Color> {
+    match color.and_then(|color| NAMED_COLORS.iter().position(|(_, c)| *c == color)) {
+        Some(i) => NAMED_COLORS.get(i + 1).map(|(_, color)| *color),
+        None => Some(NAMED_COLORS[0].1),
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0]
+    }
+}
+
+impl Theme {
+    /// The preset called `name`, green for anything unknown
+    pub fn by_name(name: &str) -> Theme {
+        THEMES.iter().find(|t| t.name.eq_ignore_ascii_case(name)).copied().unwrap_or_default()
+    }
+
+    /// The selected row of a focused list
+    pub fn highlight(&self) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        if self.mono {
+            style
+        } else {
+            style.bg(self.primary).fg(self.dim)
+        }
+    }
+
+    /// What goes left of the selected row, as wide as the blank unfocused lists use
+    pub fn marker(&self) -> &'static str {
+        if self.mono { "▶  " } else { ">> " }
+    }
+
+    pub fn next(&self) -> Theme {
+        let i = THEMES.iter().position(|t| t.name == self.name).unwrap_or(0);
+        THEMES[(i + 1) % THEMES.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_names_round_trip() {
+        for (name, color) in NAMED_COLORS {
+            assert_eq!(color_by_name(name), Some(color));
+            assert_eq!(color_name(color), Some(name));
+        }
+        assert_eq!(color_by_name("LightBlue"), Some(Color::LightBlue));
+        assert_eq!(color_by_name("chartreuse"), None);
+        assert_eq!(color_name(Color::Rgb(1, 2, 3)), None);
+    }
+
+    #[test]
+    fn cycling_passes_through_no_color() {
+        assert_eq!(next_named_color(None), Some(Color::Red));
+        assert_eq!(next_named_color(Some(Color::Red)), Some(Color::Green));
+        assert_eq!(next_named_color(Some(Color::LightCyan)), None);
+    }
+}
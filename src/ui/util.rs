@@ -0,0 +1,103 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::Span,
+    widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+use crate::app::input::TextInput;
+use crate::ui::theme::Theme;
+
+/// A rectangle `percent_x` by `percent_y` of `area`, in its middle
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+/// A `width` x `height` rectangle in the middle of `area`, shrunk to fit if it has to be
+pub fn centered_box(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// A text prompt: `label`, then the input scrolled so its cursor shows within `width` cells
+/// (the whole line, label included). Also returns the cursor's column in the line, for `place_cursor`.
+pub fn input_spans(label: &'static str, input: &TextInput, width: u16, theme: &Theme) -> (Vec<Span<'static>>, u16) {
+    let room = (width as usize).saturating_sub(label.width());
+    let (before, under, after) = input.visible(room);
+    let column = label.width() + before.width();
+    let rest: String = under.into_iter().chain(after.chars()).collect();
+    let spans = vec![
+        Span::raw(label),
+        Span::styled(before.to_string(), Style::default().fg(theme.accent)),
+        Span::styled(rest, Style::default().fg(theme.accent)),
+    ];
+    (spans, column as u16)
+}
+
+/// A scrollbar down the right border of the bordered list in `area`, whose first `header` rows
+/// aren't entries. Only drawn when the `len` entries don't all fit; the thumb follows the selection.
+pub fn render_scrollbar(f: &mut Frame, area: Rect, header: u16, len: usize, selected: Option<usize>, theme: &Theme) {
+    let rows = area.height.saturating_sub(2 + header);
+    if len <= rows as usize || area.width < 2 {
+        return;
+    }
+    let track = Rect::new(area.right() - 1, area.y + 1 + header, 1, rows);
+    let mut state = ScrollbarState::new(len).viewport_content_length(rows as usize).position(selected.unwrap_or(0));
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_symbol(Some(symbols::line::VERTICAL))
+        .style(Style::default().fg(theme.primary).add_modifier(Modifier::DIM));
+    f.render_stateful_widget(scrollbar, track, &mut state);
+}
+
+/// Put the terminal's cursor `column` cells into the first line inside the bordered `area`
+pub fn place_cursor(f: &mut Frame, area: Rect, column: u16) {
+    let x = (area.x + 1 + column).min(area.right().saturating_sub(2));
+    f.set_cursor_position((x, area.y + 1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    // The right border column of a 10x8 box, top to bottom
+    fn right_edge(len: usize, selected: Option<usize>) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(10, 8)).unwrap();
+        let theme = Theme::by_name("green");
+        terminal.draw(|f| render_scrollbar(f, f.area(), 0, len, selected, &theme)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..8).map(|y| buffer[(9, y)].symbol()).collect()
+    }
+
+    #[test]
+    fn scrollbar_only_when_the_list_overflows() {
+        assert_eq!(right_edge(6, Some(5)), "        ");
+        assert_eq!(right_edge(60, Some(0)), " █│││││ ");
+        assert_eq!(right_edge(60, Some(59)), " │││││█ ");
+    }
+}
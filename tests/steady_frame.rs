@@ -0,0 +1,117 @@
+//! Once its buffers have grown, a frame of the scope shouldn't touch the allocator.
+//! This lives in its own test binary so the counting allocator doesn't wrap every other test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use ratatui::style::Color;
+use sound_cows::scope::channels::{mid_side, ChannelFilter};
+use sound_cows::scope::display::oscilloscope::Oscilloscope;
+use sound_cows::scope::display::persistence::Persistence;
+use sound_cows::scope::display::spectrogram::Spectrogram;
+use sound_cows::scope::display::spectroscope::Spectroscope;
+use sound_cows::scope::display::{DisplayMode, GraphConfig};
+use sound_cows::scope::processing::Processing;
+use sound_cows::scope::Matrix;
+
+// Counts each thread's allocations, so tests running alongside don't show up in one another's
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static COUNTING: Counting = Counting;
+
+// Allocations made by `f` on this thread
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn input() -> Matrix<f64> {
+    [0.8, -0.4].iter().map(|gain| (0..8192).map(|i| (i as f64 * 0.05).sin() * gain).collect()).collect()
+}
+
+// The newest `len` samples of `input`, in place of what was there
+fn copy_newest(window: &mut Matrix<f64>, input: &Matrix<f64>, len: usize) {
+    window.resize_with(input.len(), Vec::new);
+    for (channel, samples) in window.iter_mut().zip(input) {
+        channel.clear();
+        channel.extend_from_slice(&samples[samples.len() - len..]);
+    }
+}
+
+#[test]
+fn a_steady_oscilloscope_frame_does_not_allocate() {
+    let input = input();
+    let mut processing = Processing::default();
+    processing.dc_block = true;
+    processing.step_gain(1, true);
+    let cfg = GraphConfig {
+        samples: 2000,
+        columns: 160,
+        references: true,
+        mid_side: true,
+        persistence: 4,
+        palette: vec![Color::White],
+        ..Default::default()
+    };
+    let mut scope = Oscilloscope::default();
+    scope.triggering = true;
+    scope.peaks = true;
+    let mut persistence = Persistence::default();
+    let mut window = Vec::new();
+    let mut sets = 0;
+    // From the newest samples to datasets and their trail, as the RADIO tab does each frame
+    let mut frame = || {
+        copy_newest(&mut window, &input, scope.window_len(cfg.samples));
+        processing.apply(&mut window, 44_100);
+        mid_side(&mut window);
+        ChannelFilter::default().apply(&mut window);
+        let datasets = scope.process(&cfg, &window);
+        sets = datasets.len();
+        persistence.push(datasets, cfg.persistence as usize);
+    };
+    // Until the trail's ring is full, each frame adds to it
+    for _ in 0..=cfg.persistence {
+        frame();
+    }
+    assert_eq!(allocations(&mut frame), 0);
+    // Reference, trigger level, and two peak lines and a trace a channel
+    assert_eq!(sets, 8);
+}
+
+#[test]
+fn a_steady_spectrum_frame_does_not_allocate() {
+    let input = input();
+    let mut spectroscope = Spectroscope::default();
+    spectroscope.peak_hold = true;
+    let mut spectrogram = Spectrogram::default();
+    let mut window = Vec::new();
+    let mut frame = || {
+        copy_newest(&mut window, &input, 2048);
+        let levels = spectroscope.process(&window, 44_100, 64);
+        spectrogram.push(levels, 20);
+        spectroscope.snapshot(&window, 44_100, 64).len()
+    };
+    for _ in 0..20 {
+        frame();
+    }
+    let mut bands = 0;
+    assert_eq!(allocations(|| bands = frame()), 0);
+    assert_eq!(bands, 64);
+}